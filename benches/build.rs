@@ -0,0 +1,108 @@
+//! Throughput of `DepTree::build` over synthetic projects, every asset importing `FAN_OUT` new ones
+//! until the project has the wanted amount of assets.
+//!
+//! The sizes can be set with `UASSET_HELPER_BENCH_SIZES`, e.g. `UASSET_HELPER_BENCH_SIZES=100,10000 cargo bench`
+
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tempfile::TempDir;
+use uasset_helper::{
+    dependency_tree::{TraversalOrder, UNLIMITED_RECURSE_DEPTH},
+    AssetDirs, DepTree,
+};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::PackageWriter;
+
+/// Amount of the assets each asset imports
+const FAN_OUT: usize = 4;
+const DEFAULT_SIZES: [usize; 3] = [10, 100, 1000];
+
+/// Project with `size` assets in a tree, `Asset_0` is the root
+struct Fixture {
+    _root: TempDir,
+    asset_dirs: AssetDirs,
+}
+
+impl Fixture {
+    fn new(size: usize) -> Self {
+        let root = tempfile::tempdir().unwrap();
+        let assets_dir = root.path().join("Project/Content/Bench");
+        std::fs::create_dir_all(&assets_dir).unwrap();
+
+        for index in 0..size {
+            let dependency_names = (index * FAN_OUT + 1..(index + 1) * FAN_OUT + 1)
+                .filter(|&dependency_index| dependency_index < size)
+                .map(|dependency_index| format!("/Game/Bench/Asset_{}", dependency_index))
+                .collect::<Vec<_>>();
+
+            std::fs::write(
+                Self::asset_path(&assets_dir, index),
+                PackageWriter::package(&dependency_names),
+            )
+            .unwrap();
+        }
+
+        Self {
+            asset_dirs: AssetDirs::new(Some(Self::asset_path(&assets_dir, 0)), None),
+            _root: root,
+        }
+    }
+
+    fn asset_path(assets_dir: &Path, index: usize) -> PathBuf {
+        assets_dir.join(format!("Asset_{}.uasset", index))
+    }
+}
+
+fn sizes() -> Vec<usize> {
+    std::env::var("UASSET_HELPER_BENCH_SIZES")
+        .ok()
+        .map(|sizes| {
+            sizes
+                .split(',')
+                .filter_map(|size| size.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_else(|| DEFAULT_SIZES.to_vec())
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DepTree::build");
+
+    for size in sizes() {
+        let fixture = Fixture::new(size);
+
+        // Catches the fixtures the parser can't read, the timings would be meaningless
+        let dep_tree = DepTree::build(
+            &fixture.asset_dirs,
+            UNLIMITED_RECURSE_DEPTH,
+            TraversalOrder::default(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(dep_tree.nodes.len(), size);
+
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &fixture, |b, fixture| {
+            b.iter(|| {
+                DepTree::build(
+                    &fixture.asset_dirs,
+                    UNLIMITED_RECURSE_DEPTH,
+                    TraversalOrder::default(),
+                    None,
+                    None,
+                )
+                .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, build);
+criterion_main!(benches);
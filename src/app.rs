@@ -1,22 +1,45 @@
+mod asset_info;
 mod dep_graph;
 mod file_picker;
 mod interactable_text;
+mod node_colors;
 
-use std::path::PathBuf;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use enum_iterator::all;
 use iced::{
     executor,
-    widget::{Column, Container, PickList, Space, Text},
-    Alignment, Application, Element, Length, Theme,
+    keyboard::{self, KeyCode},
+    theme::Palette,
+    widget::{Button, Checkbox, Column, Container, PickList, Row, Space, Text},
+    Alignment, Application, Color, Element, Length, Subscription, Theme,
 };
+use iced_aw::{floating_element::Anchor, FloatingElement};
 use iced_native::{row, Command};
+use itertools::Itertools;
 
 use crate::{
-    app::dep_graph::{DepTreePage, DepTreePageMsg},
+    app::{
+        asset_info::AssetInfoPage,
+        dep_graph::{
+            DepTreePage, DepTreePageMsg, DepTreePageTab, SelectionMove, EXPORT_STATUS_DURATION,
+        },
+        node_colors::NodeColors,
+    },
     asset::AssetDirs,
-    util::save_to_clipboard,
+    settings::{
+        AppTheme, CopyFormat, UserSettings, LARGE_TREE_THRESHOLD_STEP, MAX_FONT_SIZE, MIN_FONT_SIZE,
+    },
+    util::{disk_path_to_object_path, path_to_str},
 };
 
+/// How often the watched assets are checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum AppTab {
     AssetInfo,
@@ -39,16 +62,56 @@ impl AppTab {
     }
 }
 
+/// Entry of the recently picked paths list
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PathHistoryEntry(PathBuf);
+
+impl ToString for PathHistoryEntry {
+    #[inline]
+    fn to_string(&self) -> String {
+        path_to_str(&self.0)
+    }
+}
+
+impl From<AppTheme> for Theme {
+    fn from(value: AppTheme) -> Self {
+        match value {
+            AppTheme::Dark => Theme::Dark,
+            AppTheme::Light => Theme::Light,
+            AppTheme::Custom => Theme::custom(Palette {
+                background: Color::from_rgb8(0x2E, 0x34, 0x40),
+                text: Color::from_rgb8(0xEC, 0xEF, 0xF4),
+                primary: Color::from_rgb8(0x81, 0xA1, 0xC1),
+                success: Color::from_rgb8(0xA3, 0xBE, 0x8C),
+                danger: Color::from_rgb8(0xBF, 0x61, 0x6A),
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum GuiAppMessage {
     TabChanged(AppTab),
+    SetTheme(AppTheme),
+    SetColorblindColors(bool),
+    SetFontSize(u16),
+    SetSystemFont(bool),
+    SetClickCopyFormat(CopyFormat),
+    SetCtrlClickCopyFormat(CopyFormat),
+    SetLargeTreeThreshold(usize),
+    SetMultiColumnGraph(bool),
+    WindowResized(u32),
 
     SaveTextToClipboard(String),
 
     OpenFilePicker(bool),
+    OpenEditorPicker,
 
     SetAssetPath(Option<PathBuf>),
     SetEnginePath(Option<PathBuf>),
+    SetEditorPath(Option<PathBuf>),
+    /// Launches the editor with the project, opening the asset if it's a map
+    OpenInEditor(PathBuf),
 
     DepTreePage(DepTreePageMsg),
 }
@@ -59,12 +122,16 @@ pub struct GuiApp {
 
     // Cache
     asset_dirs: AssetDirs,
+    settings: UserSettings,
 
     // State
     theme: Theme,
     current_tab: AppTab,
+    engine_dir_warning: Option<String>,
+    asset_file_warning: Option<String>,
 
     // Body
+    asset_info_page: AssetInfoPage,
     dep_tree_page: DepTreePage,
 }
 
@@ -75,6 +142,8 @@ impl Application for GuiApp {
     type Flags = AssetDirs;
 
     fn new(asset_dirs: Self::Flags) -> (Self, Command<Self::Message>) {
+        let settings = UserSettings::load();
+
         (
             Self {
                 // System
@@ -82,13 +151,24 @@ impl Application for GuiApp {
 
                 // Cache
                 asset_dirs: asset_dirs.clone(),
+                settings: settings.clone(),
 
                 // State
-                theme: Theme::Dark,
+                theme: settings.theme.into(),
                 current_tab: AppTab::DependencyTree,
+                engine_dir_warning: asset_dirs.validate_engine_dir().err(),
+                asset_file_warning: asset_dirs.validate_asset_file().err(),
 
                 // Body
-                dep_tree_page: DepTreePage::new(asset_dirs),
+                asset_info_page: AssetInfoPage::new(&asset_dirs),
+                dep_tree_page: DepTreePage {
+                    large_tree_threshold: settings.large_tree_threshold,
+                    multi_column_graph: settings.multi_column_graph,
+                    max_recurse_depth: settings.max_recurse_depth,
+                    max_recurse_depth_text: settings.max_recurse_depth.to_string(),
+                    panes: DepTreePage::panes(settings.footer_split_ratio),
+                    ..DepTreePage::new(asset_dirs)
+                },
             },
             Command::none(),
         )
@@ -103,24 +183,135 @@ impl Application for GuiApp {
             GuiAppMessage::TabChanged(new_tab) => {
                 self.current_tab = new_tab;
             }
+            GuiAppMessage::SetTheme(new_theme) => {
+                self.theme = new_theme.into();
+
+                self.settings.theme = new_theme;
+                self.settings.save();
+            }
+            GuiAppMessage::SetColorblindColors(new_colorblind_colors) => {
+                self.settings.colorblind_colors = new_colorblind_colors;
+                self.settings.save();
+            }
+            GuiAppMessage::SetFontSize(new_font_size) => {
+                self.settings.font_size = new_font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+                self.settings.save();
+            }
+            GuiAppMessage::SetSystemFont(new_system_font) => {
+                self.settings.system_font = new_system_font;
+                self.settings.save();
+            }
+            GuiAppMessage::SetClickCopyFormat(new_copy_format) => {
+                self.settings.click_copy_format = new_copy_format;
+                self.settings.save();
+            }
+            GuiAppMessage::SetCtrlClickCopyFormat(new_copy_format) => {
+                self.settings.ctrl_click_copy_format = new_copy_format;
+                self.settings.save();
+            }
+            GuiAppMessage::WindowResized(width) => {
+                self.dep_tree_page.window_width = width;
+            }
+            GuiAppMessage::SetLargeTreeThreshold(new_threshold) => {
+                self.settings.large_tree_threshold = new_threshold.max(LARGE_TREE_THRESHOLD_STEP);
+                self.settings.save();
+
+                self.dep_tree_page.large_tree_threshold = self.settings.large_tree_threshold;
+            }
+            GuiAppMessage::SetMultiColumnGraph(new_multi_column_graph) => {
+                self.settings.multi_column_graph = new_multi_column_graph;
+                self.settings.save();
+
+                self.dep_tree_page.multi_column_graph = new_multi_column_graph;
+            }
             GuiAppMessage::OpenFilePicker(asset) => {
                 return Command::perform(file_picker::open(asset), move |path| match asset {
                     true => GuiAppMessage::SetAssetPath(path),
                     false => GuiAppMessage::SetEnginePath(path),
                 })
             }
+            GuiAppMessage::OpenEditorPicker => {
+                return Command::perform(file_picker::open_editor(), GuiAppMessage::SetEditorPath)
+            }
             GuiAppMessage::SetAssetPath(path) => {
+                if let Some(path) = &path {
+                    UserSettings::push_path_history(
+                        &mut self.settings.asset_path_history,
+                        path.clone(),
+                    );
+                    self.settings.save();
+                }
+
                 self.asset_dirs.update_asset_file(path);
+                self.asset_file_warning = self.asset_dirs.validate_asset_file().err();
+                self.asset_info_page.update_asset(&self.asset_dirs);
+                self.dep_tree_page.asset_dirs = self.asset_dirs.clone();
+                // Refreshing would rebuild the tree of the previous asset
+                self.dep_tree_page.clear_dep_tree();
             }
             GuiAppMessage::SetEnginePath(path) => {
-                self.asset_dirs.update_engine_dir(path);
+                if let Some(path) = &path {
+                    UserSettings::push_path_history(
+                        &mut self.settings.engine_path_history,
+                        path.clone(),
+                    );
+                    self.settings.save();
+                }
+
+                self.engine_dir_warning = self.asset_dirs.update_engine_dir(path).err();
+                self.asset_file_warning = self.asset_dirs.validate_asset_file().err();
+                self.dep_tree_page.asset_dirs = self.asset_dirs.clone();
+                // The tree was resolved with the content of the previous engine
+                self.dep_tree_page.clear_dep_tree();
+            }
+            GuiAppMessage::SetEditorPath(path) => {
+                // Cancelling the picker keeps the old editor
+                if path.is_some() {
+                    self.settings.editor_path = path;
+                    self.settings.save();
+                }
+            }
+            GuiAppMessage::OpenInEditor(asset_path) => {
+                let Some(editor_path) = &self.settings.editor_path else {
+                    log::warn!("Pick the Unreal Editor executable to open the assets in first");
+
+                    return Command::none();
+                };
+
+                open_in_editor(editor_path, &self.asset_dirs, &asset_path);
             }
             GuiAppMessage::DepTreePage(dep_tree_page_msg) => {
-                return self.dep_tree_page.update(
-                    dep_tree_page_msg,
-                    &self.asset_dirs,
-                    &mut self.clipboard,
-                );
+                let mut open_in_editor = None;
+
+                match &dep_tree_page_msg {
+                    DepTreePageMsg::SetMaxRecurseDepth(Some(new_depth)) => {
+                        self.settings.max_recurse_depth = *new_depth;
+                        self.settings.save();
+                    }
+                    DepTreePageMsg::ResizeFooter(resize_event) => {
+                        self.settings.footer_split_ratio = resize_event.ratio;
+                        self.settings.save();
+                    }
+                    DepTreePageMsg::ContextMenuAction(action) => {
+                        if let DepTreePageMsg::OpenInEditor(asset_path) = action.as_ref() {
+                            open_in_editor = Some(asset_path.clone());
+                        }
+                    }
+                    _ => {}
+                }
+
+                let command = self
+                    .dep_tree_page
+                    .update(dep_tree_page_msg, &self.asset_dirs, &mut self.clipboard)
+                    .map(GuiAppMessage::DepTreePage);
+
+                return match open_in_editor {
+                    Some(asset_path) => Command::batch([
+                        command,
+                        Command::perform(async { asset_path }, GuiAppMessage::OpenInEditor),
+                    ]),
+                    None => command,
+                };
             }
             GuiAppMessage::SaveTextToClipboard(text) => {
                 save_to_clipboard(&mut self.clipboard, text)
@@ -131,29 +322,135 @@ impl Application for GuiApp {
     }
 
     fn view(&self) -> Element<'_, Self::Message> {
-        let header = Self::header(&self.asset_dirs, self.current_tab);
+        let header = Self::header(
+            &self.asset_dirs,
+            self.current_tab,
+            &self.settings,
+            &self.engine_dir_warning,
+            &self.asset_file_warning,
+            self.theme.palette(),
+        );
+        let node_colors = NodeColors::new(self.theme.palette(), self.settings.colorblind_colors);
 
         let body = match self.current_tab {
-            AppTab::AssetInfo => Container::new(Text::new("Asset Info")).into(),
-            AppTab::DependencyTree => self.dep_tree_page.view().map(GuiAppMessage::DepTreePage),
+            AppTab::AssetInfo => self.asset_info_page.view(
+                &self.asset_dirs,
+                self.settings.font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE),
+            ),
+            AppTab::DependencyTree => self
+                .dep_tree_page
+                .view(
+                    node_colors,
+                    self.settings.font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE),
+                    (
+                        self.settings.click_copy_format,
+                        self.settings.ctrl_click_copy_format,
+                    ),
+                )
+                .map(GuiAppMessage::DepTreePage),
         };
 
-        Container::new(
+        let content = Container::new(
             Column::with_children(vec![header, body])
                 .spacing(20)
                 .align_items(Alignment::Center),
         )
-        .padding(20)
+        .padding(20);
+
+        // The context menu is positioned in the window coordinates, so it floats over the whole app
+        let context_menu_position = match self.current_tab {
+            AppTab::DependencyTree => self
+                .dep_tree_page
+                .context_menu
+                .map(|(_, position)| position),
+            AppTab::AssetInfo => None,
+        };
+
+        FloatingElement::new(content, move || {
+            self.dep_tree_page
+                .context_menu_view()
+                .map(GuiAppMessage::DepTreePage)
+        })
+        .anchor(Anchor::NorthWest)
+        .offset(
+            context_menu_position
+                .map(|position| [position.x, position.y])
+                .unwrap_or_default(),
+        )
+        .hide(context_menu_position.is_none())
         .into()
     }
 
     fn theme(&self) -> Self::Theme {
         self.theme.clone()
     }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let window_events = iced_native::subscription::events_with(|event, _| match event {
+            iced_native::Event::Window(iced_native::window::Event::Resized { width, .. }) => {
+                Some(GuiAppMessage::WindowResized(width))
+            }
+            _ => None,
+        });
+
+        let watch = match self.dep_tree_page.watch {
+            true => iced::time::every(WATCH_INTERVAL)
+                .map(|_| GuiAppMessage::DepTreePage(DepTreePageMsg::WatchTick)),
+            false => Subscription::none(),
+        };
+
+        let graph_navigation = match (self.current_tab, self.dep_tree_page.tab) {
+            (AppTab::DependencyTree, DepTreePageTab::Graph) => {
+                iced_native::subscription::events_with(|event, status| {
+                    // The keys typed into the inputs are left to them
+                    if status == iced_native::event::Status::Captured {
+                        return None;
+                    }
+
+                    let iced_native::Event::Keyboard(keyboard::Event::KeyPressed {
+                        key_code,
+                        modifiers,
+                    }) = event
+                    else {
+                        return None;
+                    };
+
+                    let message = match key_code {
+                        KeyCode::Up => DepTreePageMsg::MoveSelection(SelectionMove::Prev),
+                        KeyCode::Down => DepTreePageMsg::MoveSelection(SelectionMove::Next),
+                        KeyCode::Home => DepTreePageMsg::MoveSelection(SelectionMove::First),
+                        KeyCode::End => DepTreePageMsg::MoveSelection(SelectionMove::Last),
+                        KeyCode::Enter => DepTreePageMsg::ActivateSelection {
+                            re_root: modifiers.control(),
+                        },
+                        _ => return None,
+                    };
+
+                    Some(GuiAppMessage::DepTreePage(message))
+                })
+            }
+            _ => Subscription::none(),
+        };
+
+        let export_status = match self.dep_tree_page.export_status {
+            Some(_) => iced::time::every(EXPORT_STATUS_DURATION)
+                .map(|_| GuiAppMessage::DepTreePage(DepTreePageMsg::ClearExportStatus)),
+            None => Subscription::none(),
+        };
+
+        Subscription::batch([window_events, watch, graph_navigation, export_status])
+    }
 }
 
 impl GuiApp {
-    fn header<'a>(asset_dirs: &AssetDirs, current_tab: AppTab) -> Element<'a, GuiAppMessage> {
+    fn header<'a>(
+        asset_dirs: &AssetDirs,
+        current_tab: AppTab,
+        settings: &UserSettings,
+        engine_dir_warning: &Option<String>,
+        asset_file_warning: &Option<String>,
+        palette: Palette,
+    ) -> Element<'a, GuiAppMessage> {
         let pick_list_tabs = PickList::new(
             &[AppTab::AssetInfo, AppTab::DependencyTree][..],
             Some(current_tab),
@@ -161,6 +458,87 @@ impl GuiApp {
         )
         .width(Length::FillPortion(2));
 
+        let pick_list_theme = PickList::new(
+            all::<AppTheme>().collect_vec(),
+            Some(settings.theme),
+            GuiAppMessage::SetTheme,
+        )
+        .width(Length::FillPortion(1));
+
+        let colorblind_checkbox = Checkbox::new(
+            settings.colorblind_colors,
+            "Colorblind Colors",
+            GuiAppMessage::SetColorblindColors,
+        )
+        .spacing(5);
+
+        let font_size = Row::new()
+            .push(
+                Button::new(Text::new("-")).on_press(GuiAppMessage::SetFontSize(
+                    settings.font_size.saturating_sub(1),
+                )),
+            )
+            .push(Text::new(settings.font_size.to_string()))
+            .push(
+                Button::new(Text::new("+")).on_press(GuiAppMessage::SetFontSize(
+                    settings.font_size.saturating_add(1),
+                )),
+            )
+            .push(
+                Checkbox::new(
+                    settings.system_font,
+                    "System Font",
+                    GuiAppMessage::SetSystemFont,
+                )
+                .spacing(5),
+            )
+            .spacing(5)
+            .align_items(Alignment::Center);
+
+        let copy_formats = Row::new()
+            .push(Text::new("Click copies:"))
+            .push(PickList::new(
+                all::<CopyFormat>().collect_vec(),
+                Some(settings.click_copy_format),
+                GuiAppMessage::SetClickCopyFormat,
+            ))
+            .push(Text::new("Ctrl:"))
+            .push(PickList::new(
+                all::<CopyFormat>().collect_vec(),
+                Some(settings.ctrl_click_copy_format),
+                GuiAppMessage::SetCtrlClickCopyFormat,
+            ))
+            .spacing(5)
+            .align_items(Alignment::Center);
+
+        let large_tree_threshold = Row::new()
+            .push(Text::new("Large tree:"))
+            .push(
+                Button::new(Text::new("-")).on_press(GuiAppMessage::SetLargeTreeThreshold(
+                    settings
+                        .large_tree_threshold
+                        .saturating_sub(LARGE_TREE_THRESHOLD_STEP),
+                )),
+            )
+            .push(Text::new(settings.large_tree_threshold.to_string()))
+            .push(
+                Button::new(Text::new("+")).on_press(GuiAppMessage::SetLargeTreeThreshold(
+                    settings
+                        .large_tree_threshold
+                        .saturating_add(LARGE_TREE_THRESHOLD_STEP),
+                )),
+            )
+            .push(
+                Checkbox::new(
+                    settings.multi_column_graph,
+                    "Columns",
+                    GuiAppMessage::SetMultiColumnGraph,
+                )
+                .spacing(5),
+            )
+            .spacing(5)
+            .align_items(Alignment::Center);
+
         let asset_file_picker_text = asset_dirs.asset_file_name_str().unwrap_or_default();
         let asset_file_picker_tooltip = asset_dirs.asset_file_path_str();
 
@@ -174,8 +552,15 @@ impl GuiApp {
                 None,
             ),
             GuiAppMessage::OpenFilePicker(true),
-        )
-        .width(Length::FillPortion(3));
+        );
+        let asset_file_picker = Row::new()
+            .push(asset_file_picker)
+            .push(Self::path_history_pick_list(
+                &settings.asset_path_history,
+                GuiAppMessage::SetAssetPath,
+            ))
+            .spacing(5)
+            .width(Length::FillPortion(3));
 
         let engine_folder_picker_text = asset_dirs.engine_dir_str().unwrap_or_default();
 
@@ -190,21 +575,124 @@ impl GuiApp {
                 None,
                 None,
             ),
-            GuiAppMessage::OpenFilePicker(true),
-        )
-        .width(Length::FillPortion(3));
-
-        Container::new(
+            GuiAppMessage::OpenFilePicker(false),
+        );
+        let engine_folder_picker = Row::new()
+            .push(engine_folder_picker)
+            .push(Self::path_history_pick_list(
+                &settings.engine_path_history,
+                GuiAppMessage::SetEnginePath,
+            ))
+            .spacing(5)
+            .width(Length::FillPortion(3));
+
+        let editor_picker_text = settings
+            .editor_path
+            .as_ref()
+            .and_then(|editor_path| editor_path.file_name())
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let editor_picker = file_picker::widget(
+            "Pick Editor",
+            editor_picker_text,
+            settings.editor_path.as_ref().map(path_to_str),
+            (None, None, None),
+            GuiAppMessage::OpenEditorPicker,
+        );
+
+        let header = Container::new(
             row![
                 pick_list_tabs,
+                pick_list_theme,
+                colorblind_checkbox,
+                font_size,
+                copy_formats,
+                large_tree_threshold,
                 Space::with_width(Length::FillPortion(1)),
                 asset_file_picker,
                 engine_folder_picker,
+                editor_picker,
             ]
             .spacing(10)
             .width(Length::Shrink),
         )
-        .max_height(30)
-        .into()
+        .max_height(30);
+
+        let warnings = [asset_file_warning, engine_dir_warning]
+            .into_iter()
+            .flatten()
+            .collect_vec();
+
+        match warnings.is_empty() {
+            true => header.into(),
+            false => warnings
+                .into_iter()
+                .fold(Column::new().push(header), |column, warning| {
+                    column.push(Text::new(warning.clone()).size(14).style(palette.danger))
+                })
+                .spacing(5)
+                .align_items(Alignment::Center)
+                .into(),
+        }
+    }
+
+    fn path_history_pick_list<'a>(
+        history: &[PathBuf],
+        on_selected: fn(Option<PathBuf>) -> GuiAppMessage,
+    ) -> PickList<'a, PathHistoryEntry, GuiAppMessage> {
+        PickList::new(
+            history
+                .iter()
+                .cloned()
+                .map(PathHistoryEntry)
+                .collect::<Vec<_>>(),
+            None,
+            move |entry| on_selected(Some(entry.0)),
+        )
+        .placeholder("Recent")
+        .width(Length::Units(100))
+    }
+}
+
+/// Starts the editor with the project of the asset. The editor only opens the maps passed after the project,
+/// the other assets are left for the user to find, e.g. with the copied reference
+fn open_in_editor(editor_path: &Path, asset_dirs: &AssetDirs, asset_path: &Path) {
+    let Some(uproject_path) = asset_dirs.uproject_path() else {
+        log::error!("No .uproject file found in {:?}", asset_dirs.project_dir);
+
+        return;
+    };
+
+    let mut command = std::process::Command::new(editor_path);
+    command.arg(&uproject_path);
+
+    let is_map = asset_path.extension().and_then(|ext| ext.to_str()) == Some("umap");
+    if let (true, Some(object_path)) = (
+        is_map,
+        disk_path_to_object_path(
+            asset_path,
+            &asset_dirs.content_dir,
+            &asset_dirs.engine_content_dir,
+            &asset_dirs.plugins_dirs,
+        ),
+    ) {
+        command.arg(object_path);
+    }
+
+    match command.spawn() {
+        Ok(_) => log::info!("Opening {:?} in {:?}", asset_path, editor_path),
+        Err(err) => log::error!("Failed to start the editor {:?}: {}", editor_path, err),
+    }
+}
+
+pub fn save_to_clipboard<'a>(clipboard: &mut arboard::Clipboard, text: impl Into<Cow<'a, str>>) {
+    match clipboard.set_text(text) {
+        Ok(_) => {
+            log::info!("Copied text to clipboard");
+        }
+        Err(err) => {
+            log::error!("Failed to copy text to clipboard: {}", err);
+        }
     }
 }
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use iced::{
+    widget::{image, Column, Container, Image, Text},
+    Alignment, Element, Length,
+};
+
+use crate::asset::{Asset, AssetDirs};
+
+/// Size of the thumbnail preview, the editor saves them at 256x256
+const THUMBNAIL_SIZE: u16 = 256;
+
+/// Info about the picked asset
+#[derive(Default)]
+pub struct AssetInfoPage {
+    pub asset: Option<Arc<Asset>>,
+    /// Decoded only when the asset changes, not on every redraw
+    pub thumbnail: Option<image::Handle>,
+    /// Why the picked asset couldn't be read
+    pub error: Option<String>,
+}
+
+impl AssetInfoPage {
+    pub fn new(asset_dirs: &AssetDirs) -> Self {
+        let mut page = Self::default();
+        page.update_asset(asset_dirs);
+
+        page
+    }
+
+    /// Reads the asset picked in the asset dirs
+    pub fn update_asset(&mut self, asset_dirs: &AssetDirs) {
+        *self = Self::default();
+
+        let Some(asset_file_path) = &asset_dirs.asset_file_path else {
+            return;
+        };
+
+        match Asset::new(asset_file_path) {
+            Ok(asset) => {
+                self.thumbnail = asset.read_thumbnail().map(image::Handle::from_memory);
+                self.asset = Some(Arc::new(asset));
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// The page has no interactions, so it fits into any message type
+    pub fn view<'a, Message: 'a>(
+        &'a self,
+        asset_dirs: &AssetDirs,
+        text_size: u16,
+    ) -> Element<'a, Message> {
+        let Some(asset) = &self.asset else {
+            return Text::new(
+                self.error
+                    .clone()
+                    .unwrap_or_else(|| "No asset picked yet.".to_string()),
+            )
+            .size(text_size)
+            .into();
+        };
+
+        let preview: Element<Message> = match &self.thumbnail {
+            Some(thumbnail) => Image::new(thumbnail.clone())
+                .width(Length::Units(THUMBNAIL_SIZE))
+                .height(Length::Units(THUMBNAIL_SIZE))
+                .into(),
+            None => Container::new(Text::new("No thumbnail").size(text_size))
+                .width(Length::Units(THUMBNAIL_SIZE))
+                .height(Length::Units(THUMBNAIL_SIZE))
+                .center_x()
+                .center_y()
+                .into(),
+        };
+
+        let info = [
+            format!("Path: {}", asset.path_str()),
+            format!(
+                "Object path: {}",
+                asset
+                    .object_path(asset_dirs)
+                    .unwrap_or_else(|| "...Unknown...".to_string())
+            ),
+            format!("Origin: {}", asset.origin.to_string()),
+            format!(
+                "Class: {}",
+                asset
+                    .primary_class()
+                    .unwrap_or_else(|| "...Unknown...".to_string())
+            ),
+            format!(
+                "Engine version: {}, File version: {}",
+                asset.engine_version(),
+                asset.file_version()
+            ),
+        ]
+        .into_iter()
+        .fold(Column::new().spacing(5), |column, info| {
+            column.push(Text::new(info).size(text_size))
+        });
+
+        Column::new()
+            .push(preview)
+            .push(info)
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .into()
+    }
+}
@@ -1,14 +1,27 @@
 use std::borrow::Cow;
-use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::{Duration, SystemTime};
 
 use enum_iterator::{all, Sequence};
+use graphviz_rust::{
+    cmd::{CommandArg, Format},
+    dot_structures::Graph,
+    exec,
+    printer::{DotPrinter, PrinterContext},
+};
 use iced::{
     alignment::{Horizontal, Vertical},
     widget::{
-        column, Button, Checkbox, Column, Container, PickList, Row, Scrollable, Space, Text,
-        TextInput,
+        column, pane_grid, scrollable, tooltip, Button, Checkbox, Column, Container, PaneGrid,
+        PickList, Row, Scrollable, Space, Text, TextInput,
     },
-    Alignment, Color, Command, Element, Length,
+    Alignment, Color, Command, Element, Length, Point,
 };
 use iced_aw::{graphics::IconText, Icon, TabBar, TabLabel};
 use iced_native::row;
@@ -16,10 +29,20 @@ use itertools::Itertools;
 use num_traits::{FromPrimitive, ToPrimitive};
 
 use crate::{
-    app::interactable_text::interactive_text_tooltip,
-    asset::{Asset, AssetDirs, AssetOrigin},
-    dependency_tree::{DepTree, NodeID},
-    util::{path_to_str, save_to_clipboard, SortOrder},
+    app::{
+        file_picker, interactable_text::interactive_text_tooltip, node_colors::NodeColors,
+        save_to_clipboard,
+    },
+    asset::{Asset, AssetDirs, AssetError, AssetOrigin, EdgeKind},
+    dependency_tree::{
+        fix_file_name, AggregatedNode, ColorMode, DepTree, NodeID, TraversalOrder,
+        UNLIMITED_RECURSE_DEPTH,
+    },
+    settings::{CopyFormat, DEFAULT_FOOTER_SPLIT_RATIO},
+    util::{
+        disk_path_to_object_path, ellipsize_middle, format_size, path_to_str, reveal_in_folder,
+        SortOrder,
+    },
 };
 
 #[repr(usize)]
@@ -86,6 +109,7 @@ pub enum DepTreePageGraphSortType {
     Filename,
     Path,
     NumDeps,
+    NumDependents,
 }
 
 impl ToPrimitive for DepTreePageGraphSortType {
@@ -117,6 +141,9 @@ impl FromPrimitive for DepTreePageGraphSortType {
             n if n == DepTreePageGraphSortType::NumDeps.to_i64().unwrap() => {
                 Some(DepTreePageGraphSortType::NumDeps)
             }
+            n if n == DepTreePageGraphSortType::NumDependents.to_i64().unwrap() => {
+                Some(DepTreePageGraphSortType::NumDependents)
+            }
             _ => None,
         }
     }
@@ -133,6 +160,9 @@ impl FromPrimitive for DepTreePageGraphSortType {
             n if n == DepTreePageGraphSortType::NumDeps as usize => {
                 Some(DepTreePageGraphSortType::NumDeps)
             }
+            n if n == DepTreePageGraphSortType::NumDependents as usize => {
+                Some(DepTreePageGraphSortType::NumDependents)
+            }
             _ => None,
         }
     }
@@ -151,6 +181,9 @@ impl FromPrimitive for DepTreePageGraphSortType {
             n if n == DepTreePageGraphSortType::NumDeps.to_u64().unwrap() => {
                 Some(DepTreePageGraphSortType::NumDeps)
             }
+            n if n == DepTreePageGraphSortType::NumDependents.to_u64().unwrap() => {
+                Some(DepTreePageGraphSortType::NumDependents)
+            }
             _ => None,
         }
     }
@@ -163,68 +196,246 @@ impl ToString for DepTreePageGraphSortType {
             DepTreePageGraphSortType::Filename => "Filename",
             DepTreePageGraphSortType::Path => "Path",
             DepTreePageGraphSortType::NumDeps => "Num Dependencies",
+            DepTreePageGraphSortType::NumDependents => "Num Dependents",
+        }
+        .to_string()
+    }
+}
+
+/// Which connections of the nodes the graph tab shows
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Sequence)]
+pub enum Direction {
+    /// What the nodes depend on
+    Dependencies,
+    /// What depends on the nodes
+    Dependents,
+}
+
+impl ToString for Direction {
+    fn to_string(&self) -> String {
+        match self {
+            Direction::Dependencies => "Dependencies",
+            Direction::Dependents => "Dependents",
         }
         .to_string()
     }
 }
 
+/// Minimum width of a column of the graph when it's wrapped into several ones
+const GRAPH_COLUMN_WIDTH: u32 = 500;
+
+/// How long the result of an export stays shown
+pub const EXPORT_STATUS_DURATION: Duration = Duration::from_secs(5);
+
+/// Longer paths in the node tooltips are cut in the middle
+const TOOLTIP_MAX_PATH_LEN: usize = 120;
+
+/// More parents than that are only counted in the footer
+const FOOTER_MAX_PARENTS: usize = 3;
+
+/// Panes of the split between the tabs and the footer
+#[derive(Debug, Copy, Clone)]
+pub enum DepTreePane {
+    Tabs,
+    Footer,
+}
+
+/// Keyboard movement through the nodes of the graph tab
+#[derive(Debug, Copy, Clone)]
+pub enum SelectionMove {
+    Prev,
+    Next,
+    First,
+    Last,
+}
+
 #[derive(Debug, Clone)]
 pub enum DepTreePageMsg {
     GenerateDependencyTree,
+    CancelBuild,
+    DependencyTreeBuilt(Arc<Result<DepTree, AssetError>>),
+    RefreshDependencyTree,
+    /// Tree with the refreshed node, built from a copy of the shown one
+    DependencyTreeRefreshed(Arc<Result<DepTree, AssetError>>),
+    /// Tries to resolve the failed dependencies once more
+    RetryFailures,
     ClearDependencyTree,
     SetMaxRecurseDepth(Option<u32>),
+    SetUnlimitedRecurseDepth(bool),
+    SetSkipEngine(bool),
+    SetIgnorePatterns(String),
+    SetWatch(bool),
+    /// Checks if the root asset changed while watching it
+    WatchTick,
     SetTab(DepTreePageTab),
 
     SetMinGraphDepth(Option<u32>),
 
     SetFilter(usize, bool),
+    SetClassFilter(usize, bool),
     SetSortType(DepTreePageGraphSortType),
+    SetColorMode(ColorMode),
+    SetDirection(Direction),
     ToggleSortOrder,
-    SetShowChildren(bool),
+    SetExpandAll(bool),
+    ToggleExpand(NodeID),
     SetShowOnlyChanged(bool),
+    SetLeavesOnly(bool),
+    /// Hides the child rows of the assets already shown elsewhere in the graph
+    SetUniqueNodes(bool),
+    /// Shows each engine plugin as a single node
+    SetCollapseEnginePlugins(bool),
 
     ShowFooterInfo(Option<(NodeID, bool)>),
+    /// Dragging the split between the tabs and the footer
+    ResizeFooter(pane_grid::ResizeEvent),
+
+    ShowContextMenu(NodeID, Point),
+    HideContextMenu,
+    /// Action picked in the context menu, closes it
+    ContextMenuAction(Box<DepTreePageMsg>),
+    ReRoot(NodeID),
+    RevealInFolder(NodeID),
+    /// Highlights the chain of the dependents from the root to the node, dimming the other nodes
+    HighlightPathToRoot(NodeID),
+    ClearHighlight,
+    /// Handled by the app, which knows where the editor is
+    OpenInEditor(PathBuf),
+
+    SetFindObjectPath(String),
+    FindObjectPath,
+    /// Shows the node in the graph tab, e.g. the one referencing a failure
+    JumpToNode(NodeID),
+
+    SetSubtreeDepth(Option<u32>),
+    ExportSubtree(NodeID),
+    ExportFinished(Result<PathBuf, String>),
+
+    /// Copies the dot of the graph with the current filters applied
+    CopyGraphDot,
+    /// Renders the graph with the current filters applied to SVG and saves it
+    ExportGraphSvg,
+    ClearExportStatus,
+    /// Copies the summary of the whole tree, with the time it's copied at
+    CopySummary,
+    /// Copies the editor references of the nodes of the graph tab, filtered and sorted as they're shown
+    CopyVisibleReferences,
 
     SaveToClipboard(String),
+    /// Copies the node in the given format, falling back to the disk path if it can't be made
+    CopyNode(NodeID, CopyFormat),
+    MoveSelection(SelectionMove),
+    /// Expands the selected node, or builds the tree from it
+    ActivateSelection {
+        re_root: bool,
+    },
 }
 
 pub struct DepTreePage {
     pub asset_dirs: AssetDirs,
 
     pub dep_tree: Option<DepTree>,
+    /// Set while the tree is being built in the background, raising it cancels the build
+    pub build_cancel: Option<Arc<AtomicBool>>,
+    /// State of the last build, e.g. if it was cancelled
+    pub build_status: Option<String>,
+    /// Result of the last export, shown for [`EXPORT_STATUS_DURATION`]
+    pub export_status: Option<String>,
 
     pub tab: DepTreePageTab,
 
     pub max_recurse_depth: u32,
     pub max_recurse_depth_text: String,
+    /// Ignore the maximum recurse depth and resolve all the dependencies
+    pub unlimited_recurse_depth: bool,
+    /// Don't resolve the engine dependencies when building the tree
+    pub skip_engine: bool,
+    /// Comma separated globs of the object paths of the dependencies not to resolve
+    pub ignore_patterns_text: String,
+    pub ignore_patterns: Vec<glob::Pattern>,
+    /// Refresh the tree when the root asset changes
+    pub watch: bool,
+    /// Modification time of the root asset when the tree was last built
+    pub watched_modified: Option<SystemTime>,
+    /// Modification time seen on the last tick, the tree is refreshed once it stops changing
+    pub pending_modified: Option<SystemTime>,
 
     pub min_graph_depth: u32,
     pub min_graph_depth_text: String,
 
     /// Filters for the graph
     pub filters: Vec<(AssetOrigin, bool)>,
+    /// Filters by the classes found in the tree, `None` for the assets with unknown class
+    pub class_filters: Vec<(Option<String>, bool)>,
     /// Sorting type of the graph
     pub graph_sort_type: DepTreePageGraphSortType,
     /// Sorting order of the graph
     pub graph_sort_order: SortOrder,
-    pub graph_show_children: bool,
+    /// What the nodes of the graph and the exports are colored by
+    pub color_mode: ColorMode,
+    /// If the nodes list their dependencies or the assets depending on them
+    pub direction: Direction,
+    /// Nodes that show their children
+    pub expanded: HashSet<NodeID>,
     pub graph_show_only_changed: bool,
-
+    /// Show only the assets without dependencies
+    pub graph_leaves_only: bool,
+    /// Show each asset in a single row, the shared dependencies aren't repeated under all their parents
+    pub unique_nodes: bool,
+    /// Show the tree with the assets of each engine plugin collapsed into a single node
+    pub collapse_engine_plugins: bool,
+    /// Tree with the engine plugins collapsed, kept up to date with the tree while the option is on
+    pub aggregated_dep_tree: Option<DepTree>,
+    /// Trees with more nodes than this have their nodes collapsed by default
+    pub large_tree_threshold: usize,
+    /// Width of the window, for fitting the long paths
+    pub window_width: u32,
+    /// Wrap the nodes of the graph into as many columns as fit the window
+    pub multi_column_graph: bool,
+
+    /// Split between the tabs and the footer
+    pub panes: pane_grid::State<DepTreePane>,
     /// ID and if info is extended
     pub footer_asset_show_min_info: Option<(NodeID, bool)>,
+    /// Node selected with the keyboard
+    pub selected_node: Option<NodeID>,
+    /// Nodes on the highlighted path to the root, nothing is dimmed if empty
+    pub highlighted: HashSet<NodeID>,
+    /// Node the context menu is opened for and where
+    pub context_menu: Option<(NodeID, Point)>,
+
+    pub subtree_depth: u32,
+    pub subtree_depth_text: String,
+
+    /// Object path of the asset to search for
+    pub find_object_path_text: String,
+    /// Why the last searched asset wasn't found
+    pub find_object_path_error: Option<String>,
 }
 
 impl DepTreePage {
     pub fn new(asset_dirs: AssetDirs) -> Self {
         Self {
+            // Starting with the patterns passed on the command line
+            ignore_patterns_text: asset_dirs.ignore_patterns.iter().join(", "),
+            ignore_patterns: asset_dirs.ignore_patterns.clone(),
+
             asset_dirs,
 
             dep_tree: None,
+            build_cancel: None,
+            build_status: None,
+            export_status: None,
 
             tab: DepTreePageTab::Graph,
 
             max_recurse_depth: 10,
             max_recurse_depth_text: String::from("10"),
+            unlimited_recurse_depth: false,
+            skip_engine: false,
+            watch: false,
+            watched_modified: None,
+            pending_modified: None,
 
             min_graph_depth: 0,
             min_graph_depth_text: String::from("0"),
@@ -235,43 +446,172 @@ impl DepTreePage {
                 (AssetOrigin::Project, true),
                 (AssetOrigin::ProjectPlugin, true),
             ],
+            class_filters: vec![],
             graph_sort_type: DepTreePageGraphSortType::Id,
             graph_sort_order: SortOrder::Ascending,
-            graph_show_children: true,
+            color_mode: ColorMode::default(),
+            direction: Direction::Dependencies,
+            expanded: HashSet::new(),
             graph_show_only_changed: false,
-
+            graph_leaves_only: false,
+            unique_nodes: false,
+            collapse_engine_plugins: false,
+            aggregated_dep_tree: None,
+            large_tree_threshold: 1000,
+            window_width: 1600,
+            multi_column_graph: false,
+
+            panes: Self::panes(DEFAULT_FOOTER_SPLIT_RATIO),
             footer_asset_show_min_info: None,
+            selected_node: None,
+            highlighted: HashSet::new(),
+            context_menu: None,
+
+            subtree_depth: 1,
+            subtree_depth_text: String::from("1"),
+
+            find_object_path_text: String::new(),
+            find_object_path_error: None,
         }
     }
 
-    pub fn update<'a, Message>(
+    pub fn update(
         &mut self,
         message: DepTreePageMsg,
         asset_dirs: &AssetDirs,
         clipboard: &mut arboard::Clipboard,
-    ) -> Command<Message>
-    where
-        Message: Clone + 'a,
-    {
+    ) -> Command<DepTreePageMsg> {
         match message {
             DepTreePageMsg::GenerateDependencyTree => {
-                let dependency_tree = DepTree::build_with_pb(asset_dirs, self.max_recurse_depth);
+                return self.build_in_background(asset_dirs.clone());
+            }
+            DepTreePageMsg::CancelBuild => {
+                if let Some(build_cancel) = &self.build_cancel {
+                    build_cancel.store(true, Ordering::Release);
 
-                match dependency_tree {
-                    Ok(dependency_tree) => {
-                        if dependency_tree.nodes.len() > 1000 {
-                            self.graph_show_children = false;
+                    self.build_status = Some("Cancelling...".to_string());
+                }
+            }
+            DepTreePageMsg::DependencyTreeBuilt(res) => {
+                let cancelled = self
+                    .build_cancel
+                    .take()
+                    .map(|build_cancel| build_cancel.load(Ordering::Acquire))
+                    .unwrap_or_default();
+                self.build_status = None;
+
+                match Arc::try_unwrap(res) {
+                    // Finished before noticing it was cancelled, e.g. for the previously picked asset
+                    Ok(Ok(_)) if cancelled => {
+                        self.build_status = Some("The build was cancelled".to_string())
+                    }
+                    Ok(Ok(dependency_tree)) => {
+                        self.expanded =
+                            match dependency_tree.nodes.len() > self.large_tree_threshold {
+                                true => HashSet::new(),
+                                false => dependency_tree.node_connections.keys().copied().collect(),
+                            };
+                        self.footer_asset_show_min_info = None;
+                        self.selected_node = None;
+                        self.highlighted.clear();
+
+                        self.dep_tree = Some(dependency_tree);
+                        self.update_class_filters();
+                        self.update_aggregated_tree();
+                        self.watched_modified = self.root_modified();
+                    }
+                    Ok(Err(err)) => match cancelled {
+                        true => self.build_status = Some("The build was cancelled".to_string()),
+                        false => {
+                            log::error!("Failed to generate dependency tree: {}", err);
+
+                            self.build_status =
+                                Some(format!("Failed to generate dependency tree: {}", err));
+                        }
+                    },
+                    Err(_) => {
+                        log::error!("Failed to take the built dependency tree, it's still shared");
+                    }
+                }
+            }
+            DepTreePageMsg::RefreshDependencyTree => match &self.dep_tree {
+                // Only the selected asset (or the root) is re-read, unless the tree has to go deeper or shallower
+                Some(dep_tree)
+                    if dep_tree.max_recurse_depth == self.effective_max_recurse_depth() =>
+                {
+                    let node_id = self
+                        .footer_asset_show_min_info
+                        .map(|(node_id, _)| node_id)
+                        .unwrap_or(dep_tree.root_node_id);
+
+                    return self.rebuild_in_background(node_id, asset_dirs.clone());
+                }
+                _ => {
+                    return self.update(
+                        DepTreePageMsg::GenerateDependencyTree,
+                        asset_dirs,
+                        clipboard,
+                    )
+                }
+            },
+            DepTreePageMsg::DependencyTreeRefreshed(res) => {
+                let cancelled = self
+                    .build_cancel
+                    .take()
+                    .map(|build_cancel| build_cancel.load(Ordering::Acquire))
+                    .unwrap_or_default();
+                self.build_status = None;
+
+                match Arc::try_unwrap(res) {
+                    Ok(Ok(_)) if cancelled => {
+                        self.build_status = Some("The refresh was cancelled".to_string())
+                    }
+                    Ok(Ok(dependency_tree)) => {
+                        if let Some((footer_node_id, _)) = self.footer_asset_show_min_info {
+                            if !dependency_tree.nodes.contains_key(&footer_node_id) {
+                                self.footer_asset_show_min_info = None;
+                            }
                         }
 
                         self.dep_tree = Some(dependency_tree);
+                        self.update_class_filters();
+                        self.update_aggregated_tree();
                     }
-                    Err(err) => {
-                        log::error!("Failed to generate dependency tree: {}", err);
+                    Ok(Err(err)) => match cancelled {
+                        true => self.build_status = Some("The refresh was cancelled".to_string()),
+                        false => {
+                            log::error!("Failed to refresh the dependency tree: {}", err);
+
+                            self.build_status =
+                                Some(format!("Failed to refresh the dependency tree: {}", err));
+                        }
+                    },
+                    Err(_) => {
+                        log::error!(
+                            "Failed to take the refreshed dependency tree, it's still shared"
+                        );
                     }
                 }
             }
+            DepTreePageMsg::RetryFailures => {
+                if let Some(dep_tree) = &mut self.dep_tree {
+                    let asset_dirs = AssetDirs {
+                        skip_engine: self.skip_engine,
+                        ignore_patterns: self.ignore_patterns.clone(),
+                        ..asset_dirs.clone()
+                    };
+
+                    match dep_tree.retry_failures(&asset_dirs) {
+                        Ok(fixed) => log::info!("Retrying the failures fixed {}", fixed),
+                        Err(err) => log::error!("Failed to retry the failures: {}", err),
+                    }
+
+                    self.update_class_filters();
+                    self.update_aggregated_tree();
+                }
+            }
             DepTreePageMsg::ClearDependencyTree => {
-                self.dep_tree = None;
+                self.clear_dep_tree();
             }
             DepTreePageMsg::SetMaxRecurseDepth(new_max_recurse_depth) => {
                 match new_max_recurse_depth {
@@ -282,22 +622,211 @@ impl DepTreePage {
                     None => self.max_recurse_depth_text = String::new(),
                 }
             }
+            DepTreePageMsg::SetUnlimitedRecurseDepth(new_unlimited) => {
+                self.unlimited_recurse_depth = new_unlimited;
+            }
+            DepTreePageMsg::SetIgnorePatterns(new_text) => {
+                // The patterns being typed are often invalid, they're just left out until fixed
+                self.ignore_patterns = new_text
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+                    .collect();
+                self.ignore_patterns_text = new_text;
+            }
+            DepTreePageMsg::SetSkipEngine(new_skip_engine) => {
+                self.skip_engine = new_skip_engine;
+            }
+            DepTreePageMsg::SetWatch(new_watch) => {
+                self.watch = new_watch;
+                self.pending_modified = None;
+                self.watched_modified = self.root_modified();
+            }
+            DepTreePageMsg::WatchTick => {
+                let modified = self.root_modified();
+
+                if !self.watch || self.build_cancel.is_some() || modified == self.watched_modified {
+                    return Command::none();
+                }
+
+                // Editors often write the files several times when saving, so wait for a tick without changes
+                if modified != self.pending_modified {
+                    self.pending_modified = modified;
+
+                    return Command::none();
+                }
+
+                self.watched_modified = modified;
+                self.pending_modified = None;
+
+                if let Some(root_node_id) =
+                    self.dep_tree.as_ref().map(|dep_tree| dep_tree.root_node_id)
+                {
+                    log::info!("The root asset changed, refreshing the dependency tree");
+
+                    return self.rebuild_in_background(root_node_id, asset_dirs.clone());
+                }
+            }
             DepTreePageMsg::SetTab(new_tab) => {
                 self.tab = new_tab;
             }
             DepTreePageMsg::SetFilter(index, new_checked) => {
                 self.filters[index].1 = new_checked;
             }
+            DepTreePageMsg::SetClassFilter(index, new_checked) => {
+                self.class_filters[index].1 = new_checked;
+            }
             DepTreePageMsg::SetSortType(new_sort) => {
                 self.graph_sort_type = new_sort;
             }
+            DepTreePageMsg::SetColorMode(new_color_mode) => {
+                self.color_mode = new_color_mode;
+            }
+            DepTreePageMsg::SetDirection(new_direction) => {
+                self.direction = new_direction;
+            }
             DepTreePageMsg::ToggleSortOrder => {
                 self.graph_sort_order.toggle();
             }
             DepTreePageMsg::ShowFooterInfo(new_footer_info) => {
                 self.footer_asset_show_min_info = new_footer_info;
             }
+            DepTreePageMsg::ResizeFooter(pane_grid::ResizeEvent { split, ratio }) => {
+                self.panes.resize(&split, ratio);
+            }
+            DepTreePageMsg::ShowContextMenu(node_id, position) => {
+                self.context_menu = Some((node_id, position));
+            }
+            DepTreePageMsg::HideContextMenu => {
+                self.context_menu = None;
+            }
+            DepTreePageMsg::ContextMenuAction(action) => {
+                self.context_menu = None;
+
+                return self.update(*action, asset_dirs, clipboard);
+            }
+            DepTreePageMsg::ReRoot(node_id) => {
+                let Some(node) = self
+                    .dep_tree
+                    .as_ref()
+                    .and_then(|dep_tree| dep_tree.get_node(node_id))
+                else {
+                    return Command::none();
+                };
+
+                // Keep the project directories, so the dependencies of engine assets resolve the same way
+                return self.build_in_background(AssetDirs {
+                    asset_file_path: Some(node.path.clone()),
+                    ..asset_dirs.clone()
+                });
+            }
+            DepTreePageMsg::RevealInFolder(node_id) => {
+                if let Some(node) = self
+                    .dep_tree
+                    .as_ref()
+                    .and_then(|dep_tree| dep_tree.get_node(node_id))
+                {
+                    reveal_in_folder(&node.path);
+                }
+            }
+            DepTreePageMsg::HighlightPathToRoot(node_id) => {
+                self.highlighted = self
+                    .shown_dep_tree()
+                    .map(|dep_tree| dep_tree.path_to_root(node_id).into_iter().collect())
+                    .unwrap_or_default();
+            }
+            DepTreePageMsg::ClearHighlight => {
+                self.highlighted.clear();
+            }
+            DepTreePageMsg::OpenInEditor(_) => {}
+            DepTreePageMsg::CopyGraphDot => {
+                let Some(graph) = self.visible_graph(asset_dirs) else {
+                    return Command::none();
+                };
+
+                save_to_clipboard(clipboard, graph.print(&mut PrinterContext::default()));
+            }
+            DepTreePageMsg::ExportGraphSvg => {
+                let (Some(graph), Some(dep_tree)) =
+                    (self.visible_graph(asset_dirs), self.shown_dep_tree())
+                else {
+                    return Command::none();
+                };
+
+                let file_name = format!(
+                    "{}_deptree.svg",
+                    fix_file_name(dep_tree.get_root_node().path.file_name())
+                );
+
+                return Command::perform(
+                    async move {
+                        let graph_svg = exec(
+                            graph,
+                            &mut PrinterContext::default(),
+                            vec![CommandArg::Format(Format::Svg)],
+                        )
+                        .map_err(|err| format!("Graphviz failed: {}", err))?;
+
+                        file_picker::save(file_name, graph_svg).await
+                    },
+                    DepTreePageMsg::ExportFinished,
+                );
+            }
+            DepTreePageMsg::ClearExportStatus => {
+                self.export_status = None;
+            }
+            DepTreePageMsg::CopySummary => {
+                if let Some(dep_tree) = &self.dep_tree {
+                    save_to_clipboard(
+                        clipboard,
+                        format!("{}Copied at {}", dep_tree.summary(), crate::timestamp()),
+                    );
+                }
+            }
+            DepTreePageMsg::CopyVisibleReferences => {
+                let Some(dep_tree) = self.shown_dep_tree() else {
+                    return Command::none();
+                };
+
+                let node_ids = self.visible_node_ids(asset_dirs);
+                let references = node_ids
+                    .iter()
+                    .filter_map(|&node_id| dep_tree.get_node(node_id))
+                    .filter_map(|node| node.reference(asset_dirs))
+                    .collect_vec();
+
+                // The paths would break the pasted lists, so the assets outside the known content dirs are left out
+                if references.len() < node_ids.len() {
+                    log::warn!(
+                        "Couldn't make the references of {} assets",
+                        node_ids.len() - references.len()
+                    );
+                }
+
+                save_to_clipboard(clipboard, references.join("\n"));
+            }
             DepTreePageMsg::SaveToClipboard(text) => save_to_clipboard(clipboard, text),
+            DepTreePageMsg::CopyNode(node_id, copy_format) => {
+                if let Some(node) = self
+                    .dep_tree
+                    .as_ref()
+                    .and_then(|dep_tree| dep_tree.get_node(node_id))
+                {
+                    let text = match copy_format {
+                        CopyFormat::FileName => node.file_name_str(),
+                        CopyFormat::BaseName => node
+                            .path
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string()),
+                        CopyFormat::DiskPath => None,
+                        CopyFormat::ObjectPath => node.object_path(asset_dirs),
+                        CopyFormat::Reference => node.reference(asset_dirs),
+                    };
+
+                    save_to_clipboard(clipboard, text.unwrap_or_else(|| node.path_str()));
+                }
+            }
             DepTreePageMsg::SetMinGraphDepth(new_min_graph_depth) => match new_min_graph_depth {
                 Some(new_depth) => {
                     self.min_graph_depth = new_depth;
@@ -305,50 +834,582 @@ impl DepTreePage {
                 }
                 None => self.min_graph_depth_text = String::new(),
             },
-            DepTreePageMsg::SetShowChildren(new_graph_show_children) => {
-                self.graph_show_children = new_graph_show_children
+            DepTreePageMsg::SetExpandAll(expand_all) => {
+                self.expanded = match (expand_all, &self.dep_tree) {
+                    // Every node, so the dependents are expanded as well
+                    (true, Some(dep_tree)) => dep_tree.nodes.keys().copied().collect(),
+                    _ => HashSet::new(),
+                };
+            }
+            DepTreePageMsg::ToggleExpand(node_id) => {
+                if !self.expanded.remove(&node_id) {
+                    self.expanded.insert(node_id);
+                }
+            }
+            DepTreePageMsg::SetLeavesOnly(new_graph_leaves_only) => {
+                self.graph_leaves_only = new_graph_leaves_only;
+            }
+            DepTreePageMsg::SetUniqueNodes(new_unique_nodes) => {
+                self.unique_nodes = new_unique_nodes;
+            }
+            DepTreePageMsg::SetCollapseEnginePlugins(new_collapse_engine_plugins) => {
+                self.collapse_engine_plugins = new_collapse_engine_plugins;
+                self.update_aggregated_tree();
             }
             DepTreePageMsg::SetShowOnlyChanged(new_graph_show_only_changed) => {
                 self.graph_show_only_changed = new_graph_show_only_changed;
             }
+            DepTreePageMsg::SetFindObjectPath(new_text) => {
+                self.find_object_path_text = new_text;
+            }
+            DepTreePageMsg::FindObjectPath => {
+                let Some(dep_tree) = &self.dep_tree else {
+                    return Command::none();
+                };
+
+                match dep_tree
+                    .find_node_by_object_path(self.find_object_path_text.trim(), asset_dirs)
+                {
+                    Ok(node_id) => {
+                        self.find_object_path_error = None;
+
+                        return self.jump_to_node(node_id, asset_dirs);
+                    }
+                    Err(err) => {
+                        self.find_object_path_error = Some(err.to_string());
+                    }
+                }
+            }
+            DepTreePageMsg::JumpToNode(node_id) => {
+                self.tab = DepTreePageTab::Graph;
+                self.selected_node = Some(node_id);
+
+                return self.jump_to_node(node_id, asset_dirs);
+            }
+            DepTreePageMsg::MoveSelection(selection_move) => {
+                let nodes = self.visible_node_ids(asset_dirs);
+
+                if nodes.is_empty() {
+                    return Command::none();
+                }
+
+                let current_index = self
+                    .selected_node
+                    .and_then(|selected_node| nodes.iter().position(|id| *id == selected_node));
+                let index = match (selection_move, current_index) {
+                    (SelectionMove::First, _) | (SelectionMove::Next, None) => 0,
+                    (SelectionMove::Last, _) | (SelectionMove::Prev, None) => nodes.len() - 1,
+                    (SelectionMove::Next, Some(index)) => (index + 1).min(nodes.len() - 1),
+                    (SelectionMove::Prev, Some(index)) => index.saturating_sub(1),
+                };
+
+                self.selected_node = Some(nodes[index]);
+                self.footer_asset_show_min_info = Some((nodes[index], false));
+
+                return Self::snap_to_node(index, nodes.len());
+            }
+            DepTreePageMsg::ActivateSelection { re_root } => {
+                if let Some(node_id) = self.selected_node {
+                    return match re_root {
+                        true => self.update(DepTreePageMsg::ReRoot(node_id), asset_dirs, clipboard),
+                        false => self.update(
+                            DepTreePageMsg::ToggleExpand(node_id),
+                            asset_dirs,
+                            clipboard,
+                        ),
+                    };
+                }
+            }
+            DepTreePageMsg::SetSubtreeDepth(new_subtree_depth) => match new_subtree_depth {
+                Some(new_depth) => {
+                    self.subtree_depth = new_depth;
+                    self.subtree_depth_text = new_depth.to_string();
+                }
+                None => self.subtree_depth_text = String::new(),
+            },
+            DepTreePageMsg::ExportSubtree(node_id) => {
+                let Some(subtree) = self
+                    .dep_tree
+                    .as_ref()
+                    .and_then(|dep_tree| dep_tree.subtree(node_id, self.subtree_depth))
+                else {
+                    log::error!(
+                        "Failed to export the subtree of node {}: node not found",
+                        node_id
+                    );
+
+                    return Command::none();
+                };
+
+                let file_name = format!(
+                    "{}_subtree.dot",
+                    fix_file_name(subtree.get_root_node().path.file_name())
+                );
+                let graph: Graph = subtree.into();
+                let graph_dot = graph.print(&mut PrinterContext::default());
+
+                return Command::perform(
+                    file_picker::save(file_name, graph_dot),
+                    DepTreePageMsg::ExportFinished,
+                );
+            }
+            DepTreePageMsg::ExportFinished(res) => {
+                self.export_status = Some(match res {
+                    Ok(path) => {
+                        log::info!("Exported to {:?}", path);
+
+                        format!("Exported to {}", path_to_str(&path))
+                    }
+                    Err(err) => {
+                        log::error!("Failed to export: {}", err);
+
+                        format!("Failed to export: {}", err)
+                    }
+                });
+            }
         }
 
         Command::none()
     }
 
-    pub fn view(&self) -> Element<DepTreePageMsg> {
-        let controls = Self::controls(self.dep_tree.is_some(), &self.max_recurse_depth_text).into();
+    /// `copy_formats` are what clicking and ctrl-clicking the assets copies
+    pub fn view(
+        &self,
+        node_colors: NodeColors,
+        text_size: u16,
+        copy_formats: (CopyFormat, CopyFormat),
+    ) -> Element<DepTreePageMsg> {
+        let controls = Self::controls(
+            self.dep_tree.is_some(),
+            self.asset_dirs.asset_file_path.is_some(),
+            self.build_cancel.is_some(),
+            &self.build_status,
+            &self.max_recurse_depth_text,
+            self.unlimited_recurse_depth,
+            self.skip_engine,
+            &self.ignore_patterns_text,
+            self.watch,
+        )
+        .into();
 
         let (tab_bar, tab_body, footer) = Self::tabs(
             &self.asset_dirs,
-            &self.dep_tree,
+            self.shown_dep_tree(),
             self.tab,
             self.min_graph_depth,
             &self.min_graph_depth_text,
             &self.filters,
+            &self.class_filters,
             self.graph_sort_type,
             self.graph_sort_order,
-            self.graph_show_children,
+            self.color_mode,
+            self.direction,
+            &self.expanded,
             self.graph_show_only_changed,
+            self.graph_leaves_only,
+            self.unique_nodes,
+            self.collapse_engine_plugins,
             self.footer_asset_show_min_info,
+            self.selected_node,
+            &self.highlighted,
+            &self.subtree_depth_text,
+            &self.find_object_path_text,
+            &self.find_object_path_error,
+            node_colors,
+            text_size,
+            copy_formats,
+            self.footer_path_max_len(text_size),
+            self.graph_column_count(),
+        );
+
+        let mut res = Column::new().push(controls);
+
+        if let Some(export_status) = &self.export_status {
+            res = res.push(Text::new(export_status).size(text_size - 2));
+        }
+
+        if self.is_large_tree() {
+            res = res.push(Text::new("Large tree: children hidden by default").size(text_size - 2));
+        }
+
+        let tab_body = match footer {
+            Some(footer) => {
+                // The panes are only built while the grid is made, so each of them takes its element once
+                let elements = RefCell::new((Some(tab_body), Some(footer)));
+
+                PaneGrid::new(&self.panes, |_, pane| {
+                    let mut elements = elements.borrow_mut();
+                    let element = match pane {
+                        DepTreePane::Tabs => elements.0.take(),
+                        DepTreePane::Footer => elements.1.take(),
+                    };
+
+                    pane_grid::Content::new(
+                        element
+                            .unwrap_or_else(|| Space::new(Length::Shrink, Length::Shrink).into()),
+                    )
+                })
+                .spacing(10)
+                .on_resize(10, DepTreePageMsg::ResizeFooter)
+                .into()
+            }
+            None => tab_body,
+        };
+
+        res.push(tab_bar)
+            .push(tab_body)
+            .spacing(10)
+            .height(Length::Fill)
+            .width(Length::Fill)
+            .align_items(Alignment::Center)
+            .into()
+    }
+
+    /// Split between the tabs and the footer, with the tabs taking `ratio` of the height
+    pub fn panes(ratio: f32) -> pane_grid::State<DepTreePane> {
+        pane_grid::State::with_configuration(pane_grid::Configuration::Split {
+            axis: pane_grid::Axis::Horizontal,
+            ratio,
+            a: Box::new(pane_grid::Configuration::Pane(DepTreePane::Tabs)),
+            b: Box::new(pane_grid::Configuration::Pane(DepTreePane::Footer)),
+        })
+    }
+
+    /// Ids of the top level nodes of the graph tab, in the order they are shown
+    fn visible_node_ids(&self, asset_dirs: &AssetDirs) -> Vec<NodeID> {
+        let Some(dep_tree) = self.shown_dep_tree() else {
+            return vec![];
+        };
+
+        Self::visible_nodes(
+            asset_dirs,
+            dep_tree,
+            self.min_graph_depth,
+            &self.filters,
+            &self.class_filters,
+            self.graph_sort_type,
+            self.graph_sort_order,
+            self.direction,
+            self.graph_show_only_changed,
+            self.graph_leaves_only,
+        )
+        .into_iter()
+        .map(|(node_id, ..)| node_id)
+        .collect()
+    }
+
+    /// Graph of the top level nodes of the graph tab, with the current filters applied
+    fn visible_graph(&self, asset_dirs: &AssetDirs) -> Option<Graph> {
+        let visible_node_ids = self.visible_node_ids(asset_dirs).into_iter().collect();
+
+        self.shown_dep_tree().map(|dep_tree| {
+            dep_tree.to_graph(
+                Some(&visible_node_ids),
+                false,
+                &dep_tree.graphviz_colors(self.color_mode, |asset| asset_dirs.is_modified(asset)),
+                None,
+                None,
+                false,
+            )
+        })
+    }
+
+    /// Wraps the groups of the nodes into the columns of about the same height, the groups aren't split
+    fn graph_columns<'a>(
+        groups: Vec<Vec<Element<'a, DepTreePageMsg>>>,
+        columns: usize,
+    ) -> Element<'a, DepTreePageMsg> {
+        if columns <= 1 {
+            return Column::with_children(groups.into_iter().flatten().collect()).into();
+        }
+
+        let column_len = groups.iter().map(Vec::len).sum::<usize>().div_ceil(columns);
+
+        let mut wrapped = vec![vec![]];
+        for group in groups {
+            let column = wrapped.last_mut().unwrap();
+
+            if column.len() >= column_len && wrapped.len() < columns {
+                wrapped.push(group);
+            } else {
+                column.extend(group);
+            }
+        }
+
+        wrapped
+            .into_iter()
+            .fold(Row::new().spacing(20), |row, column| {
+                row.push(Column::with_children(column).width(Length::Fill))
+            })
+            .into()
+    }
+
+    /// How many columns of the graph fit the window, one if they aren't wrapped
+    fn graph_column_count(&self) -> usize {
+        match self.multi_column_graph {
+            true => (self.window_width / GRAPH_COLUMN_WIDTH).max(1) as usize,
+            false => 1,
+        }
+    }
+
+    /// Scrolls the graph to the top level node at the index
+    /// Shows the info of the node in the footer and scrolls to it if it's visible in the graph
+    fn jump_to_node(&mut self, node_id: NodeID, asset_dirs: &AssetDirs) -> Command<DepTreePageMsg> {
+        self.footer_asset_show_min_info = Some((node_id, false));
+
+        let nodes = self.visible_node_ids(asset_dirs);
+
+        match nodes.iter().position(|id| *id == node_id) {
+            Some(index) => Self::snap_to_node(index, nodes.len()),
+            None => Command::none(),
+        }
+    }
+
+    fn snap_to_node(index: usize, nodes_count: usize) -> Command<DepTreePageMsg> {
+        scrollable::snap_to(
+            Self::graph_scrollable_id(),
+            index as f32 / (nodes_count.max(2) - 1) as f32,
+        )
+    }
+
+    /// How many chars of a path fit in the footer at the current window width
+    fn footer_path_max_len(&self, text_size: u16) -> usize {
+        // The average char is about 0.6 of the font size wide, minus the label and the padding
+        ((self.window_width as f32 / ((text_size - 2) as f32 * 0.6)) as usize).saturating_sub(20)
+    }
+
+    /// If the tree has more nodes than the threshold, so its nodes are collapsed by default
+    pub fn is_large_tree(&self) -> bool {
+        self.dep_tree
+            .as_ref()
+            .map(|dep_tree| dep_tree.nodes.len() > self.large_tree_threshold)
+            .unwrap_or_default()
+    }
+
+    fn effective_max_recurse_depth(&self) -> u32 {
+        match self.unlimited_recurse_depth {
+            true => UNLIMITED_RECURSE_DEPTH,
+            false => self.max_recurse_depth,
+        }
+    }
+
+    /// Modification time of the root asset of the tree
+    fn root_modified(&self) -> Option<SystemTime> {
+        let dep_tree = self.dep_tree.as_ref()?;
+
+        std::fs::metadata(&dep_tree.get_root_node().path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Tree shown in the graph tab, the one with the engine plugins collapsed if it's on
+    fn shown_dep_tree(&self) -> Option<&DepTree> {
+        match self.collapse_engine_plugins {
+            true => self.aggregated_dep_tree.as_ref(),
+            false => self.dep_tree.as_ref(),
+        }
+    }
+
+    /// Collapses the engine plugins of the tree again, after it changed or the option was toggled
+    fn update_aggregated_tree(&mut self) {
+        self.aggregated_dep_tree = match (self.collapse_engine_plugins, &self.dep_tree) {
+            (true, Some(dep_tree)) => Some(dep_tree.collapse_engine_plugins()),
+            _ => None,
+        };
+    }
+
+    /// Syncs the class filters with the classes found in the tree, keeping the states of the known ones
+    fn update_class_filters(&mut self) {
+        let Some(dep_tree) = &self.dep_tree else {
+            return;
+        };
+
+        self.class_filters = dep_tree
+            .classes()
+            .into_iter()
+            .map(|class| {
+                let on = self
+                    .class_filters
+                    .iter()
+                    .find(|(old_class, _)| old_class == &class)
+                    .map(|(_, on)| *on)
+                    .unwrap_or(true);
+
+                (class, on)
+            })
+            .collect();
+    }
+
+    /// Builds the tree on another thread, so the UI stays responsive and the build can be cancelled
+    fn build_in_background(&mut self, asset_dirs: AssetDirs) -> Command<DepTreePageMsg> {
+        // Only one build runs at a time, the page keeps the cancel flag of a single one
+        if self.build_cancel.is_some() {
+            return Command::none();
+        }
+
+        let build_cancel = Arc::new(AtomicBool::new(false));
+        let max_recurse_depth = self.effective_max_recurse_depth();
+        let asset_dirs = AssetDirs {
+            skip_engine: self.skip_engine,
+            ignore_patterns: self.ignore_patterns.clone(),
+            ..asset_dirs
+        };
+
+        self.build_cancel = Some(build_cancel.clone());
+        self.build_status = Some("Building the dependency tree...".to_string());
+
+        Command::perform(
+            async move {
+                DepTree::build(
+                    &asset_dirs,
+                    max_recurse_depth,
+                    TraversalOrder::default(),
+                    None,
+                    Some(&build_cancel),
+                )
+            },
+            |res| DepTreePageMsg::DependencyTreeBuilt(Arc::new(res)),
+        )
+    }
+
+    /// Re-reads the node on another thread like [`DepTreePage::build_in_background`], keeping the shown tree
+    /// until the refreshed copy is ready
+    fn rebuild_in_background(
+        &mut self,
+        node_id: NodeID,
+        asset_dirs: AssetDirs,
+    ) -> Command<DepTreePageMsg> {
+        if self.build_cancel.is_some() {
+            return Command::none();
+        }
+
+        let Some(mut dep_tree) = self.dep_tree.clone() else {
+            return Command::none();
+        };
+
+        let build_cancel = Arc::new(AtomicBool::new(false));
+        let asset_dirs = AssetDirs {
+            skip_engine: self.skip_engine,
+            ignore_patterns: self.ignore_patterns.clone(),
+            ..asset_dirs
+        };
+
+        self.build_cancel = Some(build_cancel.clone());
+        self.build_status = Some("Refreshing the dependency tree...".to_string());
+
+        Command::perform(
+            async move {
+                dep_tree
+                    .rebuild_node(node_id, &asset_dirs, Some(&build_cancel))
+                    .map(|_| dep_tree)
+            },
+            |res| DepTreePageMsg::DependencyTreeRefreshed(Arc::new(res)),
+        )
+    }
+
+    /// Drops the tree of the previous asset, cancelling its build if it's still running
+    pub fn clear_dep_tree(&mut self) {
+        if let Some(build_cancel) = &self.build_cancel {
+            build_cancel.store(true, Ordering::Release);
+        }
+
+        self.dep_tree = None;
+        self.aggregated_dep_tree = None;
+        self.footer_asset_show_min_info = None;
+        self.selected_node = None;
+        self.context_menu = None;
+        self.highlighted.clear();
+    }
+
+    /// Actions for the node the context menu is opened for
+    pub fn context_menu_view(&self) -> Element<DepTreePageMsg> {
+        let Some((node_id, node)) = self.context_menu.and_then(|(node_id, _)| {
+            self.dep_tree
+                .as_ref()
+                .and_then(|dep_tree| dep_tree.get_node(node_id))
+                .map(|node| (node_id, node))
+        }) else {
+            return Column::new().into();
+        };
+
+        let object_path = disk_path_to_object_path(
+            &node.path,
+            &self.asset_dirs.content_dir,
+            &self.asset_dirs.engine_content_dir,
+            &self.asset_dirs.plugins_dirs,
         );
 
-        let mut res = Column::with_children(vec![controls, tab_bar, tab_body])
-            .spacing(10)
-            .height(Length::Fill)
-            .width(Length::Fill)
-            .align_items(Alignment::Center);
+        let actions = [
+            (
+                "Copy name",
+                node.file_name_str().map(DepTreePageMsg::SaveToClipboard),
+            ),
+            (
+                "Copy path",
+                Some(DepTreePageMsg::SaveToClipboard(node.path_str())),
+            ),
+            (
+                "Copy object path",
+                object_path.map(DepTreePageMsg::SaveToClipboard),
+            ),
+            (
+                "Show info",
+                Some(DepTreePageMsg::ShowFooterInfo(Some((node_id, false)))),
+            ),
+            ("Re-root", Some(DepTreePageMsg::ReRoot(node_id))),
+            (
+                "Reveal in folder",
+                Some(DepTreePageMsg::RevealInFolder(node_id)),
+            ),
+            (
+                "Highlight path to root",
+                Some(DepTreePageMsg::HighlightPathToRoot(node_id)),
+            ),
+            (
+                "Clear highlight",
+                (!self.highlighted.is_empty()).then_some(DepTreePageMsg::ClearHighlight),
+            ),
+            (
+                "Open in editor",
+                Some(DepTreePageMsg::OpenInEditor(node.path.clone())),
+            ),
+            (
+                "Show dependents",
+                Some(DepTreePageMsg::ShowFooterInfo(Some((node_id, true)))),
+            ),
+        ];
+
+        let menu = actions
+            .into_iter()
+            .fold(Column::new().spacing(2), |menu, (text, action)| {
+                let mut button = Button::new(Text::new(text).size(15)).width(Length::Units(180));
 
-        if let Some(footer) = footer {
-            res = res.push(footer);
-        }
+                if let Some(action) = action {
+                    button = button.on_press(DepTreePageMsg::ContextMenuAction(Box::new(action)));
+                }
 
-        res.into()
+                menu.push(button)
+            });
+
+        menu.push(
+            Button::new(Text::new("Close").size(15))
+                .width(Length::Units(180))
+                .on_press(DepTreePageMsg::HideContextMenu),
+        )
+        .into()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn controls<'a>(
         dep_tree_exists: bool,
+        asset_picked: bool,
+        building: bool,
+        build_status: &Option<String>,
         max_recurse_depth_text: &str,
+        unlimited_recurse_depth: bool,
+        skip_engine: bool,
+        ignore_patterns_text: &str,
+        watch: bool,
     ) -> Row<'a, DepTreePageMsg> {
         let max_recurse_limit = Self::text_with_input(
             "Max Recursion Depth: ",
@@ -361,15 +1422,62 @@ impl DepTreePage {
         )
         .into();
 
-        let gen_tree_button =
-            Button::new(Text::new("Generate").horizontal_alignment(Horizontal::Center))
-                .width(Length::Units(150))
-                .on_press(DepTreePageMsg::GenerateDependencyTree)
+        let unlimited_checkbox = Checkbox::new(
+            unlimited_recurse_depth,
+            "Unlimited",
+            DepTreePageMsg::SetUnlimitedRecurseDepth,
+        )
+        .spacing(5)
+        .into();
+
+        let skip_engine_checkbox =
+            Checkbox::new(skip_engine, "Skip Engine", DepTreePageMsg::SetSkipEngine)
+                .spacing(5)
                 .into();
 
-        let mut widgets = vec![max_recurse_limit, gen_tree_button];
+        let ignore_patterns_input = TextInput::new(
+            "Ignore, e.g. /Engine/*Default*, ...",
+            ignore_patterns_text,
+            DepTreePageMsg::SetIgnorePatterns,
+        )
+        .width(Length::Units(250))
+        .size(15)
+        .padding([5, 10])
+        .into();
+
+        let (gen_tree_text, gen_tree_msg) = match (building, dep_tree_exists) {
+            (true, _) => ("Cancel", DepTreePageMsg::CancelBuild),
+            (false, true) => ("Refresh", DepTreePageMsg::RefreshDependencyTree),
+            (false, false) => ("Generate", DepTreePageMsg::GenerateDependencyTree),
+        };
+
+        // The cleared depth input would leave the previous depth in use without showing it
+        let depth_set = unlimited_recurse_depth || !max_recurse_depth_text.is_empty();
+
+        let gen_tree_button =
+            Button::new(Text::new(gen_tree_text).horizontal_alignment(Horizontal::Center))
+                .width(Length::Units(150));
+        // Nothing to build the tree from until an asset is picked
+        let gen_tree_button = match building || (asset_picked && depth_set) {
+            true => gen_tree_button.on_press(gen_tree_msg),
+            false => gen_tree_button,
+        }
+        .into();
 
-        if dep_tree_exists {
+        let watch_checkbox = Checkbox::new(watch, "Watch", DepTreePageMsg::SetWatch)
+            .spacing(5)
+            .into();
+
+        let mut widgets = vec![
+            max_recurse_limit,
+            unlimited_checkbox,
+            skip_engine_checkbox,
+            ignore_patterns_input,
+            gen_tree_button,
+            watch_checkbox,
+        ];
+
+        if dep_tree_exists && !building {
             let clear_tree_button =
                 Button::new(Text::new("Clear").horizontal_alignment(Horizontal::Center))
                     .width(Length::Units(150))
@@ -379,6 +1487,14 @@ impl DepTreePage {
             widgets.push(clear_tree_button);
         }
 
+        if let Some(build_status) = build_status {
+            widgets.push(Text::new(build_status.clone()).into());
+        } else if !asset_picked && !building {
+            widgets.push(Text::new("Pick an asset to generate its dependency tree").into());
+        } else if !depth_set && !building {
+            widgets.push(Text::new("Set the max recursion depth or check Unlimited").into());
+        }
+
         Row::with_children(widgets)
             .spacing(20)
             .align_items(Alignment::Center)
@@ -407,6 +1523,10 @@ impl DepTreePage {
         container
     }
 
+    fn graph_scrollable_id() -> scrollable::Id {
+        scrollable::Id::new("dep_tree_graph")
+    }
+
     fn only_numeric_chars(str: &str) -> String {
         str.chars()
             .filter(|char| char.is_numeric())
@@ -417,19 +1537,37 @@ impl DepTreePage {
     fn tabs<'a>(
         asset_dirs: &AssetDirs,
 
-        dep_tree: &'a Option<DepTree>,
+        dep_tree: Option<&'a DepTree>,
         tab: DepTreePageTab,
 
         min_graph_depth: u32,
         min_graph_depth_text: &str,
 
         filters: &'a [(AssetOrigin, bool)],
+        class_filters: &'a [(Option<String>, bool)],
         graph_sort_type: DepTreePageGraphSortType,
         graph_sort_order: SortOrder,
-        graph_show_children: bool,
+        color_mode: ColorMode,
+        direction: Direction,
+        expanded: &'a HashSet<NodeID>,
         graph_show_only_changed: bool,
+        graph_leaves_only: bool,
+        unique_nodes: bool,
+        collapse_engine_plugins: bool,
 
         footer_asset_show_min_info: Option<(NodeID, bool)>,
+        selected_node: Option<NodeID>,
+        highlighted: &'a HashSet<NodeID>,
+        subtree_depth_text: &str,
+
+        find_object_path_text: &str,
+        find_object_path_error: &Option<String>,
+
+        node_colors: NodeColors,
+        text_size: u16,
+        copy_formats: (CopyFormat, CopyFormat),
+        footer_path_max_len: usize,
+        graph_columns: usize,
     ) -> (
         Element<'a, DepTreePageMsg>,
         Element<'a, DepTreePageMsg>,
@@ -455,26 +1593,85 @@ impl DepTreePage {
             let (tab_body, footer) = match dep_tree {
                 Some(dep_tree) => match tab {
                     DepTreePageTab::Graph => {
-                        let (min_depth_filters_sort, graph, nodes_count, graph_info) =
+                        let (min_depth_filters_sort, graph, (nodes_count, rows_count), graph_info) =
                             Self::graph_tab(
                                 asset_dirs,
                                 dep_tree,
                                 min_graph_depth,
                                 min_graph_depth_text,
                                 filters,
+                                class_filters,
                                 graph_sort_type,
                                 graph_sort_order,
-                                graph_show_children,
+                                color_mode,
+                                direction,
+                                expanded,
                                 graph_show_only_changed,
+                                graph_leaves_only,
+                                unique_nodes,
+                                collapse_engine_plugins,
                                 footer_asset_show_min_info,
+                                selected_node,
+                                highlighted,
+                                subtree_depth_text,
+                                node_colors,
+                                text_size,
+                                copy_formats,
+                                footer_path_max_len,
+                                graph_columns,
+                            );
+
+                        let mut find_object_path = Row::new()
+                            .push(
+                                TextInput::new(
+                                    "Find by object path (e.g. /Game/Characters/Hero)",
+                                    find_object_path_text,
+                                    DepTreePageMsg::SetFindObjectPath,
+                                )
+                                .on_submit(DepTreePageMsg::FindObjectPath)
+                                .width(Length::Units(500))
+                                .size(text_size - 1)
+                                .padding([5, 10]),
+                            )
+                            .push(
+                                Button::new(Text::new("Find"))
+                                    .on_press(DepTreePageMsg::FindObjectPath),
+                            )
+                            .spacing(10)
+                            .align_items(Alignment::Center);
+
+                        if let Some(find_object_path_error) = find_object_path_error {
+                            find_object_path = find_object_path.push(
+                                Text::new(find_object_path_error.clone())
+                                    .size(text_size - 2)
+                                    .style(node_colors.unknown),
                             );
+                        }
 
                         let body = Column::with_children(Vec::from([
                             min_depth_filters_sort,
                             Space::with_height(Length::Units(10)).into(),
-                            Text::new(format!("Nodes: {}", nodes_count))
-                                .horizontal_alignment(Horizontal::Center)
-                                .into(),
+                            find_object_path.into(),
+                            Space::with_height(Length::Units(10)).into(),
+                            row![
+                                Text::new(match rows_count == nodes_count {
+                                    true => format!("Nodes: {}", nodes_count),
+                                    false =>
+                                        format!("Nodes: {} ({} rows)", nodes_count, rows_count),
+                                })
+                                .horizontal_alignment(Horizontal::Center),
+                                Button::new(Text::new("Copy as Graphviz dot"))
+                                    .on_press(DepTreePageMsg::CopyGraphDot),
+                                Button::new(Text::new("Export SVG"))
+                                    .on_press(DepTreePageMsg::ExportGraphSvg),
+                                Button::new(Text::new("Copy summary"))
+                                    .on_press(DepTreePageMsg::CopySummary),
+                                Button::new(Text::new("Copy references"))
+                                    .on_press(DepTreePageMsg::CopyVisibleReferences),
+                            ]
+                            .spacing(20)
+                            .align_items(Alignment::Center)
+                            .into(),
                             Space::with_height(Length::Units(10)).into(),
                             graph,
                         ]))
@@ -483,7 +1680,9 @@ impl DepTreePage {
 
                         (body, graph_info)
                     }
-                    DepTreePageTab::Failures => Self::failures_tab(viewer, dep_tree),
+                    DepTreePageTab::Failures => {
+                        Self::failures_tab(viewer, dep_tree, node_colors, text_size)
+                    }
                 },
                 None => (
                     viewer
@@ -508,16 +1707,32 @@ impl DepTreePage {
         min_graph_depth_text: &str,
 
         filters: &'a [(AssetOrigin, bool)],
+        class_filters: &'a [(Option<String>, bool)],
         graph_sort_type: DepTreePageGraphSortType,
         graph_sort_order: SortOrder,
-        graph_show_children: bool,
+        color_mode: ColorMode,
+        direction: Direction,
+        expanded: &'a HashSet<NodeID>,
         graph_show_only_changed: bool,
+        graph_leaves_only: bool,
+        unique_nodes: bool,
+        collapse_engine_plugins: bool,
 
         footer_asset_show_min_info: Option<(NodeID, bool)>,
+        selected_node: Option<NodeID>,
+        highlighted: &'a HashSet<NodeID>,
+        subtree_depth_text: &str,
+
+        node_colors: NodeColors,
+        text_size: u16,
+        copy_formats: (CopyFormat, CopyFormat),
+        footer_path_max_len: usize,
+        graph_columns: usize,
     ) -> (
         Element<'a, DepTreePageMsg>,
         Element<'a, DepTreePageMsg>,
-        usize,
+        // Shown assets and rows, the shared dependencies take several rows unless only the unique nodes are shown
+        (usize, usize),
         Option<Element<'a, DepTreePageMsg>>,
     ) {
         let mut show_only_changed_show_children_min_depth_filters_sort =
@@ -526,9 +1741,30 @@ impl DepTreePage {
         let mut show_only_changed_show_children_min_depth =
             Vec::<Element<'a, DepTreePageMsg>>::from([
                 Checkbox::new(
-                    graph_show_children,
-                    "Show Node Children",
-                    DepTreePageMsg::SetShowChildren,
+                    !dep_tree.node_connections.is_empty()
+                        && dep_tree
+                            .nodes
+                            .keys()
+                            .all(|node_id| expanded.contains(node_id)),
+                    "Expand All Nodes",
+                    DepTreePageMsg::SetExpandAll,
+                )
+                .spacing(5)
+                .into(),
+                Checkbox::new(
+                    graph_leaves_only,
+                    "Leaves Only",
+                    DepTreePageMsg::SetLeavesOnly,
+                )
+                .spacing(5)
+                .into(),
+                Checkbox::new(unique_nodes, "Unique Nodes", DepTreePageMsg::SetUniqueNodes)
+                    .spacing(5)
+                    .into(),
+                Checkbox::new(
+                    collapse_engine_plugins,
+                    "Collapse Engine Plugins",
+                    DepTreePageMsg::SetCollapseEnginePlugins,
                 )
                 .spacing(5)
                 .into(),
@@ -540,7 +1776,7 @@ impl DepTreePage {
                             Self::only_numeric_chars(&new_number)
                                 .parse()
                                 .ok()
-                                .map(|num: u32| num.clamp(0, dep_tree.max_recurse_depth)),
+                                .map(|num: u32| num.min(dep_tree.depth())),
                         )
                     },
                 )
@@ -597,10 +1833,27 @@ impl DepTreePage {
             },
         );
 
+        let class_filters = Scrollable::new(class_filters.iter().enumerate().fold(
+            Column::new().spacing(5),
+            |column, (index, (class, on))| {
+                column.push(
+                    Checkbox::new(
+                        *on,
+                        class.clone().unwrap_or_else(|| "...Unknown...".to_string()),
+                        move |new_checked| DepTreePageMsg::SetClassFilter(index, new_checked),
+                    )
+                    .spacing(5),
+                )
+            },
+        ))
+        .height(Length::Units(60));
+
         let show_only_changed_show_children_min_depth_filters_sort =
             show_only_changed_show_children_min_depth_filters_sort
                 .push(min_depth_filters)
                 .push(Space::with_width(Length::Units(5)))
+                .push(class_filters)
+                .push(Space::with_width(Length::Units(5)))
                 .push(
                     PickList::new(
                         all::<DepTreePageGraphSortType>().collect_vec(),
@@ -618,116 +1871,139 @@ impl DepTreePage {
                     })
                     .on_press(DepTreePageMsg::ToggleSortOrder),
                 )
+                .push(Space::with_width(Length::Units(5)))
+                .push(
+                    PickList::new(
+                        all::<Direction>().collect_vec(),
+                        Some(direction),
+                        DepTreePageMsg::SetDirection,
+                    )
+                    .width(Length::Shrink)
+                    .padding([5, 10])
+                    .text_size(16),
+                )
+                .push(Space::with_width(Length::Units(5)))
+                .push(Text::new("Color By:"))
+                .push(
+                    PickList::new(
+                        ColorMode::ALL.to_vec(),
+                        Some(color_mode),
+                        DepTreePageMsg::SetColorMode,
+                    )
+                    .width(Length::Shrink)
+                    .padding([5, 10])
+                    .text_size(16),
+                )
                 .into();
 
-        let nodes = dep_tree
-            .nodes
-            .iter()
-            .filter_map(|(&node_id, asset)| {
-                let main_check = dep_tree.get_recurse_depth(node_id).unwrap_or_default()
-                    >= min_graph_depth
-                    && filters.contains(&(asset.origin, true));
-
-                if !main_check {
-                    None
-                } else {
-                    let has_changed_in_git_repo = asset_dirs
-                        .get_git_repo(asset.origin)
-                        .as_ref()
-                        .and_then(|repo| {
-                            asset_dirs
-                                .get_relative_path(asset)
-                                .and_then(|relative_path| repo.status_file(&relative_path).ok())
-                        })
-                        .map(|status| status.is_index_modified() || status.is_wt_modified())
-                        .unwrap_or_default();
+        let nodes = Self::visible_nodes(
+            asset_dirs,
+            dep_tree,
+            min_graph_depth,
+            filters,
+            class_filters,
+            graph_sort_type,
+            graph_sort_order,
+            direction,
+            graph_show_only_changed,
+            graph_leaves_only,
+        );
 
-                    match graph_show_only_changed {
-                        true => match has_changed_in_git_repo {
-                            true => Some((node_id, asset, true)),
-                            false => None,
-                        },
-                        false => Some((node_id, asset, has_changed_in_git_repo)),
-                    }
-                }
-            })
-            .map(|(id, asset, has_changed)| {
-                (id, asset, has_changed, dep_tree.get_node_connections(id))
-            })
-            .sorted_by(|(id1, asset1, _, cons1), (id2, asset2, _, cons2)| {
-                let ordering = match graph_sort_type {
-                    DepTreePageGraphSortType::Id => id1.cmp(id2),
-                    DepTreePageGraphSortType::Filename => {
-                        asset1.path.file_name().cmp(&asset2.path.file_name())
-                    }
-                    DepTreePageGraphSortType::Path => asset1.path.cmp(&asset2.path),
-                    DepTreePageGraphSortType::NumDeps => cons1.len().cmp(&cons2.len()),
-                };
+        let color_gradient = dep_tree.color_gradient(color_mode);
 
-                match graph_sort_order {
-                    SortOrder::Ascending => ordering,
-                    SortOrder::Descending => ordering.reverse(),
-                }
-            })
-            .collect_vec();
+        // Assets that already have a row, the top level ones are all shown before their children
+        let mut shown_nodes = nodes
+            .iter()
+            .map(|(node_id, ..)| *node_id)
+            .collect::<HashSet<_>>();
+        let mut rows_count = nodes.len();
 
-        let nodes_count = nodes.len();
+        // Every top level node with its children, kept together when wrapped into columns
+        let groups = nodes
+            .into_iter()
+            .map(|(node_id, asset, has_changed, node_connections)| {
+                let node_expanded = expanded.contains(&node_id);
 
-        let graph = nodes.into_iter().fold(
-            vec![],
-            |mut graph, (node_id, asset, has_changed, node_connections)| {
-                graph.push(Self::asset_name_text(
-                    false,
+                let graph = vec![Self::asset_name_text(
+                    None,
+                    (!node_connections.is_empty()).then_some(node_expanded),
+                    selected_node == Some(node_id),
+                    (!highlighted.is_empty()).then(|| highlighted.contains(&node_id)),
                     node_id,
                     asset.clone(),
+                    dep_tree.aggregated.get(&node_id),
+                    (
+                        dep_tree.num_dependencies(node_id),
+                        dep_tree.num_dependents(node_id),
+                    ),
                     has_changed,
-                ));
+                    (color_mode, color_gradient.get(&node_id).copied()),
+                    node_colors,
+                    text_size,
+                    copy_formats,
+                )];
 
-                match graph_show_children {
+                match node_expanded {
                     true => {
                         let mut graph = node_connections
                             .iter()
                             .filter_map(|&con_node_id| {
                                 dep_tree.get_node(con_node_id).and_then(|con_node| {
-                                    let main_check = filters.contains(&(con_node.origin, true));
+                                    let main_check = filters.contains(&(con_node.origin, true))
+                                        && Self::class_enabled(class_filters, &con_node);
 
                                     if !main_check {
                                         None
                                     } else {
-                                        let has_changed_in_git_repo = asset_dirs
-                                            .get_git_repo(con_node.origin)
-                                            .as_ref()
-                                            .and_then(|repo| {
-                                                asset_dirs.get_relative_path(&con_node).and_then(
-                                                    |relative_path| {
-                                                        repo.status_file(&relative_path).ok()
-                                                    },
-                                                )
-                                            })
-                                            .map(|status| {
-                                                status.is_index_modified()
-                                                    || status.is_wt_modified()
-                                            })
-                                            .unwrap_or_default();
+                                        let has_changed_in_git_repo =
+                                            asset_dirs.is_modified(&con_node);
 
                                         match graph_show_only_changed {
                                             true => match has_changed_in_git_repo {
-                                                true => Some((node_id, con_node, true)),
+                                                true => Some((con_node_id, con_node, true)),
                                                 false => None,
                                             },
-                                            false => {
-                                                Some((node_id, con_node, has_changed_in_git_repo))
-                                            }
+                                            false => Some((
+                                                con_node_id,
+                                                con_node,
+                                                has_changed_in_git_repo,
+                                            )),
                                         }
                                     }
                                 })
                             })
+                            // Always recorded for the count, only skipped if each asset gets a single row
+                            .filter(|(con_node_id, ..)| {
+                                shown_nodes.insert(*con_node_id) || !unique_nodes
+                            })
                             .fold(graph, |mut graph, (con_node_id, con_asset, has_changed)| {
+                                rows_count += 1;
+
                                 graph.push(Self::asset_name_text(
-                                    true,
+                                    Some(match direction {
+                                        Direction::Dependencies => {
+                                            dep_tree.edge_kind(node_id, con_node_id)
+                                        }
+                                        Direction::Dependents => {
+                                            dep_tree.edge_kind(con_node_id, node_id)
+                                        }
+                                    }),
+                                    None,
+                                    false,
+                                    (!highlighted.is_empty())
+                                        .then(|| highlighted.contains(&con_node_id)),
                                     con_node_id,
                                     con_asset,
+                                    dep_tree.aggregated.get(&con_node_id),
+                                    (
+                                        dep_tree.num_dependencies(con_node_id),
+                                        dep_tree.num_dependents(con_node_id),
+                                    ),
                                     has_changed,
+                                    (color_mode, color_gradient.get(&con_node_id).copied()),
+                                    node_colors,
+                                    text_size,
+                                    copy_formats,
                                 ));
 
                                 graph
@@ -739,10 +2015,12 @@ impl DepTreePage {
                     }
                     false => graph,
                 }
-            },
-        );
+            })
+            .collect_vec();
 
-        let graph = Scrollable::new(Column::with_children(graph)).into();
+        let graph = Scrollable::new(Self::graph_columns(groups, graph_columns))
+            .id(Self::graph_scrollable_id())
+            .into();
 
         let graph_info = footer_asset_show_min_info.and_then(|(node_id, extended)| {
             let Some(node) = dep_tree.get_node(node_id) else {
@@ -750,31 +2028,112 @@ impl DepTreePage {
             };
 
             let dependencies = dep_tree.get_node_connections(node_id);
+            let dependents = dep_tree.parents(node_id);
+            let redirectors = dep_tree.get_redirectors_to(&node.path);
 
             Some(
                 Scrollable::new(
                     column![
-                        Text::new(format!("Full path: {:?}", node.path)).size(14),
-                        Text::new(format!("Origin: {:?}", node.origin)).size(14),
-                        Text::new(format!(
-                            "Parent Node: {}",
-                            dep_tree
-                                .get_parent_node_id(node_id)
-                                .and_then(|parent_node_id| dep_tree.get_node(parent_node_id).map(
-                                    |parent_node| format!(
-                                        "{} - {}",
-                                        parent_node_id,
-                                        parent_node
-                                            .file_name_str()
-                                            .unwrap_or_else(|| "...Unknown...".to_string())
+                        row![
+                            Self::text_with_input(
+                                "Subtree Depth:",
+                                subtree_depth_text,
+                                move |new_number| {
+                                    DepTreePageMsg::SetSubtreeDepth(
+                                        Self::only_numeric_chars(&new_number).parse().ok(),
                                     )
-                                ))
-                                .unwrap_or_else(|| "None".to_string())
+                                },
+                            ),
+                            Button::new(Text::new("Export Subtree"))
+                                .on_press(DepTreePageMsg::ExportSubtree(node_id)),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        Text::new(format!(
+                            "Full path: {}",
+                            ellipsize_middle(&node.path_str(), footer_path_max_len)
+                        ))
+                        .size(text_size - 2),
+                        row![
+                            Text::new(format!(
+                                "Object path: {}{}",
+                                node.object_path(asset_dirs)
+                                    .unwrap_or_else(|| "...Unknown...".to_string()),
+                                match node.origin {
+                                    // The mount point of a plugin is guessed from its directory name
+                                    AssetOrigin::ProjectPlugin | AssetOrigin::EnginePlugin =>
+                                        " (plugin mount inferred from its folder)",
+                                    AssetOrigin::Project | AssetOrigin::Engine => "",
+                                }
+                            ))
+                            .size(text_size - 2),
+                            Button::new(Text::new("Copy").size(text_size - 2)).on_press(
+                                DepTreePageMsg::CopyNode(node_id, CopyFormat::ObjectPath)
+                            ),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        Text::new(format!(
+                            "Size: {}, with dependencies: {}",
+                            format_size(node.file_size()),
+                            format_size(dep_tree.subtree_size(node_id))
+                        ))
+                        .size(text_size - 2),
+                        Text::new(match &node.origin_ambiguity {
+                            Some(reason) =>
+                                format!("Origin: {:?} (might be wrong: {})", node.origin, reason),
+                            None => format!("Origin: {:?}", node.origin),
+                        })
+                        .size(text_size - 2),
+                        Text::new(format!(
+                            "Class: {}",
+                            node.primary_class()
+                                .unwrap_or_else(|| "...Unknown...".to_string())
+                        ))
+                        .size(text_size - 2),
+                        Text::new(format!(
+                            "Engine version: {}, File version: {}",
+                            node.engine_version(),
+                            node.file_version()
+                        ))
+                        .size(text_size - 2),
+                        Text::new(format!(
+                            "Redirected from: {}",
+                            match redirectors.is_empty() {
+                                true => "None".to_string(),
+                                false => redirectors.iter().map(path_to_str).join(", "),
+                            }
                         ))
-                        .size(14),
+                        .size(text_size - 2),
                         Text::new(format!(
-                            "Dependencies: {}{}",
+                            "Parent Nodes: {}",
+                            match dependents.len() {
+                                0 => "None".to_string(),
+                                parents_count if parents_count > FOOTER_MAX_PARENTS =>
+                                    format!("{} parents", parents_count),
+                                _ => dependents
+                                    .iter()
+                                    .filter_map(|&parent_node_id| dep_tree
+                                        .get_node(parent_node_id)
+                                        .map(|parent_node| format!(
+                                            "{} - {}",
+                                            parent_node_id,
+                                            parent_node
+                                                .file_name_str()
+                                                .unwrap_or_else(|| "...Unknown...".to_string())
+                                        )))
+                                    .join(", "),
+                            }
+                        ))
+                        .size(text_size - 2),
+                        Text::new(format!(
+                            "Dependencies: {}{}{}",
                             dependencies.len(),
+                            dep_tree
+                                .truncated
+                                .get(&node_id)
+                                .map(|left_out| format!(" (+{} not followed)", left_out))
+                                .unwrap_or_default(),
                             match extended {
                                 true => format!(
                                     " ({})",
@@ -789,11 +2148,29 @@ impl DepTreePage {
                                 false => String::new(),
                             }
                         ))
-                        .size(14),
+                        .size(text_size - 2),
+                        Text::new(format!(
+                            "Dependents: {}{}",
+                            dependents.len(),
+                            match extended {
+                                true => format!(
+                                    " ({})",
+                                    dependents
+                                        .iter()
+                                        .map(|id| path_to_str(
+                                            &dep_tree.get_node(*id).unwrap().path
+                                        ))
+                                        .collect_vec()
+                                        .join(", ")
+                                ),
+                                false => String::new(),
+                            }
+                        ))
+                        .size(text_size - 2),
                     ]
                     .spacing(15),
                 )
-                .height(Length::Units(100))
+                .height(Length::Fill)
                 .into(),
             )
         });
@@ -801,78 +2178,327 @@ impl DepTreePage {
         (
             show_only_changed_show_children_min_depth_filters_sort,
             graph,
-            nodes_count,
+            (shown_nodes.len(), rows_count),
             graph_info,
         )
     }
 
+    fn class_enabled(class_filters: &[(Option<String>, bool)], asset: &Asset) -> bool {
+        class_filters
+            .iter()
+            .any(|(class, on)| *on && class.as_deref() == asset.primary_class.as_deref())
+    }
+
+    /// Nodes shown at the top level of the graph, filtered and sorted, with their git status and dependencies
+    #[allow(clippy::too_many_arguments)]
+    fn visible_nodes<'a>(
+        asset_dirs: &AssetDirs,
+        dep_tree: &'a DepTree,
+        min_graph_depth: u32,
+        filters: &[(AssetOrigin, bool)],
+        class_filters: &[(Option<String>, bool)],
+        graph_sort_type: DepTreePageGraphSortType,
+        graph_sort_order: SortOrder,
+        direction: Direction,
+        graph_show_only_changed: bool,
+        graph_leaves_only: bool,
+    ) -> Vec<(NodeID, &'a Arc<Asset>, bool, Vec<NodeID>)> {
+        let mut dependents = match direction {
+            Direction::Dependencies => HashMap::new(),
+            Direction::Dependents => dep_tree.dependents(),
+        };
+
+        dep_tree
+            .nodes
+            .iter()
+            .filter_map(|(&node_id, asset)| {
+                let main_check = dep_tree.get_recurse_depth(node_id).unwrap_or_default()
+                    >= min_graph_depth
+                    && filters.contains(&(asset.origin, true))
+                    && Self::class_enabled(class_filters, asset)
+                    && (!graph_leaves_only || dep_tree.is_leaf(node_id));
+
+                if !main_check {
+                    None
+                } else {
+                    let has_changed_in_git_repo = asset_dirs.is_modified(asset);
+
+                    match graph_show_only_changed {
+                        true => match has_changed_in_git_repo {
+                            true => Some((node_id, asset, true)),
+                            false => None,
+                        },
+                        false => Some((node_id, asset, has_changed_in_git_repo)),
+                    }
+                }
+            })
+            .map(|(id, asset, has_changed)| {
+                let connections = match direction {
+                    Direction::Dependencies => dep_tree.get_node_connections(id),
+                    Direction::Dependents => dependents.remove(&id).unwrap_or_default(),
+                };
+
+                (id, asset, has_changed, connections)
+            })
+            .sorted_by(|(id1, asset1, _, cons1), (id2, asset2, _, cons2)| {
+                let ordering = match graph_sort_type {
+                    DepTreePageGraphSortType::Id => id1.cmp(id2),
+                    DepTreePageGraphSortType::Filename => {
+                        asset1.path.file_name().cmp(&asset2.path.file_name())
+                    }
+                    DepTreePageGraphSortType::Path => asset1.path.cmp(&asset2.path),
+                    DepTreePageGraphSortType::NumDeps => cons1.len().cmp(&cons2.len()),
+                    DepTreePageGraphSortType::NumDependents => dep_tree
+                        .num_dependents(*id1)
+                        .cmp(&dep_tree.num_dependents(*id2)),
+                };
+
+                match graph_sort_order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            })
+            .collect_vec()
+    }
+
     fn failures_tab<'a>(
         viewer: Column<'a, DepTreePageMsg>,
         dep_tree: &'a DepTree,
+        node_colors: NodeColors,
+        text_size: u16,
     ) -> (
         Element<'a, DepTreePageMsg>,
         Option<Element<'a, DepTreePageMsg>>,
     ) {
-        (
-            dep_tree
-                .failures
-                .iter()
-                .fold(viewer, |viewer, failure| {
-                    viewer.push(Text::new(failure.to_string()).style(Color::from([0.9, 0.1, 0.1])))
-                })
-                .into(),
-            None,
+        let (ambiguous, failures): (Vec<_>, Vec<_>) = dep_tree
+            .real_failures()
+            .partition(|failure| failure.is_ambiguous());
+        let skipped = dep_tree.failures.len() - failures.len() - ambiguous.len();
+
+        let viewer = viewer
+            .push(Button::new(Text::new("Retry failures")).on_press(DepTreePageMsg::RetryFailures))
+            .push(Space::with_height(Length::Units(10)));
+
+        let viewer = match skipped {
+            0 => viewer,
+            _ => viewer
+                .push(Text::new(format!("Skipped dependencies: {}", skipped)).size(text_size))
+                .push(Space::with_height(Length::Units(10))),
+        };
+
+        let viewer = failures.into_iter().fold(viewer, |viewer, failure| {
+            let text = match dep_tree.retried_failures.contains(&failure.path) {
+                true => format!("{} (retried)", failure),
+                false => failure.to_string(),
+            };
+
+            let viewer = viewer.push(Text::new(text).size(text_size).style(node_colors.unknown));
+
+            let parents = dep_tree.failure_parents(failure);
+            match parents.is_empty() {
+                true => viewer,
+                false => viewer.push(Self::referenced_by(
+                    dep_tree,
+                    parents,
+                    node_colors,
+                    text_size,
+                )),
+            }
+        });
+
+        let viewer = match ambiguous.is_empty() {
+            true => viewer,
+            false => ambiguous.into_iter().fold(
+                viewer
+                    .push(Space::with_height(Length::Units(10)))
+                    .push(Text::new("Dependencies matching several assets:").size(text_size)),
+                |viewer, failure| {
+                    viewer.push(
+                        Text::new(failure.to_string())
+                            .size(text_size)
+                            .style(node_colors.modified),
+                    )
+                },
+            ),
+        };
+
+        let ambiguous_origins = dep_tree.ambiguous_origins();
+
+        let viewer = match ambiguous_origins.is_empty() {
+            true => viewer,
+            false => ambiguous_origins.into_iter().fold(
+                viewer
+                    .push(Space::with_height(Length::Units(10)))
+                    .push(Text::new("Assets whose origin might be wrong:").size(text_size)),
+                |viewer, (node_id, reason)| {
+                    let path = dep_tree.get_node(node_id).unwrap().path_str();
+
+                    viewer.push(
+                        Text::new(format!("{} - {}: {}", node_id, path, reason))
+                            .size(text_size)
+                            .style(node_colors.modified),
+                    )
+                },
+            ),
+        };
+
+        (viewer.into(), None)
+    }
+
+    /// Names of the nodes referencing a failure, clicking one jumps to it in the graph
+    fn referenced_by<'a>(
+        dep_tree: &'a DepTree,
+        parents: Vec<NodeID>,
+        node_colors: NodeColors,
+        text_size: u16,
+    ) -> Element<'a, DepTreePageMsg> {
+        let parent_texts = parents.into_iter().map(|parent_id| {
+            let name = dep_tree
+                .get_node(parent_id)
+                .and_then(|parent_node| parent_node.file_name_str())
+                .unwrap_or_else(|| "...Unknown...".to_string());
+
+            interactive_text_tooltip::<DepTreePageMsg>(
+                format!("{} - {}", parent_id, name),
+                Some(text_size - 2),
+                None,
+                Some(node_colors.child),
+                (Some(DepTreePageMsg::JumpToNode(parent_id)), None, None),
+                None,
+                (None, None, None),
+            )
+        });
+
+        Row::with_children(
+            std::iter::once(Text::new("    referenced by:").size(text_size - 2).into())
+                .chain(parent_texts)
+                .collect(),
         )
+        .spacing(10)
+        .into()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn asset_name_text<'state>(
-        connected: bool,
+        edge_kind: Option<EdgeKind>,
+        expanded: Option<bool>,
+        selected: bool,
+        // If the node is on the highlighted path, `None` when nothing is highlighted
+        highlighted: Option<bool>,
         node_id: NodeID,
-        asset: Rc<Asset>,
+        asset: Arc<Asset>,
+        aggregated: Option<&AggregatedNode>,
+        (num_dependencies, num_dependents): (usize, usize),
 
         has_changed_in_git_repo: bool,
+        // Color mode and the position of the node on its gradient
+        (color_mode, gradient_position): (ColorMode, Option<f32>),
+
+        node_colors: NodeColors,
+        text_size: u16,
+        copy_formats: (CopyFormat, CopyFormat),
     ) -> Element<'state, DepTreePageMsg> {
-        let name = asset.file_name_str();
+        // Collapsed groups are named after their key rather than the asset standing for them
+        let name = match aggregated {
+            Some(aggregated_node) => Some(format!(
+                "{} [{}]",
+                aggregated_node.name,
+                aggregated_node.badge()
+            )),
+            None => asset.file_name_str(),
+        };
         let name_known = name.is_some();
 
         let text = format!(
-            "{}{} - {}",
-            if connected { "└─── " } else { "" },
+            "{}{}{} - {}  ↓{} ↑{}",
+            if selected { "» " } else { "" },
+            match edge_kind {
+                Some(EdgeKind::Hard) => "└─── ",
+                Some(EdgeKind::Soft) => "└┄┄┄ ",
+                None => "",
+            },
             node_id,
-            name.clone().unwrap_or_else(|| "...Unknown...".to_string())
+            name.unwrap_or_else(|| "...Unknown...".to_string()),
+            num_dependencies,
+            num_dependents
         );
 
-        interactive_text_tooltip::<DepTreePageMsg>(
-            text.clone(),
-            None,
-            Some(if has_changed_in_git_repo {
-                [0.75, 0.75, 0.15]
-            } else if !name_known {
-                [0.8, 0.2, 0.2]
-            } else if connected {
-                [0.2, 0.2, 0.8]
-            } else {
-                [0.2, 0.8, 0.2]
+        let color = match color_mode {
+            ColorMode::Origin if has_changed_in_git_repo => node_colors.modified,
+            _ if !name_known => node_colors.unknown,
+            ColorMode::Origin => match edge_kind {
+                Some(EdgeKind::Hard) => node_colors.child,
+                Some(EdgeKind::Soft) => node_colors.soft,
+                None => node_colors.root,
+            },
+            ColorMode::GitStatus => match has_changed_in_git_repo {
+                true => node_colors.modified,
+                false => node_colors.child,
+            },
+            ColorMode::OutDegree | ColorMode::Depth => {
+                node_colors.gradient(gradient_position.unwrap_or_default())
+            }
+        };
+
+        let tooltip = ellipsize_middle(&asset.path_str(), TOOLTIP_MAX_PATH_LEN);
+
+        let name_text = interactive_text_tooltip::<DepTreePageMsg>(
+            text,
+            Some(text_size),
+            Some((tooltip, tooltip::Position::Bottom, Some(text_size - 2))),
+            Some(match highlighted {
+                Some(true) => node_colors.highlighted,
+                Some(false) => NodeColors::dim(color),
+                None => color,
             }),
             (
-                Some(DepTreePageMsg::SaveToClipboard(
-                    name.clone().unwrap_or_else(|| text.clone()),
-                )),
-                Some(DepTreePageMsg::SaveToClipboard(asset.path_str())),
-                name.map(|file_name_str| {
-                    DepTreePageMsg::SaveToClipboard(
-                        file_name_str
-                            .strip_suffix(".uasset")
-                            .map(String::from)
-                            .unwrap_or(file_name_str),
-                    )
-                }),
+                Some(DepTreePageMsg::CopyNode(node_id, copy_formats.0)),
+                Some(DepTreePageMsg::CopyNode(node_id, CopyFormat::DiskPath)),
+                Some(DepTreePageMsg::CopyNode(node_id, copy_formats.1)),
             ),
+            Some(Box::new(move |position| {
+                DepTreePageMsg::ShowContextMenu(node_id, position)
+            })),
             (
                 Some(DepTreePageMsg::ShowFooterInfo(Some((node_id, false)))),
                 Some(DepTreePageMsg::ShowFooterInfo(Some((node_id, true)))),
-                Some(DepTreePageMsg::ShowFooterInfo(None)),
+                // Keep the info of the last hovered node, so its footer actions stay reachable
+                None,
             ),
-        )
+        );
+
+        let class_icon =
+            IconText::new(Self::class_icon(asset.primary_class().as_deref())).size(text_size);
+
+        match expanded {
+            Some(expanded) => row![
+                interactive_text_tooltip::<DepTreePageMsg>(
+                    if expanded { "▼" } else { "▶" },
+                    Some(text_size),
+                    None,
+                    None::<Color>,
+                    (Some(DepTreePageMsg::ToggleExpand(node_id)), None, None),
+                    None,
+                    (None, None, None),
+                ),
+                class_icon,
+                name_text,
+            ]
+            .spacing(5)
+            .into(),
+            None => row![class_icon, name_text].spacing(5).into(),
+        }
+    }
+
+    /// Icon closest to the class of the asset, a generic file for the classes without one
+    fn class_icon(class: Option<&str>) -> Icon {
+        match class {
+            Some(class) if class.contains("Texture") => Icon::Image,
+            Some(class) if class.contains("Material") => Icon::Brush,
+            Some(class) if class.ends_with("Mesh") => Icon::Box,
+            Some(class) if class.contains("Blueprint") => Icon::Diagram3,
+            Some("World") => Icon::Map,
+            _ => Icon::FileEarmark,
+        }
     }
 }
@@ -36,6 +36,30 @@ pub async fn open(asset: bool) -> Option<PathBuf> {
     }
 }
 
+/// Asks the user for the Unreal Editor executable
+pub async fn open_editor() -> Option<PathBuf> {
+    let file = AsyncFileDialog::new()
+        .set_directory(dirs::home_dir().unwrap_or_default())
+        .pick_file()
+        .await?;
+
+    Some(file.path().to_path_buf())
+}
+
+/// Asks the user where to save the file and writes the contents to it
+pub async fn save(file_name: String, contents: String) -> Result<PathBuf, String> {
+    let file = AsyncFileDialog::new()
+        .set_directory(dirs::home_dir().unwrap_or_default())
+        .set_file_name(&file_name)
+        .save_file()
+        .await
+        .ok_or_else(|| "No file was picked".to_string())?;
+
+    std::fs::write(file.path(), contents).map_err(|err| err.to_string())?;
+
+    Ok(file.path().to_path_buf())
+}
+
 pub fn widget<'a, Message>(
     button_text: impl Into<Cow<'a, str>>,
     text: impl Into<Cow<'a, str>> + Clone,
@@ -54,9 +78,11 @@ where
 
     let text = interactive_text_tooltip(
         text,
+        None,
         tooltip,
         None::<Color>,
         (text_on_press, text_on_shift_press, text_on_ctrl_press),
+        None,
         (None, None, None),
     );
 
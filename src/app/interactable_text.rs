@@ -19,6 +19,8 @@ pub struct InteractiveText<'a, Message> {
     on_press: Option<Message>,
     on_shift_press: Option<Message>,
     on_ctrl_press: Option<Message>,
+    /// Gets the cursor position, e.g. to open a context menu there
+    on_right_press: Option<Box<dyn Fn(Point) -> Message + 'a>>,
 
     on_hover_in: Option<Message>,
     on_shift_hover: Option<Message>,
@@ -38,6 +40,7 @@ impl<'a, Message> InteractiveText<'a, Message> {
             on_press: None,
             on_shift_press: None,
             on_ctrl_press: None,
+            on_right_press: None,
 
             on_hover_in: None,
             on_shift_hover: None,
@@ -72,6 +75,13 @@ impl<'a, Message> InteractiveText<'a, Message> {
         }
     }
 
+    pub fn on_right_press(self, on_right_press: impl Fn(Point) -> Message + 'a) -> Self {
+        Self {
+            on_right_press: Some(Box::new(on_right_press)),
+            ..self
+        }
+    }
+
     pub fn on_hover_in(self, message: Message) -> Self {
         Self {
             on_hover_in: Some(message),
@@ -172,6 +182,13 @@ where
                         _ => {}
                     }
                 }
+                mouse::Event::ButtonPressed(mouse::Button::Right) if is_mouse_over => {
+                    if let Some(on_right_press) = &self.on_right_press {
+                        shell.publish(on_right_press(cursor_position));
+
+                        return Status::Captured;
+                    }
+                }
                 mouse::Event::CursorMoved { position } => {
                     let is_mouse_over = layout.bounds().contains(position);
 
@@ -265,9 +282,11 @@ where
 
 pub fn interactive_text_tooltip<'a, Message>(
     text: impl Into<Cow<'a, str>> + Clone,
+    size: Option<u16>,
     tooltip: Option<(String, tooltip::Position, Option<u16>)>,
     color: Option<impl Into<Color>>,
     (on_press, on_shift_press, on_ctrl_press): (Option<Message>, Option<Message>, Option<Message>),
+    on_right_press: Option<Box<dyn Fn(Point) -> Message + 'a>>,
     (on_hover_in, on_shift_hover, on_hover_out): (
         Option<Message>,
         Option<Message>,
@@ -277,7 +296,9 @@ pub fn interactive_text_tooltip<'a, Message>(
 where
     Message: Clone + 'a,
 {
-    let mut text_widget = Text::new(text).size(16).width(Length::Shrink);
+    let mut text_widget = Text::new(text)
+        .size(size.unwrap_or(16))
+        .width(Length::Shrink);
 
     if let Some(color) = color {
         text_widget = text_widget.style(color.into());
@@ -297,6 +318,10 @@ where
         text_widget = text_widget.on_ctrl_press(on_ctrl_press);
     }
 
+    if let Some(on_right_press) = on_right_press {
+        text_widget = text_widget.on_right_press(on_right_press);
+    }
+
     if let Some(on_hover_in) = on_hover_in {
         text_widget = text_widget.on_hover_in(on_hover_in);
     }
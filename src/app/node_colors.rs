@@ -0,0 +1,98 @@
+use iced::{theme::Palette, Color};
+
+/// Colors of the node texts in the dependency tree, derived from the active theme
+#[derive(Debug, Copy, Clone)]
+pub struct NodeColors {
+    /// Top level nodes of the graph
+    pub root: Color,
+    /// Child nodes shown under their parents
+    pub child: Color,
+    /// Child nodes that are only soft references of their parents
+    pub soft: Color,
+    /// Nodes with unknown names and failures
+    pub unknown: Color,
+    /// Nodes modified in the git repo
+    pub modified: Color,
+    /// Nodes on the highlighted path to the root
+    pub highlighted: Color,
+}
+
+impl NodeColors {
+    pub fn new(palette: Palette, colorblind: bool) -> Self {
+        match colorblind {
+            true => Self::colorblind(palette),
+            false => Self::from_palette(palette),
+        }
+    }
+
+    fn from_palette(palette: Palette) -> Self {
+        Self {
+            root: palette.success,
+            child: palette.primary,
+            soft: Color {
+                a: 0.6,
+                ..palette.primary
+            },
+            unknown: palette.danger,
+            // There's no yellow in the palette, so we mix it from the green and the red
+            modified: Color {
+                r: palette.success.r.max(palette.danger.r),
+                g: palette.success.g.max(palette.danger.g),
+                b: palette.success.b.min(palette.danger.b),
+                a: 1.0,
+            },
+            highlighted: palette.text,
+        }
+    }
+
+    /// Okabe-Ito colors, which stay distinguishable for the most common kinds of color blindness
+    fn colorblind(palette: Palette) -> Self {
+        let adjust = |color: Color| match Self::is_light(palette.background) {
+            true => Self::darken(color, 0.6),
+            false => color,
+        };
+
+        Self {
+            root: adjust(Color::from_rgb8(0x00, 0x9E, 0x73)),
+            child: adjust(Color::from_rgb8(0x56, 0xB4, 0xE9)),
+            soft: adjust(Color::from_rgb8(0xCC, 0x79, 0xA7)),
+            unknown: adjust(Color::from_rgb8(0xD5, 0x5E, 0x00)),
+            modified: adjust(Color::from_rgb8(0xF0, 0xE4, 0x42)),
+            highlighted: adjust(Color::from_rgb8(0xE6, 0x9F, 0x00)),
+        }
+    }
+
+    /// Color of the position on the gradient from the child color (0) to the unknown one (1)
+    pub fn gradient(&self, position: f32) -> Color {
+        let position = position.clamp(0.0, 1.0);
+        let lerp = |from: f32, to: f32| from + (to - from) * position;
+
+        Color {
+            r: lerp(self.child.r, self.unknown.r),
+            g: lerp(self.child.g, self.unknown.g),
+            b: lerp(self.child.b, self.unknown.b),
+            a: 1.0,
+        }
+    }
+
+    /// Fades the color out, for the nodes off the highlighted path
+    pub fn dim(color: Color) -> Color {
+        Color {
+            a: color.a * 0.35,
+            ..color
+        }
+    }
+
+    fn is_light(color: Color) -> bool {
+        0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b > 0.5
+    }
+
+    fn darken(color: Color, factor: f32) -> Color {
+        Color {
+            r: color.r * factor,
+            g: color.g * factor,
+            b: color.b * factor,
+            a: color.a,
+        }
+    }
+}
@@ -1,39 +1,193 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ffi::{OsStr, OsString},
     fmt::{Debug, Display},
     fs::File,
+    io::{Read, Seek, SeekFrom},
     path::{Path, PathBuf},
-    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use uasset::{AssetHeader, ImportIterator};
+use clap::ValueEnum;
+use itertools::Itertools;
+use uasset::{Archive, AssetHeader, ImportIterator};
 
-use crate::util::{path_to_str, SplitVecContainer};
+use crate::util::{
+    default_jobs, disk_path_to_object_path, find_descriptor, object_path_candidates, path_to_str,
+    SplitVecContainer,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AssetErrorKind {
+    NotFound,
+    WrongExtension,
+    Io(String),
+    /// The user can't read the file
+    PermissionDenied,
+    /// Another process has the file open exclusively, usually the editor on Windows
+    Locked,
+    ParseFailed(String),
+    RedirectChainTooLong,
+    UnresolvedRedirector,
+    /// The object path doesn't start with a `/`
+    MalformedObjectPath,
+    NoRootFolder,
+    MissingAssetFile,
+    MissingContentDir,
+    MissingEngineDir,
+    MissingInContent,
+    MissingInEngineContent,
+    NotInPlugins,
+    ScriptReference,
+    /// The dependency matches several files, contains all the candidates
+    Ambiguous(Vec<PathBuf>),
+    /// The dependency wasn't resolved on purpose, engine content with `--skip-engine`
+    EngineSkipped,
+    /// The dependency matches one of the ignore patterns
+    Ignored,
+    /// The dependency is of an origin excluded from the build
+    OriginExcluded,
+    NotInTree,
+    NodeNotInTree,
+    NoRoots,
+    Cancelled,
+}
+
+impl AssetErrorKind {
+    /// Tells the common reasons the files can't be opened apart from the rest of the IO errors
+    pub fn from_io_error(err: &std::io::Error) -> Self {
+        /// `ERROR_SHARING_VIOLATION` and `ERROR_LOCK_VIOLATION`
+        const WINDOWS_LOCKED_ERRORS: [i32; 2] = [32, 33];
+
+        match err.kind() {
+            std::io::ErrorKind::PermissionDenied => AssetErrorKind::PermissionDenied,
+            _ if cfg!(windows)
+                && err
+                    .raw_os_error()
+                    .map(|code| WINDOWS_LOCKED_ERRORS.contains(&code))
+                    .unwrap_or_default() =>
+            {
+                AssetErrorKind::Locked
+            }
+            _ => AssetErrorKind::Io(err.to_string()),
+        }
+    }
+}
+
+impl Display for AssetErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetErrorKind::NotFound => write!(f, "Could not find asset"),
+            AssetErrorKind::WrongExtension => {
+                write!(f, "File does not exist or is not a .uasset or .umap file")
+            }
+            AssetErrorKind::Io(err) => write!(f, "{}", err),
+            AssetErrorKind::PermissionDenied => {
+                write!(f, "Permission denied, check the access rights of the file")
+            }
+            AssetErrorKind::Locked => write!(
+                f,
+                "The file is locked by another process, close the editor or retry the build later"
+            ),
+            AssetErrorKind::ParseFailed(err) => write!(f, "Failed to read asset: {}", err),
+            AssetErrorKind::RedirectChainTooLong => write!(f, "The redirector chain is too long"),
+            AssetErrorKind::UnresolvedRedirector => {
+                write!(f, "Couldn't resolve the target of the redirector")
+            }
+            AssetErrorKind::MalformedObjectPath => {
+                write!(f, "The object path doesn't start with a \"/\"")
+            }
+            AssetErrorKind::NoRootFolder => write!(f, "Couldn't get the root folder of the path"),
+            AssetErrorKind::MissingAssetFile => write!(f, "Asset file is not set!"),
+            AssetErrorKind::MissingContentDir => write!(f, "Content directory is not set!"),
+            AssetErrorKind::MissingEngineDir => write!(f, "Engine content directory is not set!"),
+            AssetErrorKind::MissingInContent => {
+                write!(f, "The asset doesn't exist in the game content directory")
+            }
+            AssetErrorKind::MissingInEngineContent => {
+                write!(f, "The asset doesn't exist in the engine content directory")
+            }
+            AssetErrorKind::NotInPlugins => write!(
+                f,
+                "Couldn't find the asset in any of the plugins directories"
+            ),
+            AssetErrorKind::ScriptReference => write!(f, "Need to figure out what this folder is for yet, cuz I can't seem to find much info about it online and can't find files on my drive"),
+            AssetErrorKind::Ambiguous(_) => {
+                write!(f, "The asset was found in several plugins directories")
+            }
+            AssetErrorKind::EngineSkipped => write!(f, "Engine content"),
+            AssetErrorKind::Ignored => write!(f, "Matches an ignore pattern"),
+            AssetErrorKind::OriginExcluded => write!(f, "Origin excluded from the build"),
+            AssetErrorKind::NotInTree => write!(f, "The asset isn't a part of the dependency tree"),
+            AssetErrorKind::NodeNotInTree => {
+                write!(f, "The node isn't a part of the dependency tree")
+            }
+            AssetErrorKind::NoRoots => write!(f, "No root assets to build the tree from"),
+            AssetErrorKind::Cancelled => {
+                write!(f, "The build of the dependency tree was cancelled")
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Eq, Hash)]
 pub struct AssetError {
     pub path: PathBuf,
-    pub reason: String,
+    pub kind: AssetErrorKind,
 }
 
 impl Display for AssetError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_skipped() {
+            return write!(f, "Skipped asset ({:?}). Reason: {}", self.path, self.kind);
+        }
+
         write!(
             f,
             "Failed to read asset ({:?}). Reason: {}",
-            self.path, self.reason
-        )
+            self.path, self.kind
+        )?;
+
+        if let AssetErrorKind::Ambiguous(candidates) = &self.kind {
+            write!(f, " Candidates: {:?}", candidates)?;
+        }
+
+        Ok(())
     }
 }
 
 impl AssetError {
-    pub fn new(path: impl AsRef<Path>, reason: impl Into<String>) -> Self {
+    pub fn new(path: impl AsRef<Path>, kind: AssetErrorKind) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
-            reason: reason.into(),
+            kind,
         }
     }
+
+    pub fn ambiguous(path: impl AsRef<Path>, candidates: Vec<PathBuf>) -> Self {
+        Self::new(path, AssetErrorKind::Ambiguous(candidates))
+    }
+
+    pub fn skipped(path: impl AsRef<Path>) -> Self {
+        Self::new(path, AssetErrorKind::EngineSkipped)
+    }
+
+    #[inline]
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(self.kind, AssetErrorKind::Ambiguous(_))
+    }
+
+    /// If the dependency wasn't resolved on purpose
+    #[inline]
+    pub fn is_skipped(&self) -> bool {
+        matches!(
+            self.kind,
+            AssetErrorKind::EngineSkipped
+                | AssetErrorKind::Ignored
+                | AssetErrorKind::OriginExcluded
+        )
+    }
 }
 
 impl PartialEq for AssetError {
@@ -44,7 +198,7 @@ impl PartialEq for AssetError {
 
 impl std::error::Error for AssetError {}
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
 pub enum AssetOrigin {
     Project,
     Engine,
@@ -64,26 +218,95 @@ impl ToString for AssetOrigin {
     }
 }
 
+/// How a package refers to its dependency
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Imported, loaded along with the package
+    Hard,
+    /// Soft package reference, only loaded on demand
+    Soft,
+}
+
+/// Maximum length of a redirector chain we follow before giving up
+const MAX_REDIRECT_HOPS: usize = 8;
+
+/// Default depth the plugins directories are searched to when looking for a plugin
+pub const DEFAULT_PLUGIN_SCAN_DEPTH: usize = 10;
+
 #[derive(Debug)]
 pub struct Asset {
-    pub package: AssetHeader<File>,
+    /// Parsed header, without the file it was read from, so the nodes of large trees don't hold their files open
+    pub package: AssetHeader<()>,
     pub path: PathBuf,
     pub origin: AssetOrigin,
+    /// Class of the primary export (e.g. `Texture2D`), read once as it's not a part of the header
+    pub primary_class: Option<String>,
+    /// Why the origin guessed from the path might be wrong, e.g. engine content copied into the project
+    pub origin_ambiguity: Option<String>,
+    /// Size of the asset file in bytes
+    pub file_size: u64,
+    /// Packages referenced softly, read once as the header only has the offset of their table
+    pub soft_dependency_names: Vec<String>,
 }
 
 impl Asset {
+    /// Reads the asset like [`Asset::new`], trying once more after the delay if the file is locked
+    pub fn new_retrying_locked(
+        asset_path: impl AsRef<Path>,
+        retry_delay: Option<Duration>,
+    ) -> Result<Self, AssetError> {
+        match (Self::new(&asset_path), retry_delay) {
+            (Err(err), Some(retry_delay)) if err.kind == AssetErrorKind::Locked => {
+                log::debug!(
+                    "{:?} is locked, retrying in {:?}",
+                    asset_path.as_ref(),
+                    retry_delay
+                );
+
+                std::thread::sleep(retry_delay);
+
+                Self::new(asset_path)
+            }
+            (res, _) => res,
+        }
+    }
+
     pub fn new(asset_path: impl AsRef<Path>) -> Result<Self, AssetError> {
-        if !asset_path.as_ref().is_file()
-            || asset_path.as_ref().extension() != Some(OsStr::new("uasset"))
-        {
+        if !asset_path.as_ref().is_file() || !is_package_path(&asset_path) {
             return Err(AssetError::new(
                 asset_path.as_ref(),
-                "File does not exist or is not a .uasset file",
+                AssetErrorKind::WrongExtension,
             ));
         }
 
         let package = Self::read_asset(&asset_path)?;
 
+        let origin = Self::origin_from_path(&asset_path);
+        let origin_ambiguity = Self::check_origin(&asset_path, origin);
+
+        if let Some(origin_ambiguity) = &origin_ambiguity {
+            log::debug!("{:?}: {}", asset_path.as_ref(), origin_ambiguity);
+        }
+
+        let primary_class = Self::read_primary_class(&package, &asset_path);
+        let file_size = std::fs::metadata(&asset_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or_default();
+        let soft_dependency_names = Self::read_soft_dependency_names(&package, &asset_path);
+
+        Ok(Self {
+            package,
+            path: asset_path.as_ref().to_path_buf(),
+            origin,
+            primary_class,
+            origin_ambiguity,
+            file_size,
+            soft_dependency_names,
+        })
+    }
+
+    /// Guesses the origin from the first `Engine` and `Plugins` segments of the path
+    pub fn origin_from_path(asset_path: impl AsRef<Path>) -> AssetOrigin {
         let plugin_path = asset_path
             .as_ref()
             .iter()
@@ -94,17 +317,44 @@ impl Asset {
 
         let (is_engine, _) = AssetDirs::is_engine_path(&asset_path);
 
-        let origin = match (is_engine, is_plugin) {
+        match (is_engine, is_plugin) {
             (true, true) => AssetOrigin::EnginePlugin,
             (true, false) => AssetOrigin::Engine,
             (false, true) => AssetOrigin::ProjectPlugin,
             (false, false) => AssetOrigin::Project,
-        };
+        }
+    }
 
-        Ok(Self {
-            package,
-            path: asset_path.as_ref().to_path_buf(),
-            origin,
+    /// Looks for the signs of the origin guessed from the path being wrong
+    fn check_origin(asset_path: impl AsRef<Path>, origin: AssetOrigin) -> Option<String> {
+        let segments = asset_path.as_ref().iter().collect_vec();
+        let position = |name: &str| segments.iter().position(|seg| *seg == name);
+
+        // The origin segments are expected before the content folder they belong to
+        if let Some(content_pos) = position("Content") {
+            for name in ["Engine", "Plugins"] {
+                if position(name)
+                    .map(|pos| pos > content_pos)
+                    .unwrap_or_default()
+                {
+                    return Some(format!(
+                        "The path has an \"{}\" folder inside the \"Content\" folder",
+                        name
+                    ));
+                }
+            }
+        }
+
+        // Symlinked content is classified by where it's linked from, not where it's stored
+        let canonical_path = asset_path.as_ref().canonicalize().ok()?;
+        let canonical_origin = Self::origin_from_path(&canonical_path);
+
+        (canonical_origin != origin).then(|| {
+            format!(
+                "The asset is stored in {:?}, which is {} content",
+                canonical_path,
+                canonical_origin.to_string()
+            )
         })
     }
 
@@ -124,118 +374,207 @@ impl Asset {
         self.file_name().map(|s| s.to_str().unwrap().to_string())
     }
 
+    /// Name of the plugin the asset belongs to, the directory containing the `Content` directory of the plugin
+    pub fn plugin_name(&self) -> Option<String> {
+        let segments = self.path.iter().collect_vec();
+        let plugins_pos = segments.iter().position(|seg| *seg == "Plugins")?;
+        let content_pos = plugins_pos
+            + segments[plugins_pos..]
+                .iter()
+                .position(|seg| *seg == "Content")?;
+
+        segments
+            .get(content_pos.checked_sub(1)?)
+            .filter(|_| content_pos > plugins_pos + 1)
+            .map(|seg| seg.to_string_lossy().to_string())
+    }
+
+    /// Path the engine refers to the asset with, e.g. `/Game/Characters/Hero`
+    pub fn object_path(&self, asset_dirs: &AssetDirs) -> Option<String> {
+        disk_path_to_object_path(
+            &self.path,
+            &asset_dirs.content_dir,
+            &asset_dirs.engine_content_dir,
+            &asset_dirs.plugins_dirs,
+        )
+    }
+
+    /// Reference in the format of the editor's "Copy Reference", e.g. `Texture2D'/Game/Textures/Hero.Hero'`
+    pub fn reference(&self, asset_dirs: &AssetDirs) -> Option<String> {
+        let object_path = self.object_path(asset_dirs)?;
+        let name = self.path.file_stem()?.to_string_lossy();
+
+        Some(format!(
+            "{}'{}.{}'",
+            self.primary_class.as_deref().unwrap_or("Object"),
+            object_path,
+            name
+        ))
+    }
+
     #[inline]
-    pub fn get_dependency_names(&self) -> ImportIterator<File> {
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Version of the engine the asset was saved with, e.g. `5.1.0-23901901`
+    pub fn engine_version(&self) -> String {
+        let version = &self.package.engine_version;
+
+        format!(
+            "{}.{}.{}-{}",
+            version.major, version.minor, version.patch, version.changelist
+        )
+    }
+
+    /// Major and minor version of the engine the asset was saved with,
+    /// `None` for the assets saved by unversioned (e.g. source) builds of the engine
+    pub fn engine_major_minor_version(&self) -> Option<(u16, u16)> {
+        let version = &self.package.engine_version;
+
+        match version.major {
+            0 => None,
+            _ => Some((version.major, version.minor)),
+        }
+    }
+
+    /// If the asset was saved with an engine older than the major and minor version,
+    /// the ones saved by unversioned builds never are
+    pub fn is_older_than(&self, version: (u16, u16)) -> bool {
+        self.engine_major_minor_version()
+            .map(|saved_version| saved_version < version)
+            .unwrap_or_default()
+    }
+
+    /// Version of the package file format the asset was saved with
+    pub fn file_version(&self) -> i32 {
+        self.package.archive.file_version as i32
+    }
+
+    #[inline]
+    pub fn get_dependency_names(&self) -> ImportIterator<'_, ()> {
         self.package.package_import_iter()
     }
 
-    pub fn get_dependency_asset_paths(
-        &self,
+    /// If the asset imports any packages besides the engine's native ones (`/Script/...`)
+    pub fn has_package_dependencies(&self) -> bool {
+        self.get_dependency_names()
+            .any(|dependency_name| !dependency_name.starts_with("/Script/"))
+    }
+
+    /// Names of the packages referenced softly, from the soft package references table
+    #[inline]
+    pub fn get_soft_dependency_names(&self) -> &[String] {
+        &self.soft_dependency_names
+    }
+
+    /// Names of the imported packages, sorted, followed by the packages referenced only softly if they're included
+    fn dependency_names_with_kinds(&self, include_soft: bool) -> Vec<(String, EdgeKind)> {
+        let hard_dependency_names = self.get_dependency_names().collect::<HashSet<_>>();
+        // The packages that are also imported are hard dependencies
+        let soft_dependency_names = self
+            .get_soft_dependency_names()
+            .iter()
+            .filter(|name| include_soft && !hard_dependency_names.contains(*name))
+            .cloned()
+            .collect_vec();
+
+        hard_dependency_names
+            .into_iter()
+            .sorted()
+            .map(|name| (name, EdgeKind::Hard))
+            .chain(
+                soft_dependency_names
+                    .into_iter()
+                    .map(|name| (name, EdgeKind::Soft)),
+            )
+            .collect()
+    }
+
+    /// Finds the files the dependency name can point to, or the reason it isn't resolved
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_dependency_name(
+        dependency_name: &str,
         content_dir: impl AsRef<Path>,
         engine_content_dir: &Option<impl AsRef<Path>>,
         plugins_dirs: &[impl AsRef<Path>],
-    ) -> (Vec<PathBuf>, Vec<AssetError>) {
-        let result: SplitVecContainer<PathBuf, AssetError> = self.get_dependency_names().fold(
-            SplitVecContainer::default(),
-            |mut result_container, dependency_name| {
-                let dep = if !dependency_name.ends_with(".uasset") {
-                    dependency_name + ".uasset"
-                } else {
-                    dependency_name
-                };
-                let segments: Vec<_> = dep[1..].split('/').collect();
+        extra_content_roots: &[(String, PathBuf)],
+        plugin_scan_depth: usize,
+        skip_engine: bool,
+        ignore_patterns: &[glob::Pattern],
+    ) -> Result<Vec<PathBuf>, AssetError> {
+        // Not even looking for the engine assets, reading them is the expensive part
+        if skip_engine && dependency_name.starts_with("/Engine/") {
+            return Err(AssetError::skipped(dependency_name));
+        }
 
-                let Some(&root_folder) = segments.first() else {
-                    log::debug!("Error: Couldn't get the root folder of the path");
+        if ignore_patterns
+            .iter()
+            .any(|pattern| pattern.matches(dependency_name))
+        {
+            return Err(AssetError::new(dependency_name, AssetErrorKind::Ignored));
+        }
 
-                    return result_container;
-                };
+        object_path_candidates(
+            dependency_name,
+            content_dir.as_ref(),
+            engine_content_dir,
+            plugins_dirs,
+            extra_content_roots,
+            plugin_scan_depth,
+        )
+    }
 
-                let asset_path = match root_folder {
-                    "Game" => {
-                        let path = content_dir.as_ref().join(segments[1..].join("/"));
-
-                        match path.exists() {
-                            true => Ok(path),
-                            false => {
-                                Err(AssetError::new(
-                                    path,
-                                    "The asset doesn't exist in the game content directory",
-                                ))
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_dependency_asset_paths(
+        &self,
+        content_dir: impl AsRef<Path>,
+        engine_content_dir: &Option<impl AsRef<Path>>,
+        plugins_dirs: &[impl AsRef<Path>],
+        extra_content_roots: &[(String, PathBuf)],
+        plugin_scan_depth: usize,
+        skip_engine: bool,
+        ignore_patterns: &[glob::Pattern],
+        include_soft: bool,
+    ) -> (Vec<(PathBuf, EdgeKind)>, Vec<AssetError>) {
+        let result: SplitVecContainer<(PathBuf, EdgeKind), AssetError> = self
+            .dependency_names_with_kinds(include_soft)
+            .into_iter()
+            .fold(
+                SplitVecContainer::default(),
+                |mut result_container, (dependency_name, edge_kind)| {
+                    match Self::resolve_dependency_name(
+                        &dependency_name,
+                        content_dir.as_ref(),
+                        engine_content_dir,
+                        plugins_dirs,
+                        extra_content_roots,
+                        plugin_scan_depth,
+                        skip_engine,
+                        ignore_patterns,
+                    ) {
+                        Ok(candidates) => {
+                            // Unreal doesn't allow several assets with the same object path, so we report the collision,
+                            // but still resolve the first candidate to keep the tree connected
+                            if candidates.len() > 1 {
+                                result_container.push_right(AssetError::ambiguous(
+                                    &dependency_name,
+                                    candidates.clone(),
+                                ));
                             }
-                        }
-                    },
-                    "Engine" => {
-                        match engine_content_dir {
-                            Some(engine_content_dir) => {
-                                let path= engine_content_dir.as_ref().join(segments[1..].join("/"));
-
-                                match path.exists() {
-                                    true => Ok(path),
-                                    false => Err(AssetError::new(path, "The asset doesn't exist in the engine content directory")),
-                                }
-                            },
-                            None => {
-                                Err(AssetError::new(&dep, "Engine content directory is not set!"))
+
+                            if let Some(asset_path) = candidates.into_iter().next() {
+                                result_container.push_left((asset_path, edge_kind));
                             }
                         }
-                    },
-                    "Script" => {
-                        Err(AssetError::new(&dep, "Need to figure out what this folder is for yet, cuz I can't seem to find much info about it online and can't find files on my drive"))
-                    },
-                    root_dir => {
-                        let candidate_dirs = plugins_dirs.iter().map(|plugins_dir| {
-                            walkdir::WalkDir::new(plugins_dir.as_ref()).max_depth(10).into_iter().flat_map(|entry| {
-                                entry.ok().and_then(|entry| {
-                                    if entry.file_name() == root_dir {
-                                        let content_dir = entry.path().join("Content");
-
-                                        if content_dir.exists() {
-                                            Some(content_dir)
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        None
-                                    }
-                                })
-                            })
-                        }).fold(HashSet::new(), |mut acc, candidate_dirs| {
-                            acc.extend(candidate_dirs);
-                            acc
-                        });
-
-                        match candidate_dirs.iter().find_map(|candidate_dir| {
-                            let path = candidate_dir.join(segments[1..].join("/"));
-
-                            if path.exists() {
-                                Some(path)
-                            } else {
-                                None
-                            }
-                        }) {
-                            Some(file_path) => {
-                                Ok(file_path)
-                            },
-                            None => {
-                                Err(AssetError::new(&dep, "Couldn't find the asset in any of the plugins directories".to_string()))
-                            }
+                        Err(err) => {
+                            result_container.push_right(err);
                         }
-                    }
-                };
+                    };
 
-                match asset_path {
-                    Ok(asset_path) => {
-                        result_container.push_left(asset_path);
-                    }
-                    Err(err) => {
-                        result_container.push_right(err);
-                    }
-                };
-
-                result_container
-            },
-        );
+                    result_container
+                },
+            );
 
         log::debug!(
             "Successfully got asset paths: {}, Failed: {}",
@@ -246,24 +585,373 @@ impl Asset {
         result.into()
     }
 
-    pub fn read_asset(asset_path: impl AsRef<Path>) -> Result<AssetHeader<File>, AssetError> {
+    /// Resolves only the direct dependencies, keeping the names alongside the paths or the reasons they failed.
+    /// The ambiguous ones resolve to the first candidate, like in the tree
+    pub fn resolve_report(
+        &self,
+        asset_dirs: &AssetDirs,
+    ) -> Vec<(String, Result<PathBuf, AssetError>)> {
+        self.dependency_names_with_kinds(asset_dirs.include_soft)
+            .into_iter()
+            .map(|(dependency_name, _)| {
+                let res = match &asset_dirs.content_dir {
+                    Some(content_dir) => Self::resolve_dependency_name(
+                        &dependency_name,
+                        content_dir,
+                        &asset_dirs.engine_content_dir,
+                        &asset_dirs.plugins_dirs,
+                        &asset_dirs.extra_content_roots,
+                        asset_dirs.plugin_scan_depth,
+                        asset_dirs.skip_engine,
+                        &asset_dirs.ignore_patterns,
+                    )
+                    .and_then(|candidates| {
+                        candidates.into_iter().next().ok_or_else(|| {
+                            AssetError::new(&dependency_name, AssetErrorKind::NotFound)
+                        })
+                    }),
+                    None => Err(AssetError::new(
+                        &dependency_name,
+                        AssetErrorKind::MissingContentDir,
+                    )),
+                };
+
+                (dependency_name, res)
+            })
+            .collect()
+    }
+
+    /// Class of the primary export of the asset, e.g. `Texture2D`, `Material` or `Blueprint`
+    pub fn primary_class(&self) -> Option<String> {
+        self.primary_class.clone()
+    }
+
+    /// Reads the class name of the first export of the package from the export table, as the header doesn't expose it
+    fn read_primary_class(package: &AssetHeader<()>, path: impl AsRef<Path>) -> Option<String> {
+        if package.export_count <= 0 {
+            return None;
+        }
+
+        let read_class_index = || -> std::io::Result<i32> {
+            let mut file = File::open(path.as_ref())?;
+            file.seek(SeekFrom::Start(package.export_offset as u64))?;
+
+            // The export entry starts with the package index of its class
+            let mut class_index = [0; 4];
+            file.read_exact(&mut class_index)?;
+
+            Ok(i32::from_le_bytes(class_index))
+        };
+
+        match read_class_index() {
+            // Negative indices point to the imports, and the classes of the assets are always imported
+            Ok(class_index) if class_index < 0 => package
+                .imports
+                .get((-class_index - 1) as usize)
+                .and_then(|import| package.resolve_name(&import.object_name).ok())
+                .map(|name| name.to_string()),
+            Ok(_) => None,
+            Err(err) => {
+                log::debug!("Failed to read the exports of {:?}: {}", path.as_ref(), err);
+
+                None
+            }
+        }
+    }
+
+    /// Reads the names of the softly referenced packages, the table is a list of name table entries
+    fn read_soft_dependency_names(
+        package: &AssetHeader<()>,
+        path: impl AsRef<Path>,
+    ) -> Vec<String> {
+        if package.soft_package_references_count <= 0 || package.soft_package_references_offset <= 0
+        {
+            return vec![];
+        }
+
+        let read_name_indices = || -> std::io::Result<Vec<i32>> {
+            let mut file = File::open(path.as_ref())?;
+            file.seek(SeekFrom::Start(
+                package.soft_package_references_offset as u64,
+            ))?;
+
+            (0..package.soft_package_references_count)
+                .map(|_| {
+                    let index = read_i32(&mut file)?;
+                    // The package names never have a number suffix
+                    let _number = read_i32(&mut file)?;
+
+                    Ok(index)
+                })
+                .collect()
+        };
+
+        match read_name_indices() {
+            Ok(indices) => indices
+                .into_iter()
+                .filter_map(|index| {
+                    let index = u32::try_from(index).ok()?;
+
+                    package
+                        .resolve_name(&uasset::NameReference {
+                            index,
+                            number: None,
+                        })
+                        .ok()
+                        .map(|name| name.to_string())
+                })
+                .collect(),
+            Err(err) => {
+                log::debug!(
+                    "Failed to read the soft references of {:?}: {}",
+                    path.as_ref(),
+                    err
+                );
+
+                vec![]
+            }
+        }
+    }
+
+    /// Reads the editor thumbnail of the primary asset of the package, returns the encoded (PNG or JPEG) image.
+    /// Only the packages saved by the editor have thumbnails, cooked ones never do
+    pub fn read_thumbnail(&self) -> Option<Vec<u8>> {
+        if self.package.thumbnail_table_offset <= 0 {
+            return None;
+        }
+
+        let read_thumbnail = || -> std::io::Result<Option<Vec<u8>>> {
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(self.package.thumbnail_table_offset as u64))?;
+
+            let count = read_i32(&mut file)?;
+            if count <= 0 {
+                return Ok(None);
+            }
+
+            // The table entries are the class name, the object path and the offset of the thumbnail data,
+            // the first entry is the thumbnail of the primary asset
+            skip_fstring(&mut file)?;
+            skip_fstring(&mut file)?;
+            let data_offset = read_i32(&mut file)?;
+
+            file.seek(SeekFrom::Start(data_offset as u64))?;
+
+            let _width = read_i32(&mut file)?;
+            let _height = read_i32(&mut file)?;
+            let size = read_i32(&mut file)?;
+
+            if size <= 0 {
+                return Ok(None);
+            }
+
+            let mut data = vec![0; size as usize];
+            file.read_exact(&mut data)?;
+
+            Ok(Some(data))
+        };
+
+        match read_thumbnail() {
+            Ok(data) => data,
+            Err(err) => {
+                log::debug!("Failed to read the thumbnail of {:?}: {}", self.path, err);
+
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_redirector(&self) -> bool {
+        self.primary_class.as_deref() == Some("ObjectRedirector")
+    }
+
+    /// Follows the redirector chain starting at this asset, returning the asset it ends at and the redirectors passed.
+    /// The target of a redirector is the only package it imports, besides the engine scripts
+    pub fn follow_redirects(
+        self,
+        content_dir: impl AsRef<Path>,
+        engine_content_dir: &Option<impl AsRef<Path>>,
+        plugins_dirs: &[impl AsRef<Path>],
+        extra_content_roots: &[(String, PathBuf)],
+        plugin_scan_depth: usize,
+    ) -> Result<(Self, Vec<PathBuf>), AssetError> {
+        let mut asset = self;
+        let mut hops = vec![];
+
+        while asset.is_redirector() {
+            if hops.len() >= MAX_REDIRECT_HOPS {
+                return Err(AssetError::new(
+                    &asset.path,
+                    AssetErrorKind::RedirectChainTooLong,
+                ));
+            }
+
+            let (targets, _) = asset.get_dependency_asset_paths(
+                content_dir.as_ref(),
+                engine_content_dir,
+                plugins_dirs,
+                extra_content_roots,
+                plugin_scan_depth,
+                false,
+                &[],
+                false,
+            );
+
+            let Some((target, _)) = targets.into_iter().next() else {
+                return Err(AssetError::new(
+                    &asset.path,
+                    AssetErrorKind::UnresolvedRedirector,
+                ));
+            };
+
+            log::debug!("Following the redirector {:?} to {:?}", asset.path, target);
+
+            hops.push(asset.path.clone());
+            asset = Asset::new(target)?;
+        }
+
+        Ok((asset, hops))
+    }
+
+    /// Parses the header of the package and closes the file, the parts read later open it again
+    pub fn read_asset(asset_path: impl AsRef<Path>) -> Result<AssetHeader<()>, AssetError> {
         if asset_path.as_ref().exists() {
-            let file = File::open(asset_path.as_ref())
-                .map_err(|err| AssetError::new(asset_path.as_ref(), err.to_string()))?;
+            let file = File::open(asset_path.as_ref()).map_err(|err| {
+                AssetError::new(asset_path.as_ref(), AssetErrorKind::from_io_error(&err))
+            })?;
             let asset_header = AssetHeader::new(file).map_err(|err| {
                 AssetError::new(
                     asset_path.as_ref(),
-                    format!("Failed to read asset: {}", err),
+                    AssetErrorKind::ParseFailed(err.to_string()),
                 )
             })?;
 
-            Ok(asset_header)
+            Ok(Self::without_reader(asset_header))
         } else {
-            Err(AssetError::new(asset_path.as_ref(), "Could not find asset"))
+            Err(AssetError::new(
+                asset_path.as_ref(),
+                AssetErrorKind::NotFound,
+            ))
+        }
+    }
+
+    /// Moves the parsed header off its reader, dropping the reader
+    fn without_reader<R>(package: AssetHeader<R>) -> AssetHeader<()> {
+        let AssetHeader {
+            archive,
+            total_header_size,
+            folder_name,
+            package_flags,
+            names,
+            localization_id,
+            gatherable_text_data_count,
+            gatherable_text_data_offset,
+            export_count,
+            export_offset,
+            imports,
+            depends_offset,
+            soft_package_references_count,
+            soft_package_references_offset,
+            searchable_names_offset,
+            thumbnail_table_offset,
+            engine_version,
+            compatible_with_engine_version,
+            compression_flags,
+            package_source,
+            additional_packages_to_cook,
+            texture_allocations,
+            asset_registry_data_offset,
+        } = package;
+        let Archive {
+            reader: _,
+            file_version,
+            file_version_ue5,
+            file_licensee_version,
+            legacy_version,
+            with_editoronly_data,
+        } = archive;
+
+        AssetHeader {
+            archive: Archive {
+                reader: (),
+                file_version,
+                file_version_ue5,
+                file_licensee_version,
+                legacy_version,
+                with_editoronly_data,
+            },
+            total_header_size,
+            folder_name,
+            package_flags,
+            names,
+            localization_id,
+            gatherable_text_data_count,
+            gatherable_text_data_offset,
+            export_count,
+            export_offset,
+            imports,
+            depends_offset,
+            soft_package_references_count,
+            soft_package_references_offset,
+            searchable_names_offset,
+            thumbnail_table_offset,
+            engine_version,
+            compatible_with_engine_version,
+            compression_flags,
+            package_source,
+            additional_packages_to_cook,
+            texture_allocations,
+            asset_registry_data_offset,
         }
     }
 }
 
+/// If the path is of a package file, an asset (.uasset) or a level (.umap)
+pub fn is_package_path(path: impl AsRef<Path>) -> bool {
+    matches!(
+        path.as_ref().extension().and_then(OsStr::to_str),
+        Some("uasset" | "umap")
+    )
+}
+
+/// Paths of all the package files in the directory and its subdirectories, sorted
+pub fn find_package_paths(dir: impl AsRef<Path>) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.is_file() && is_package_path(path))
+        .sorted()
+        .collect()
+}
+
+fn read_i32(reader: &mut impl Read) -> std::io::Result<i32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(i32::from_le_bytes(bytes))
+}
+
+/// Skips an Unreal string, its length is negative for UTF-16 strings
+fn skip_fstring(reader: &mut (impl Read + Seek)) -> std::io::Result<()> {
+    let len = read_i32(reader)? as i64;
+    let size = match len < 0 {
+        true => -len * 2,
+        false => len,
+    };
+
+    reader.seek(SeekFrom::Current(size))?;
+
+    Ok(())
+}
+
+/// Git repositories are shared between the threads the dependency trees are built on
+pub type GitRepo = Arc<Mutex<git2::Repository>>;
+
+/// Own git repositories of the plugins by the plugin directories, `None` for the plugins without one
+pub type PluginGitRepos = Arc<Mutex<HashMap<PathBuf, Option<GitRepo>>>>;
+
 #[derive(Clone)]
 pub struct AssetDirs {
     pub asset_file_path: Option<PathBuf>,
@@ -273,8 +961,33 @@ pub struct AssetDirs {
     pub engine_content_dir: Option<PathBuf>,
     pub plugins_dirs: Vec<PathBuf>,
 
-    pub project_git_repo: Option<Rc<git2::Repository>>,
-    pub engine_git_repo: Option<Rc<git2::Repository>>,
+    pub project_git_repo: Option<GitRepo>,
+    pub engine_git_repo: Option<GitRepo>,
+    /// Filled as the plugins are looked up, as they are often submodules rather than a part of the project repo
+    pub plugin_git_repos: PluginGitRepos,
+
+    /// Don't resolve the engine dependencies, only record them as skipped
+    pub skip_engine: bool,
+    /// Dependencies with the object paths matching any of these are recorded as ignored instead of resolved
+    pub ignore_patterns: Vec<glob::Pattern>,
+    /// How deep the plugins directories are searched for the plugin of a mount point
+    pub plugin_scan_depth: usize,
+    /// Content directories of the custom mount points (e.g. `/SharedContent`) by the mount names
+    pub extra_content_roots: Vec<(String, PathBuf)>,
+    /// Only the dependencies of these origins are resolved and recursed into, all of them if empty
+    pub origins: Vec<AssetOrigin>,
+    /// Only the first this many dependencies of each asset are resolved and recursed into, all of them if not set
+    pub max_children: Option<usize>,
+    /// The locked assets are read once more after this delay, instead of failing right away
+    pub locked_retry_delay: Option<Duration>,
+    /// Follow the soft package references as soft edges, besides the imports
+    pub include_soft: bool,
+    /// Most assets read at once while building the tree, bounding the threads and the open files
+    pub jobs: usize,
+
+    /// Content directories set by the user, used instead of the ones guessed from the paths
+    pub content_dir_override: Option<PathBuf>,
+    pub engine_content_dir_override: Option<PathBuf>,
 }
 
 impl Debug for AssetDirs {
@@ -298,6 +1011,22 @@ impl Debug for AssetDirs {
                         Some(_) => "Exists",
                         None => "Doesn't Exist",
                     }))?;
+                    f.write_str(&format!("\tplugin_git_repos: {:?},\n", self.plugin_git_repos
+                        .lock()
+                        .map(|plugin_git_repos| plugin_git_repos.values().flatten().count())
+                        .unwrap_or_default()))?;
+
+                    f.write_str(&format!("\tskip_engine: {},\n", self.skip_engine))?;
+                    f.write_str(&format!("\tignore_patterns: {:?},\n", self.ignore_patterns))?;
+                    f.write_str(&format!("\tplugin_scan_depth: {},\n", self.plugin_scan_depth))?;
+                    f.write_str(&format!("\textra_content_roots: {:?},\n", self.extra_content_roots))?;
+                    f.write_str(&format!("\torigins: {:?},\n", self.origins))?;
+                    f.write_str(&format!("\tmax_children: {:?},\n", self.max_children))?;
+                    f.write_str(&format!("\tlocked_retry_delay: {:?},\n", self.locked_retry_delay))?;
+                    f.write_str(&format!("\tinclude_soft: {},\n", self.include_soft))?;
+                    f.write_str(&format!("\tjobs: {},\n", self.jobs))?;
+                    f.write_str(&format!("\tcontent_dir_override: {:?},\n", self.content_dir_override))?;
+                    f.write_str(&format!("\tengine_content_dir_override: {:?},\n", self.engine_content_dir_override))?;
                 }
             "}}"
         )
@@ -323,7 +1052,56 @@ impl AssetDirs {
 
             project_git_repo,
             engine_git_repo,
+            plugin_git_repos: Default::default(),
+
+            skip_engine: false,
+            ignore_patterns: vec![],
+            plugin_scan_depth: DEFAULT_PLUGIN_SCAN_DEPTH,
+            extra_content_roots: vec![],
+            origins: vec![],
+            max_children: None,
+            locked_retry_delay: None,
+            include_soft: false,
+            jobs: default_jobs(),
+
+            content_dir_override: None,
+            engine_content_dir_override: None,
+        }
+    }
+
+    /// Sets the content directories explicitly, for the layouts the paths can't be guessed from
+    pub fn with_dir_overrides(
+        mut self,
+        content_dir: Option<PathBuf>,
+        engine_content_dir: Option<PathBuf>,
+    ) -> Self {
+        self.content_dir_override = content_dir;
+        self.engine_content_dir_override = engine_content_dir;
+
+        self.apply_dir_overrides();
+
+        self
+    }
+
+    /// Replaces the guessed directories with the overridden ones, the project and engine dirs are their parents
+    fn apply_dir_overrides(&mut self) {
+        if self.content_dir_override.is_none() && self.engine_content_dir_override.is_none() {
+            return;
+        }
+
+        if let Some(content_dir) = &self.content_dir_override {
+            self.project_dir = content_dir.parent().map(Path::to_path_buf);
+            self.content_dir = Some(content_dir.clone());
+        }
+
+        if let Some(engine_content_dir) = &self.engine_content_dir_override {
+            self.engine_dir = engine_content_dir.parent().map(Path::to_path_buf);
+            self.engine_content_dir = Some(engine_content_dir.clone());
         }
+
+        self.update_plugin_dirs();
+        self.update_project_git_repo();
+        self.update_engine_git_repo();
     }
 
     pub fn asset_file_name(&self) -> Option<OsString> {
@@ -484,25 +1262,25 @@ impl AssetDirs {
     pub fn get_git_repos(
         project_dir: &Option<PathBuf>,
         engine_dir: &Option<PathBuf>,
-    ) -> (Option<Rc<git2::Repository>>, Option<Rc<git2::Repository>>) {
+    ) -> (Option<GitRepo>, Option<GitRepo>) {
         let project_git_repo = Self::get_project_git_repo(project_dir);
         let engine_git_repo = Self::get_engine_git_repo(engine_dir);
 
         (project_git_repo, engine_git_repo)
     }
 
-    pub fn get_project_git_repo(project_dir: &Option<PathBuf>) -> Option<Rc<git2::Repository>> {
+    pub fn get_project_git_repo(project_dir: &Option<PathBuf>) -> Option<GitRepo> {
         project_dir
             .as_ref()
             .and_then(|project_dir| git2::Repository::open(project_dir).ok())
-            .map(Rc::new)
+            .map(|repo| Arc::new(Mutex::new(repo)))
     }
 
-    pub fn get_engine_git_repo(engine_dir: &Option<PathBuf>) -> Option<Rc<git2::Repository>> {
+    pub fn get_engine_git_repo(engine_dir: &Option<PathBuf>) -> Option<GitRepo> {
         engine_dir
             .as_ref()
             .and_then(|engine_dir| git2::Repository::open(engine_dir).ok())
-            .map(Rc::new)
+            .map(|repo| Arc::new(Mutex::new(repo)))
     }
 
     pub fn update_asset_file(&mut self, asset_file_path: Option<PathBuf>) {
@@ -518,9 +1296,11 @@ impl AssetDirs {
 
         self.update_plugin_dirs();
         self.update_project_git_repo();
+        self.apply_dir_overrides();
     }
 
-    pub fn update_engine_dir(&mut self, engine_dir: Option<PathBuf>) {
+    /// Updates the engine directories, returns a warning if the engine directory doesn't look like an engine install
+    pub fn update_engine_dir(&mut self, engine_dir: Option<PathBuf>) -> Result<(), String> {
         let (engine_dir, engine_content_dir) = Self::get_engine_dirs(&engine_dir);
 
         self.engine_dir = engine_dir;
@@ -528,6 +1308,56 @@ impl AssetDirs {
 
         self.update_plugin_dirs();
         self.update_engine_git_repo();
+        self.apply_dir_overrides();
+
+        self.validate_engine_dir()
+    }
+
+    /// Checks that the engine content directory exists, as otherwise none of the engine assets can be resolved
+    pub fn validate_engine_dir(&self) -> Result<(), String> {
+        match &self.engine_content_dir {
+            Some(engine_content_dir) if !engine_content_dir.is_dir() => Err(format!(
+                "Engine content directory {:?} doesn't exist, engine dependencies won't be resolved",
+                engine_content_dir
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the asset file is inside the detected content directory, as otherwise its dependencies can't be resolved
+    pub fn validate_asset_file(&self) -> Result<(), String> {
+        let asset_file_path = match &self.asset_file_path {
+            Some(asset_file_path) => asset_file_path,
+            None => return Ok(()),
+        };
+
+        let content_dir = match (&self.content_dir, &self.engine_content_dir) {
+            (Some(content_dir), _) => content_dir,
+            (None, Some(engine_content_dir)) if asset_file_path.starts_with(engine_content_dir) => {
+                engine_content_dir
+            }
+            _ => {
+                return Err(format!(
+                    "Couldn't find the content directory for {:?}, is it inside a Content folder?",
+                    asset_file_path
+                ))
+            }
+        };
+
+        if !content_dir.is_dir() {
+            return Err(format!(
+                "Content directory {:?} doesn't exist, dependencies won't be resolved",
+                content_dir
+            ));
+        }
+
+        match asset_file_path.starts_with(content_dir) {
+            true => Ok(()),
+            false => Err(format!(
+                "Asset {:?} isn't inside the content directory {:?}, dependencies won't be resolved",
+                asset_file_path, content_dir
+            )),
+        }
     }
 
     pub fn update_plugin_dirs(&mut self) {
@@ -536,20 +1366,174 @@ impl AssetDirs {
 
     pub fn update_project_git_repo(&mut self) {
         self.project_git_repo = Self::get_project_git_repo(&self.project_dir);
+        // Not shared with the clones made for the previous dirs
+        self.plugin_git_repos = Default::default();
     }
 
     pub fn update_engine_git_repo(&mut self) {
         self.engine_git_repo = Self::get_engine_git_repo(&self.engine_dir);
+        self.plugin_git_repos = Default::default();
+    }
+
+    /// Git repo the asset is tracked in, plugins fall back to the project or engine repo if they don't have their own
+    pub fn get_git_repo(&self, asset: &Asset) -> Option<GitRepo> {
+        match asset.origin {
+            AssetOrigin::Project => self.project_git_repo.clone(),
+            AssetOrigin::Engine => self.engine_git_repo.clone(),
+            AssetOrigin::ProjectPlugin => self
+                .get_plugin_git_repo(&asset.path)
+                .or_else(|| self.project_git_repo.clone()),
+            AssetOrigin::EnginePlugin => self
+                .get_plugin_git_repo(&asset.path)
+                .or_else(|| self.engine_git_repo.clone()),
+        }
+    }
+
+    /// Directories the exported paths are made relative to with `--normalize-paths`, the project one first
+    /// as it can be inside the engine directory in the source builds
+    pub fn export_roots(&self) -> Vec<PathBuf> {
+        [&self.project_dir, &self.engine_dir]
+            .into_iter()
+            .flatten()
+            .filter_map(|dir| std::fs::canonicalize(dir).ok())
+            .collect()
+    }
+
+    /// The `.uproject` file of the project, if the project directory is known
+    pub fn uproject_path(&self) -> Option<PathBuf> {
+        find_descriptor(self.project_dir.as_ref()?, "uproject")
+    }
+
+    /// Directory of the plugin the asset is in, the one containing its `Content` folder
+    pub fn get_plugin_dir(asset_path: impl AsRef<Path>) -> Option<PathBuf> {
+        let segments = asset_path.as_ref().iter().collect_vec();
+        let plugins_pos = segments.iter().rposition(|seg| *seg == "Plugins")?;
+        let content_pos = plugins_pos
+            + segments[plugins_pos..]
+                .iter()
+                .position(|seg| *seg == "Content")?;
+
+        match content_pos > plugins_pos + 1 {
+            true => Some(segments[..content_pos].iter().collect()),
+            false => None,
+        }
     }
 
-    pub fn get_git_repo(&self, asset_origin: AssetOrigin) -> Option<Rc<git2::Repository>> {
-        match asset_origin {
-            AssetOrigin::Project | AssetOrigin::ProjectPlugin => self.project_git_repo.clone(),
-            AssetOrigin::Engine | AssetOrigin::EnginePlugin => self.engine_git_repo.clone(),
+    /// Own git repo of the plugin the asset is in, looked up once per plugin
+    pub fn get_plugin_git_repo(&self, asset_path: impl AsRef<Path>) -> Option<GitRepo> {
+        let plugin_dir = Self::get_plugin_dir(asset_path)?;
+
+        self.plugin_git_repos
+            .lock()
+            .ok()?
+            .entry(plugin_dir)
+            .or_insert_with_key(|plugin_dir| Self::discover_plugin_git_repo(plugin_dir))
+            .clone()
+    }
+
+    /// Walks up from the plugin directory to the nearest repo, only keeping it if it's the plugin's own
+    fn discover_plugin_git_repo(plugin_dir: &Path) -> Option<GitRepo> {
+        let plugin_dir = plugin_dir.canonicalize().ok()?;
+        let repo = git2::Repository::discover(&plugin_dir).ok()?;
+
+        match repo
+            .workdir()
+            .and_then(|workdir| workdir.canonicalize().ok())
+            .map(|workdir| workdir.starts_with(&plugin_dir))
+            .unwrap_or_default()
+        {
+            true => Some(Arc::new(Mutex::new(repo))),
+            false => None,
         }
     }
 
+    /// Own git repos of the plugins directly in the plugins directories
+    fn get_plugin_git_repos(&self) -> Vec<GitRepo> {
+        self.plugins_dirs
+            .iter()
+            .filter_map(|plugins_dir| std::fs::read_dir(plugins_dir).ok())
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|plugin_dir| plugin_dir.join(".git").exists())
+            .filter_map(|plugin_dir| self.get_plugin_git_repo(plugin_dir.join("Content")))
+            .collect()
+    }
+
+    /// If the assets of the origin are resolved when building the trees
+    pub fn is_origin_included(&self, origin: AssetOrigin) -> bool {
+        self.origins.is_empty() || self.origins.contains(&origin)
+    }
+
+    /// Checks if the asset is modified in the working tree or the index of its git repo
+    pub fn is_modified(&self, asset: &Asset) -> bool {
+        self.get_git_repo(asset)
+            .as_ref()
+            .and_then(|repo| {
+                self.get_relative_path(asset)
+                    .and_then(|relative_path| repo.lock().ok()?.status_file(&relative_path).ok())
+            })
+            .map(|status| status.is_index_modified() || status.is_wt_modified())
+            .unwrap_or_default()
+    }
+
+    /// All the assets and levels under the content directories (including the plugins ones) that are modified in the git repos
+    pub fn modified_assets(&self) -> Vec<PathBuf> {
+        let content_dirs = [&self.content_dir, &self.engine_content_dir]
+            .into_iter()
+            .flatten()
+            .chain(self.plugins_dirs.iter())
+            .collect::<Vec<_>>();
+
+        [&self.project_git_repo, &self.engine_git_repo]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .chain(self.get_plugin_git_repos())
+            .flat_map(|repo| {
+                let Ok(repo) = repo.lock() else {
+                    return vec![];
+                };
+                let (Some(workdir), Ok(statuses)) = (repo.workdir(), repo.statuses(None)) else {
+                    return vec![];
+                };
+
+                statuses
+                    .iter()
+                    .filter(|entry| {
+                        entry.status().is_index_modified() || entry.status().is_wt_modified()
+                    })
+                    .filter_map(|entry| entry.path().map(|path| workdir.join(path)))
+                    .collect()
+            })
+            .filter(|path| {
+                is_package_path(path)
+                    && content_dirs
+                        .iter()
+                        .any(|content_dir| path.starts_with(content_dir))
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
     pub fn get_relative_path(&self, asset: &Asset) -> Option<PathBuf> {
+        // Plugins with their own repos are relative to the plugin rather than the project or the engine
+        if let Some(workdir) = self
+            .get_plugin_git_repo(&asset.path)
+            .and_then(|repo| repo.lock().ok()?.workdir().map(Path::to_path_buf))
+        {
+            let workdir = workdir.canonicalize().ok()?;
+
+            return asset
+                .path
+                .canonicalize()
+                .ok()?
+                .strip_prefix(workdir)
+                .ok()
+                .map(Into::into);
+        }
+
         match asset.origin {
             AssetOrigin::Project | AssetOrigin::ProjectPlugin => self
                 .project_dir
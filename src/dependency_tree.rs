@@ -1,92 +1,334 @@
+mod diff;
+mod duplicates;
+mod orphans;
+mod plugins;
+mod sqlite;
+
+pub use diff::{TreeDiff, TreeSnapshot};
+pub use duplicates::find_duplicate_names;
+pub use orphans::find_orphans;
+pub use plugins::{find_disabled_plugins, DisabledPlugin};
+pub use sqlite::export_sqlite;
+
 use std::{
-    collections::{HashMap, HashSet},
-    path::Path,
-    rc::Rc,
-    sync::atomic::AtomicU64,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
 };
 
+use clap::ValueEnum;
 use itertools::Itertools;
 
 use crate::{
-    asset::{Asset, AssetError},
-    util::SplitVecContainer,
+    asset::{Asset, AssetError, AssetErrorKind, AssetOrigin, EdgeKind},
+    util::{
+        ellipsize_middle, format_size, object_path_to_disk_path, with_workers, SplitVecContainer,
+    },
 };
 
+/// Ids are minted in the order the nodes are discovered: the root gets 0 and the new dependencies of a node
+/// get consecutive ids in the order of their paths, so the same assets always produce the same ids
 pub type NodeID = u64;
 
-static NODE_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+/// Maximum recurse depth meaning there's no limit, the tree is built until all the dependencies are resolved
+pub const UNLIMITED_RECURSE_DEPTH: u32 = u32::MAX;
+
+/// Sorting of the printed node list
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum NodeSortKey {
+    Path,
+    Id,
+    Origin,
+}
+
+/// Order the dependencies are resolved in
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum TraversalOrder {
+    /// Resolves all the dependencies of a depth before going deeper,
+    /// so the recurse depth of every node is the length of the shortest path to it
+    Bfs,
+    /// Follows each dependency chain as deep as possible first
+    #[default]
+    Dfs,
+}
 
-fn new_id() -> NodeID {
-    NODE_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Acquire)
+/// What the nodes of the graph are colored by
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Roots, dependencies and failures in their own colors
+    #[default]
+    Origin,
+    /// Only the assets modified in the git repos stand out
+    GitStatus,
+    /// Gradient by the number of dependencies, to spot the hubs
+    OutDegree,
+    /// Gradient by the recurse depth
+    Depth,
 }
 
-fn reset_id() {
-    NODE_ID_COUNTER.store(0, std::sync::atomic::Ordering::SeqCst)
+impl ColorMode {
+    pub const ALL: [ColorMode; 4] = [
+        ColorMode::Origin,
+        ColorMode::GitStatus,
+        ColorMode::OutDegree,
+        ColorMode::Depth,
+    ];
+}
+
+impl Display for ColorMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ColorMode::Origin => "Origin",
+            ColorMode::GitStatus => "Git Status",
+            ColorMode::OutDegree => "Num Dependencies",
+            ColorMode::Depth => "Depth",
+        })
+    }
+}
+
+/// Node standing for a group of nodes collapsed by [`DepTree::aggregate_by`]
+#[derive(Debug, Clone)]
+pub struct AggregatedNode {
+    /// Key the nodes were grouped by, e.g. the name of the plugin
+    pub name: String,
+    pub node_count: usize,
+    /// Edges coming into the group from the other nodes
+    pub edge_count: usize,
+}
+
+impl AggregatedNode {
+    /// Short summary shown next to the name
+    pub fn badge(&self) -> String {
+        format!("{} assets, {} refs", self.node_count, self.edge_count)
+    }
 }
 
 #[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct DepTree {
     pub root_node_id: NodeID,
-    pub nodes: HashMap<NodeID, Rc<Asset>>,
+    /// Roots of the combined trees besides the main one, e.g. when building from all the modified assets
+    pub extra_root_node_ids: Vec<NodeID>,
+    pub nodes: HashMap<NodeID, Arc<Asset>>,
     pub node_connections: HashMap<NodeID, Vec<NodeID>>,
+    /// Reverse of the connections for the quick parent lookups, the shared dependencies have several parents.
+    /// The parents are in the order they were connected, so the first one is the one the node was discovered from
+    pub parents_of: HashMap<NodeID, Vec<NodeID>>,
     pub failures: Vec<AssetError>,
+    /// Nodes that referenced each of the failures, by the paths of the failures
+    pub failure_parents: HashMap<PathBuf, Vec<NodeID>>,
+    /// Paths of the failures that still failed when retried after the build
+    pub retried_failures: HashSet<PathBuf>,
+    /// Redirectors that were followed during the build, mapped to the assets they point to
+    pub redirects: HashMap<PathBuf, PathBuf>,
+    /// Connections that are soft references, all the others are hard ones
+    pub soft_edges: HashSet<(NodeID, NodeID)>,
+    /// Nodes standing for the groups of collapsed nodes, see [`DepTree::aggregate_by`]
+    pub aggregated: HashMap<NodeID, AggregatedNode>,
+    /// Nodes with more dependencies than [`AssetDirs::max_children`], mapped to the amount of the ones left out
+    pub truncated: HashMap<NodeID, usize>,
 
     pub max_recurse_depth: u32,
     pub recurse_depths: HashMap<NodeID, u32>,
+    /// Order the tree was built in, used to resolve the dependencies when rebuilding its nodes
+    pub traversal_order: TraversalOrder,
+    /// Id of the next discovered node, every tree mints its own so the concurrent builds don't share them
+    next_id: NodeID,
 }
 
 impl DepTree {
+    /// Builds the dependency tree of the asset, `cancel` is checked between the resolved nodes to abort the build.
+    /// With the maximum recurse depth of 0 the tree only has the root node, see [`UNLIMITED_RECURSE_DEPTH`] for no limit
     pub fn build(
         asset_dirs: &AssetDirs,
         max_recurse_depth: u32,
+        traversal_order: TraversalOrder,
         pb: Option<&mut ProgressBar>,
+        cancel: Option<&AtomicBool>,
     ) -> Result<Self, AssetError> {
-        reset_id();
-
         log::debug!(
             "Building the dependency tree of \"{:?}\"...",
             asset_dirs.asset_file_path.as_ref()
         );
 
-        let mut nodes = HashMap::new();
-        let mut node_connections: HashMap<NodeID, Vec<NodeID>> = HashMap::new();
-        let mut known_paths = HashSet::new();
-        let mut failures = HashSet::new();
+        let Some(asset_file_path) = &asset_dirs.asset_file_path else {
+            return Err(AssetError::new("", AssetErrorKind::MissingAssetFile));
+        };
 
-        let root_node = Asset::new(asset_dirs.asset_file_path.as_ref().unwrap()).map(Rc::new)?;
+        if asset_dirs.content_dir.is_none() {
+            return Err(AssetError::new(
+                asset_file_path,
+                AssetErrorKind::MissingContentDir,
+            ));
+        }
+
+        let root_node = Asset::new_retrying_locked(asset_file_path, asset_dirs.locked_retry_delay)
+            .map(Arc::new)?;
 
         log::debug!("Got the root asset node!");
 
-        let root_node_id = new_id();
-        known_paths.insert(root_node.path.clone());
-        nodes.insert(root_node_id, root_node);
+        let root_node_id = 0;
+
+        let mut dep_tree = Self {
+            root_node_id,
+            extra_root_node_ids: vec![],
+            nodes: HashMap::from([(root_node_id, root_node)]),
+            node_connections: HashMap::new(),
+            parents_of: HashMap::new(),
+            failures: vec![],
+            failure_parents: HashMap::new(),
+            retried_failures: HashSet::new(),
+            redirects: HashMap::new(),
+            soft_edges: HashSet::new(),
+            aggregated: HashMap::new(),
+            truncated: HashMap::new(),
 
-        // Tracking the depth of the "recursion" of the dependecy chain
-        let mut recurse_depths = HashMap::new();
-        // We put the original (root) node into the map
-        recurse_depths.insert(root_node_id, 0);
+            max_recurse_depth,
+            // Tracking the depth of the "recursion" of the dependecy chain, starting with the original (root) node
+            recurse_depths: HashMap::from([(root_node_id, 0)]),
+            traversal_order,
+            next_id: root_node_id + 1,
+        };
 
         if max_recurse_depth > 0 {
             if let Some(pb) = &pb {
                 pb.set_message(format!("Building the network of dependencies recursively with maximum recurse depth of {max_recurse_depth} ..."));
             }
 
-            // List we use to be able to dynamically resolve incoming nodes
-            let mut unresolved_nodes_ids = vec![root_node_id];
+            dep_tree.resolve_dependencies(vec![root_node_id], asset_dirs, pb.as_deref(), cancel)?;
+        }
+
+        Ok(dep_tree)
+    }
+
+    /// Builds the combined dependency tree of several assets, the first one becomes the main root
+    pub fn build_combined(
+        asset_dirs: &AssetDirs,
+        roots: &[PathBuf],
+        max_recurse_depth: u32,
+        traversal_order: TraversalOrder,
+        mut pb: Option<&mut ProgressBar>,
+    ) -> Result<Self, AssetError> {
+        let Some((main_root, extra_roots)) = roots.split_first() else {
+            return Err(AssetError::new("", AssetErrorKind::NoRoots));
+        };
+
+        let mut dep_tree = Self::build(
+            &AssetDirs {
+                asset_file_path: Some(main_root.clone()),
+                ..asset_dirs.clone()
+            },
+            max_recurse_depth,
+            traversal_order,
+            pb.as_deref_mut(),
+            None,
+        )?;
+
+        for root in extra_roots {
+            // Already reached from one of the previous roots
+            if dep_tree.find_node_id_by_path(root).is_some() {
+                continue;
+            }
+
+            match Asset::new_retrying_locked(root, asset_dirs.locked_retry_delay) {
+                Ok(asset) => {
+                    let id = dep_tree.new_id();
+
+                    dep_tree.nodes.insert(id, Arc::new(asset));
+                    dep_tree.recurse_depths.insert(id, 0);
+                    dep_tree.extra_root_node_ids.push(id);
+
+                    dep_tree.resolve_dependencies(vec![id], asset_dirs, pb.as_deref(), None)?;
+                }
+                Err(err) => dep_tree.failures.push(err),
+            }
+        }
+
+        Ok(dep_tree)
+    }
+
+    /// Resolves the dependencies of the given nodes recursively, down to the maximum recurse depth of the tree.
+    /// Only the dependencies that aren't in the tree yet become new nodes
+    fn resolve_dependencies(
+        &mut self,
+        // List we use to be able to dynamically resolve incoming nodes
+        unresolved_nodes_ids: Vec<NodeID>,
+        asset_dirs: &AssetDirs,
+        pb: Option<&ProgressBar>,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<(), AssetError> {
+        let Some(content_dir) = &asset_dirs.content_dir else {
+            return Err(AssetError::new(
+                &self.get_root_node().path,
+                AssetErrorKind::MissingContentDir,
+            ));
+        };
+
+        let mut known_paths = self
+            .nodes
+            .iter()
+            .map(|(&node_id, node)| (node.path.clone(), node_id))
+            .collect::<HashMap<_, _>>();
+        let mut failures = self.failures.drain(..).collect::<HashSet<_>>();
+        let mut unresolved_nodes_ids = VecDeque::from(unresolved_nodes_ids);
+        let traversal_order = self.traversal_order;
+
+        // Create an Asset from the dependency path, resolving the redirectors to the assets they point to.
+        // The excluded origins are checked before reading the assets, as that's the expensive part
+        let read_dependency = |dep_path: PathBuf| {
+            match asset_dirs.is_origin_included(Asset::origin_from_path(&dep_path)) {
+                true => Asset::new_retrying_locked(dep_path, asset_dirs.locked_retry_delay),
+                false => Err(AssetError::new(dep_path, AssetErrorKind::OriginExcluded)),
+            }
+            .and_then(|asset| {
+                asset.follow_redirects(
+                    content_dir,
+                    &asset_dirs.engine_content_dir,
+                    &asset_dirs.plugins_dirs,
+                    &asset_dirs.extra_content_roots,
+                    asset_dirs.plugin_scan_depth,
+                )
+            })
+            .and_then(|(asset, hops)| {
+                // Redirectors can point to the excluded origins too
+                match asset_dirs.is_origin_included(asset.origin) {
+                    true => Ok((asset, hops)),
+                    false => Err(AssetError::new(&asset.path, AssetErrorKind::OriginExcluded)),
+                }
+            })
+        };
 
+        // The dependencies of all the nodes are read on the same `jobs` threads
+        with_workers(asset_dirs.jobs, read_dependency, |workers| {
             // We do iterations as long as there are unresolved ids
-            while let Some(cur_node_id) = unresolved_nodes_ids.pop() {
+            while let Some(cur_node_id) = match traversal_order {
+                TraversalOrder::Bfs => unresolved_nodes_ids.pop_front(),
+                TraversalOrder::Dfs => unresolved_nodes_ids.pop_back(),
+            } {
+                if cancel
+                    .map(|cancel| cancel.load(std::sync::atomic::Ordering::Acquire))
+                    .unwrap_or_default()
+                {
+                    log::info!("The build of the dependency tree was cancelled");
+
+                    return Err(AssetError::new(
+                        &self.get_root_node().path,
+                        AssetErrorKind::Cancelled,
+                    ));
+                }
+
                 if let Some(pb) = &pb {
                     pb.set_message(format!("Resolving node with ID {cur_node_id}"));
                 }
 
                 // We don't need to resolve current node's dependencies if it is at the maximum depth level
-                if *recurse_depths.get(&cur_node_id).unwrap() >= max_recurse_depth {
+                if *self.recurse_depths.get(&cur_node_id).unwrap() >= self.max_recurse_depth {
                     continue;
                 }
 
                 // Get the current node
-                let cur_node = nodes.get(&cur_node_id).cloned().unwrap();
+                let cur_node = self.nodes.get(&cur_node_id).cloned().unwrap();
                 // Get current node assets path
                 let asset_path = &cur_node.path;
 
@@ -95,17 +337,59 @@ impl DepTree {
                 }
 
                 // Get current nodes dependencies
-                let (dep_paths, fails) = cur_node.get_dependency_asset_paths(
-                    asset_dirs.content_dir.as_ref().unwrap(),
+                let (mut dep_paths, fails) = cur_node.get_dependency_asset_paths(
+                    content_dir,
                     &asset_dirs.engine_content_dir,
                     &asset_dirs.plugins_dirs,
+                    &asset_dirs.extra_content_roots,
+                    asset_dirs.plugin_scan_depth,
+                    asset_dirs.skip_engine,
+                    &asset_dirs.ignore_patterns,
+                    asset_dirs.include_soft,
                 );
 
+                // Only follow the first dependencies if there's a cap, so the huge trees can be sketched quickly
+                if let Some(max_children) = asset_dirs.max_children {
+                    if dep_paths.len() > max_children {
+                        self.truncated
+                            .insert(cur_node_id, dep_paths.len() - max_children);
+                        dep_paths.truncate(max_children);
+                    }
+                }
+
+                // A package can only be referenced one way, so the kinds can be looked up by the path
+                let edge_kinds = dep_paths.iter().cloned().collect::<HashMap<_, _>>();
+
+                // The shared dependencies (and the cycles) are connected to the nodes already in the tree
+                let known_deps = dep_paths
+                    .iter()
+                    .filter_map(|(dep_path, edge_kind)| {
+                        let dep_path = self.redirects.get(dep_path).unwrap_or(dep_path);
+
+                        known_paths
+                            .get(dep_path)
+                            .map(|&dep_node_id| (dep_node_id, *edge_kind))
+                    })
+                    .collect_vec();
+                self.connect_known(cur_node_id, known_deps);
+
+                // The dependencies that already failed for the other nodes fail for this one too
+                for (dep_path, _) in &dep_paths {
+                    if failures
+                        .iter()
+                        .any(|fail: &AssetError| &fail.path == dep_path)
+                    {
+                        self.add_failure_parent(dep_path, cur_node_id);
+                    }
+                }
+
                 // Find all the assets dependency paths that we haven't checked out yet
                 let unresolved_deps = dep_paths
                     .into_iter()
+                    .map(|(dep_path, _)| dep_path)
                     .filter(|dep_path| {
-                        !known_paths.contains(dep_path)
+                        !known_paths.contains_key(dep_path)
+                            && !self.redirects.contains_key(dep_path)
                             && !failures
                                 .iter()
                                 .any(|fail: &AssetError| &fail.path == dep_path)
@@ -113,6 +397,9 @@ impl DepTree {
                     .collect::<Vec<_>>();
 
                 // Add new fails to the final list
+                for fail in &fails {
+                    self.add_failure_parent(&fail.path, cur_node_id);
+                }
                 failures.extend(fails);
 
                 if let Some(pb) = &pb {
@@ -123,92 +410,507 @@ impl DepTree {
                 }
 
                 // Try to create nodes from the dependencies and split the list in 2, for successes and failures
-                let (unresolved_nodes, fails): (Vec<Rc<Asset>>, Vec<AssetError>) =
-                    Into::<SplitVecContainer<Rc<Asset>, AssetError>>::into(
-                        unresolved_deps
-                            .into_iter()
-                            // Create an Asset from the dependency path and wrap it in a ref-counted pointer, so we don't waste memory cloning it
-                            .map(|dep_path| Asset::new(dep_path).map(Rc::new))
-                            // Collect it back to vector
-                            .collect::<Vec<_>>(),
+                let (unresolved_nodes, fails): (Vec<(Asset, Vec<PathBuf>)>, Vec<AssetError>) =
+                    Into::<SplitVecContainer<(Asset, Vec<PathBuf>), AssetError>>::into(
+                        workers.map(unresolved_deps),
                     )
                     .into();
 
+                // Save the followed redirectors and connect the targets we already know about
+                let mut new_paths = HashSet::new();
+                let mut known_targets = vec![];
+                let unresolved_nodes = unresolved_nodes
+                    .into_iter()
+                    .filter_map(|(asset, hops)| {
+                        // The referenced path is the first redirector if there are any
+                        let edge_kind = edge_kinds
+                            .get(hops.first().unwrap_or(&asset.path))
+                            .copied()
+                            .unwrap_or(EdgeKind::Hard);
+
+                        self.redirects
+                            .extend(hops.into_iter().map(|hop| (hop, asset.path.clone())));
+
+                        if let Some(&target_node_id) = known_paths.get(&asset.path) {
+                            known_targets.push((target_node_id, edge_kind));
+
+                            return None;
+                        }
+
+                        match new_paths.insert(asset.path.clone()) {
+                            // Wrap it in a ref-counted pointer, so we don't waste memory cloning it
+                            true => Some((Arc::new(asset), edge_kind)),
+                            false => None,
+                        }
+                    })
+                    // Keep the ids stable across runs, regardless of the order of the imports
+                    .sorted_by(|(node1, _), (node2, _)| node1.path.cmp(&node2.path))
+                    .collect::<Vec<_>>();
+
+                self.connect_known(cur_node_id, known_targets);
+
                 if let Some(pb) = &pb {
                     pb.set_message("Saving all the failures...");
                 }
 
                 // Add new fails to the final list
+                for fail in &fails {
+                    self.add_failure_parent(&fail.path, cur_node_id);
+                }
                 failures.extend(fails);
 
                 if let Some(pb) = &pb {
-                    pb.set_message("Caching new known paths...");
+                    pb.set_message("Saving the unresolved nodes...");
+                }
+
+                let cur_depth = *self.recurse_depths.get(&cur_node_id).unwrap();
+
+                for (asset, edge_kind) in unresolved_nodes {
+                    let id = self.new_id();
+
+                    unresolved_nodes_ids.push_back(id);
+                    known_paths.insert(asset.path.clone(), id);
+                    self.node_connections
+                        .entry(cur_node_id)
+                        .or_default()
+                        .push(id);
+                    self.parents_of.insert(id, vec![cur_node_id]);
+                    if edge_kind == EdgeKind::Soft {
+                        self.soft_edges.insert((cur_node_id, id));
+                    }
+                    self.recurse_depths.insert(id, cur_depth + 1);
+                    self.nodes.insert(id, asset);
                 }
 
-                // Add new known paths
-                known_paths.extend(unresolved_nodes.iter().map(|node| node.path.clone()));
+                let nodes_amount = self.nodes.len();
 
                 if let Some(pb) = &pb {
-                    pb.set_message("Saving the unresolved nodes...");
+                    pb.set_length(nodes_amount as u64);
+                    pb.set_position((nodes_amount - unresolved_nodes_ids.len()) as u64)
                 }
+            }
 
-                nodes.extend(unresolved_nodes.into_iter().map(|asset| {
-                    let id = new_id();
+            Ok(())
+        })?;
 
-                    unresolved_nodes_ids.push(id);
-                    node_connections.entry(cur_node_id).or_default().push(id);
-                    recurse_depths.insert(id, recurse_depths.get(&cur_node_id).unwrap() + 1);
+        self.failures = failures
+            .into_iter()
+            .sorted_by(|fail1, fail2| fail1.path.cmp(&fail2.path))
+            .collect();
 
-                    (id, asset)
-                }));
+        // The retried failures that resolved now are nodes instead
+        let failure_paths = self
+            .failures
+            .iter()
+            .map(|fail| &fail.path)
+            .collect::<HashSet<_>>();
+        self.failure_parents
+            .retain(|path, _| failure_paths.contains(path));
 
-                let nodes_amount = nodes.len();
+        Ok(())
+    }
 
-                if let Some(pb) = &pb {
-                    pb.set_length(nodes_amount as u64);
-                    pb.set_position((nodes_amount - unresolved_nodes_ids.len()) as u64)
+    /// Records the node as one of the nodes referencing the failure
+    fn add_failure_parent(&mut self, path: &Path, parent_id: NodeID) {
+        let parents = self.failure_parents.entry(path.to_path_buf()).or_default();
+
+        if !parents.contains(&parent_id) {
+            parents.push(parent_id);
+        }
+    }
+
+    fn new_id(&mut self) -> NodeID {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        id
+    }
+
+    /// Removes the nodes from the parents of the failures, dropping the failures nothing references anymore
+    fn remove_failure_parents(&mut self, is_removed: impl Fn(NodeID) -> bool) {
+        let mut orphaned_paths = HashSet::new();
+
+        self.failure_parents.retain(|path, parents| {
+            parents.retain(|&parent_id| !is_removed(parent_id));
+
+            match parents.is_empty() {
+                true => {
+                    orphaned_paths.insert(path.clone());
+                    false
                 }
+                false => true,
+            }
+        });
+
+        self.failures
+            .retain(|failure| !orphaned_paths.contains(&failure.path));
+        self.retried_failures
+            .retain(|path| !orphaned_paths.contains(path));
+    }
+
+    /// Nodes that referenced the failed dependency, in the order they did
+    pub fn failure_parents(&self, failure: &AssetError) -> Vec<NodeID> {
+        self.failure_parents
+            .get(&failure.path)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Re-reads the asset of the node and updates its dependencies without rebuilding the whole tree.
+    /// Newly referenced assets are resolved down to the original maximum recurse depth,
+    /// the dependencies the asset doesn't reference anymore are dropped along with the nodes only they led to
+    pub fn rebuild_node(
+        &mut self,
+        id: NodeID,
+        asset_dirs: &AssetDirs,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<(), AssetError> {
+        let Some(old_node) = self.get_node(id) else {
+            return Err(AssetError::new(
+                format!("Node {}", id),
+                AssetErrorKind::NodeNotInTree,
+            ));
+        };
+
+        let Some(content_dir) = &asset_dirs.content_dir else {
+            return Err(AssetError::new(
+                &old_node.path,
+                AssetErrorKind::MissingContentDir,
+            ));
+        };
+
+        let node = Arc::new(Asset::new_retrying_locked(
+            &old_node.path,
+            asset_dirs.locked_retry_delay,
+        )?);
+
+        let (mut dep_paths, _) = node.get_dependency_asset_paths(
+            content_dir,
+            &asset_dirs.engine_content_dir,
+            &asset_dirs.plugins_dirs,
+            &asset_dirs.extra_content_roots,
+            asset_dirs.plugin_scan_depth,
+            asset_dirs.skip_engine,
+            &asset_dirs.ignore_patterns,
+            asset_dirs.include_soft,
+        );
+        // The children past the cap aren't kept either, resolving the dependencies counts them again
+        if let Some(max_children) = asset_dirs.max_children {
+            dep_paths.truncate(max_children);
+        }
+        // The nodes are stored under the paths of the assets the redirectors point to
+        let dep_paths = dep_paths
+            .into_iter()
+            .map(|(dep_path, _)| self.redirects.get(&dep_path).cloned().unwrap_or(dep_path))
+            .collect::<HashSet<_>>();
+
+        self.nodes.insert(id, node);
+        // Resolving the dependencies again truncates them anew
+        self.truncated.remove(&id);
+        // And tells the kinds of the kept connections anew
+        self.soft_edges.retain(|&(from, _)| from != id);
+
+        // Keep only the children the asset still references
+        if let Some(connections) = self.node_connections.remove(&id) {
+            let connections = connections
+                .into_iter()
+                .filter(|con_node_id| {
+                    self.nodes
+                        .get(con_node_id)
+                        .map(|con_node| dep_paths.contains(&con_node.path))
+                        .unwrap_or_default()
+                })
+                .collect_vec();
+
+            if !connections.is_empty() {
+                self.node_connections.insert(id, connections);
             }
         }
 
-        Ok(Self {
-            root_node_id,
-            nodes,
-            node_connections,
-            failures: failures.into_iter().collect(),
+        // The failures the asset still references are added back when resolving its dependencies.
+        // The ones only it referenced are dropped and so get another chance, the asset might've been fixed.
+        // The ones the other nodes reference too stay failures until they're retried, see `retry_failures`
+        self.remove_failure_parents(|parent_id| parent_id == id);
+        self.prune_unreachable_nodes();
 
-            max_recurse_depth,
-            recurse_depths,
-        })
+        self.resolve_dependencies(vec![id], asset_dirs, None, cancel)
+    }
+
+    /// Tries to resolve the failed dependencies once more, e.g. after the plugins dirs were updated,
+    /// turning the ones that resolve now into nodes. Returns the amount of the failures that were fixed
+    pub fn retry_failures(&mut self, asset_dirs: &AssetDirs) -> Result<usize, AssetError> {
+        let (retried, failures): (Vec<_>, Vec<_>) = self
+            .failures
+            .drain(..)
+            .partition(|fail| !fail.is_ambiguous() && !fail.is_skipped());
+        self.failures = failures;
+
+        let retried_paths = retried
+            .into_iter()
+            .map(|fail| fail.path)
+            .collect::<HashSet<_>>();
+
+        // Only the nodes that can still have dependencies can lead to the retried ones
+        let node_ids = self
+            .recurse_depths
+            .iter()
+            .filter(|(_, &depth)| depth < self.max_recurse_depth)
+            .map(|(&node_id, _)| node_id)
+            .sorted()
+            .collect_vec();
+
+        self.resolve_dependencies(node_ids, asset_dirs, None, None)?;
+
+        let still_failing = self
+            .failures
+            .iter()
+            .filter(|fail| retried_paths.contains(&fail.path))
+            .map(|fail| fail.path.clone())
+            .collect::<HashSet<_>>();
+        let fixed = retried_paths.len() - still_failing.len();
+
+        self.retried_failures.extend(still_failing);
+
+        Ok(fixed)
+    }
+
+    /// Classes of the assets in the tree, `None` if the class of some asset is unknown
+    pub fn classes(&self) -> BTreeSet<Option<String>> {
+        self.nodes
+            .values()
+            .map(|node| node.primary_class())
+            .collect()
+    }
+
+    /// Keeps only the assets of the given classes (case-insensitive) and the nodes on the way to them from the roots,
+    /// so the tree stays connected. Nothing is filtered out if no classes are given
+    pub fn retain_classes(mut self, classes: &[String]) -> Self {
+        if classes.is_empty() {
+            return self;
+        }
+
+        let mut keep = HashSet::from([self.root_node_id]);
+        keep.extend(&self.extra_root_node_ids);
+
+        let matching_nodes_ids = self.nodes.iter().filter_map(|(&node_id, node)| {
+            let class = node.primary_class()?;
+
+            classes
+                .iter()
+                .any(|wanted_class| wanted_class.eq_ignore_ascii_case(&class))
+                .then_some(node_id)
+        });
+
+        for node_id in matching_nodes_ids.collect_vec() {
+            let mut cur_node_id = Some(node_id);
+
+            // Stop when reaching a node that's already connected to the root
+            while let Some(node_id) = cur_node_id {
+                if !keep.insert(node_id) {
+                    break;
+                }
+
+                // The first parents always lead back to a root, unlike the ones closing the cycles
+                cur_node_id = self.get_parent_node_id(node_id);
+            }
+        }
+
+        for connections in self.node_connections.values_mut() {
+            connections.retain(|con_node_id| keep.contains(con_node_id));
+        }
+        self.node_connections
+            .retain(|_, connections| !connections.is_empty());
+
+        self.prune_unreachable_nodes();
+
+        self
+    }
+
+    /// Nodes whose origin might be misclassified, with the reasons
+    pub fn ambiguous_origins(&self) -> Vec<(NodeID, &str)> {
+        self.nodes
+            .iter()
+            .filter_map(|(&node_id, node)| {
+                node.origin_ambiguity
+                    .as_deref()
+                    .map(|reason| (node_id, reason))
+            })
+            .sorted()
+            .collect()
+    }
+
+    /// Removes the nodes that can't be reached from the root anymore
+    fn prune_unreachable_nodes(&mut self) {
+        let mut unvisited_nodes_ids = vec![self.root_node_id];
+        unvisited_nodes_ids.extend(&self.extra_root_node_ids);
+        let mut reachable = unvisited_nodes_ids.iter().copied().collect::<HashSet<_>>();
+
+        while let Some(cur_node_id) = unvisited_nodes_ids.pop() {
+            for con_node_id in self.get_node_connections(cur_node_id) {
+                if reachable.insert(con_node_id) {
+                    unvisited_nodes_ids.push(con_node_id);
+                }
+            }
+        }
+
+        self.nodes.retain(|id, _| reachable.contains(id));
+        self.node_connections.retain(|id, _| reachable.contains(id));
+        // The removed connections can leave the nodes with their first parents gone
+        self.parents_of = self.retained_parents(&self.node_connections);
+        self.recurse_depths.retain(|id, _| reachable.contains(id));
+        self.truncated.retain(|id, _| reachable.contains(id));
+        self.soft_edges
+            .retain(|(from, to)| reachable.contains(from) && reachable.contains(to));
+        self.remove_failure_parents(|parent_id| !reachable.contains(&parent_id));
+        self.redirects
+            .retain(|_, target| self.nodes.values().any(|node| &node.path == target));
     }
 
     pub fn build_with_pb(
         asset_dirs: &AssetDirs,
         max_recurse_depth: u32,
+        traversal_order: TraversalOrder,
     ) -> color_eyre::Result<Self> {
-        let mut pb = ProgressBar::new(1);
-        pb.set_style(indicatif::ProgressStyle::with_template(
-            "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {wide_msg}",
-        )?);
+        let mut pb = Self::new_pb()?;
+
+        let dependency_tree = DepTree::build(
+            asset_dirs,
+            max_recurse_depth,
+            traversal_order,
+            Some(&mut pb),
+            None,
+        )?;
+
+        pb.finish_with_message("Done");
+
+        dependency_tree.warn_ambiguous_origins();
+        dependency_tree.warn_truncated();
+
+        Ok(dependency_tree)
+    }
 
-        let dependency_tree = DepTree::build(asset_dirs, max_recurse_depth, Some(&mut pb))?;
+    pub fn build_combined_with_pb(
+        asset_dirs: &AssetDirs,
+        roots: &[PathBuf],
+        max_recurse_depth: u32,
+        traversal_order: TraversalOrder,
+    ) -> color_eyre::Result<Self> {
+        let mut pb = Self::new_pb()?;
+
+        let dependency_tree = DepTree::build_combined(
+            asset_dirs,
+            roots,
+            max_recurse_depth,
+            traversal_order,
+            Some(&mut pb),
+        )?;
 
         pb.finish_with_message("Done");
 
+        dependency_tree.warn_ambiguous_origins();
+        dependency_tree.warn_truncated();
+
         Ok(dependency_tree)
     }
 
+    /// Logs the assets whose origin might be misclassified, as it skews the origin filters and the git lookups
+    pub fn warn_ambiguous_origins(&self) {
+        for (node_id, reason) in self.ambiguous_origins() {
+            let node = self.get_node(node_id).unwrap();
+
+            log::warn!(
+                "The origin ({}) of {:?} might be wrong: {}",
+                node.origin.to_string(),
+                node.path,
+                reason
+            );
+        }
+    }
+
+    /// Logs that the tree is partial if any of the nodes had their dependencies cut off
+    pub fn warn_truncated(&self) {
+        if self.truncated.is_empty() {
+            return;
+        }
+
+        log::warn!(
+            "Only the first dependencies of {} nodes were followed, {} were left out",
+            self.truncated.len(),
+            self.truncated.values().sum::<usize>()
+        );
+
+        for (node_id, left_out) in self.truncated.iter().sorted() {
+            log::debug!(
+                "{} - {:?}: {} dependencies left out",
+                node_id,
+                self.get_node(*node_id).unwrap().path,
+                left_out
+            );
+        }
+    }
+
+    /// Progress bar for the long CLI operations
+    pub fn new_pb() -> color_eyre::Result<ProgressBar> {
+        let pb = ProgressBar::new(1);
+        pb.set_style(indicatif::ProgressStyle::with_template(
+            "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {wide_msg}",
+        )?);
+
+        Ok(pb)
+    }
+
     #[allow(dead_code)]
-    pub fn get_node(&self, id: NodeID) -> Option<Rc<Asset>> {
+    pub fn get_node(&self, id: NodeID) -> Option<Arc<Asset>> {
         self.nodes.get(&id).cloned()
     }
 
-    pub fn get_root_node(&self) -> Rc<Asset> {
+    pub fn get_root_node(&self) -> Arc<Asset> {
         self.nodes.get(&self.root_node_id).cloned().unwrap()
     }
 
+    pub fn find_node_id_by_path(&self, path: impl AsRef<Path>) -> Option<NodeID> {
+        self.nodes
+            .iter()
+            .find(|(_, node)| node.path == path.as_ref())
+            .map(|(&id, _)| id)
+    }
+
+    /// Finds the node of the asset the object path (e.g. `/Game/Characters/Hero`) resolves to,
+    /// or returns the reason why it isn't in the tree
+    pub fn find_node_by_object_path(
+        &self,
+        object_path: &str,
+        asset_dirs: &AssetDirs,
+    ) -> Result<NodeID, AssetError> {
+        let Some(content_dir) = &asset_dirs.content_dir else {
+            return Err(AssetError::new(
+                object_path,
+                AssetErrorKind::MissingContentDir,
+            ));
+        };
+
+        let path = object_path_to_disk_path(
+            object_path,
+            content_dir,
+            &asset_dirs.engine_content_dir,
+            &asset_dirs.plugins_dirs,
+            &asset_dirs.extra_content_roots,
+            asset_dirs.plugin_scan_depth,
+        )?;
+        // The node is stored under the path of the asset the redirector points to
+        let path = self.redirects.get(&path).cloned().unwrap_or(path);
+
+        self.find_node_id_by_path(&path).ok_or_else(|| {
+            self.failures
+                .iter()
+                .find(|failure| failure.path == path)
+                .cloned()
+                .unwrap_or_else(|| AssetError::new(&path, AssetErrorKind::NotInTree))
+        })
+    }
+
     #[allow(dead_code)]
-    pub fn find_node_by_path(&self, path: impl AsRef<Path>) -> Option<Rc<Asset>> {
+    pub fn find_node_by_path(&self, path: impl AsRef<Path>) -> Option<Arc<Asset>> {
         self.nodes
             .values()
             .find(|node| node.path == path.as_ref())
@@ -220,48 +922,991 @@ impl DepTree {
         self.node_connections.get(&id).cloned().unwrap_or_default()
     }
 
+    /// All the (dependent, dependency) pairs of the tree
+    pub fn edges(&self) -> impl Iterator<Item = (NodeID, NodeID)> + '_ {
+        self.node_connections
+            .iter()
+            .flat_map(|(&node_id, connections)| connections.iter().map(move |&con| (node_id, con)))
+    }
+
+    /// Redirectors that were resolved to the asset at the given path
+    pub fn get_redirectors_to(&self, path: impl AsRef<Path>) -> Vec<PathBuf> {
+        self.redirects
+            .iter()
+            .filter(|(_, target)| *target == path.as_ref())
+            .map(|(redirector, _)| redirector.clone())
+            .sorted()
+            .collect()
+    }
+
     pub fn get_recurse_depth(&self, id: NodeID) -> Option<u32> {
         self.recurse_depths.get(&id).copied()
     }
 
+    /// First parent of the node, the one it was discovered from, see [`DepTree::parents_of`]
     pub fn get_parent_node_id(&self, id: NodeID) -> Option<NodeID> {
+        self.parents_of
+            .get(&id)
+            .and_then(|parents| parents.first())
+            .copied()
+    }
+
+    /// Nodes that have the given one as their dependency, in the order they were connected
+    pub fn parents(&self, id: NodeID) -> Vec<NodeID> {
+        self.parents_of.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Shortest chain of the dependents leading from a root to the node, searched breadth first through the parents.
+    /// Starts with the root and ends with the node, empty if the node isn't in the tree
+    pub fn path_to_root(&self, id: NodeID) -> Vec<NodeID> {
+        if !self.nodes.contains_key(&id) {
+            return vec![];
+        }
+
+        // Child each node was reached from, walking up
+        let mut reached_from = HashMap::from([(id, id)]);
+        let mut queue = VecDeque::from([id]);
+
+        while let Some(node_id) = queue.pop_front() {
+            let parents = self.parents(node_id);
+
+            if node_id == self.root_node_id
+                || self.extra_root_node_ids.contains(&node_id)
+                || parents.is_empty()
+            {
+                let mut path = vec![node_id];
+                let mut cur_node_id = node_id;
+
+                while cur_node_id != id {
+                    cur_node_id = reached_from[&cur_node_id];
+                    path.push(cur_node_id);
+                }
+
+                return path;
+            }
+
+            for parent_id in parents {
+                reached_from.entry(parent_id).or_insert_with(|| {
+                    queue.push_back(parent_id);
+                    node_id
+                });
+            }
+        }
+
+        vec![id]
+    }
+
+    /// Out-degree of the node, without copying its connections
+    pub fn num_dependencies(&self, id: NodeID) -> usize {
         self.node_connections
+            .get(&id)
+            .map(Vec::len)
+            .unwrap_or_default()
+    }
+
+    /// In-degree of the node from the reverse index, the shared assets have the most
+    pub fn num_dependents(&self, id: NodeID) -> usize {
+        self.parents_of.get(&id).map(Vec::len).unwrap_or_default()
+    }
+
+    /// Connects the node to the dependencies already in the tree, skipping the existing connections
+    fn connect_known(&mut self, node_id: NodeID, known_deps: Vec<(NodeID, EdgeKind)>) {
+        for (dep_node_id, edge_kind) in known_deps {
+            if dep_node_id == node_id {
+                continue;
+            }
+
+            // The kinds of the existing connections are updated too, they can change when the node is rebuilt
+            match edge_kind {
+                EdgeKind::Hard => self.soft_edges.remove(&(node_id, dep_node_id)),
+                EdgeKind::Soft => self.soft_edges.insert((node_id, dep_node_id)),
+            };
+
+            let connections = self.node_connections.entry(node_id).or_default();
+
+            if connections.contains(&dep_node_id) {
+                continue;
+            }
+
+            connections.push(dep_node_id);
+            self.parents_of
+                .entry(dep_node_id)
+                .or_default()
+                .push(node_id);
+        }
+    }
+
+    /// Parents of the nodes that are still connected to them by the given connections, keeping their order
+    fn retained_parents(
+        &self,
+        node_connections: &HashMap<NodeID, Vec<NodeID>>,
+    ) -> HashMap<NodeID, Vec<NodeID>> {
+        self.parents_of
             .iter()
-            .find(|(_, children)| children.contains(&id))
-            .map(|(parent, _)| *parent)
+            .filter_map(|(&node_id, parents)| {
+                let parents = parents
+                    .iter()
+                    .copied()
+                    .filter(|parent_id| {
+                        node_connections
+                            .get(parent_id)
+                            .map(|connections| connections.contains(&node_id))
+                            .unwrap_or_default()
+                    })
+                    .collect_vec();
+
+                (!parents.is_empty()).then_some((node_id, parents))
+            })
+            .collect()
+    }
+
+    /// Maps every node with a parent to its parents, sorted by their ids
+    fn parents_by_connections(
+        node_connections: &HashMap<NodeID, Vec<NodeID>>,
+    ) -> HashMap<NodeID, Vec<NodeID>> {
+        node_connections
+            .iter()
+            .sorted_by_key(|(&node_id, _)| node_id)
+            .fold(HashMap::new(), |mut parents_of, (&node_id, connections)| {
+                for &con_node_id in connections {
+                    parents_of.entry(con_node_id).or_default().push(node_id);
+                }
+                parents_of
+            })
+    }
+
+    /// Reverse index of the connections, the nodes mapped to the nodes that depend on them
+    pub fn dependents(&self) -> HashMap<NodeID, Vec<NodeID>> {
+        self.edges()
+            .sorted()
+            .fold(HashMap::new(), |mut dependents, (node_id, con_node_id)| {
+                dependents
+                    .entry(con_node_id)
+                    .or_insert_with(Vec::new)
+                    .push(node_id);
+                dependents
+            })
+    }
+
+    /// If any of the dependency chains leads back to an asset already on it
+    pub fn has_cycles(&self) -> bool {
+        // Nodes whose dependencies are all checked, and the ones on the current chain
+        let mut done = HashSet::new();
+        let mut on_chain = HashSet::new();
+
+        for &start_id in self.nodes.keys().sorted() {
+            if done.contains(&start_id) {
+                continue;
+            }
+
+            // Each entry is a node and the index of its next dependency to check
+            let mut stack = vec![(start_id, 0)];
+            on_chain.insert(start_id);
+
+            while let Some((node_id, index)) = stack.last_mut() {
+                let node_id = *node_id;
+
+                match self.get_node_connections(node_id).get(*index).copied() {
+                    Some(con_node_id) => {
+                        *index += 1;
+
+                        if on_chain.contains(&con_node_id) {
+                            return true;
+                        }
+
+                        if !done.contains(&con_node_id) {
+                            on_chain.insert(con_node_id);
+                            stack.push((con_node_id, 0));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        on_chain.remove(&node_id);
+                        done.insert(node_id);
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// If the node has no dependencies in the tree, the nodes cut off by the maximum recurse depth don't count
+    pub fn is_leaf(&self, id: NodeID) -> bool {
+        self.get_node_connections(id).is_empty()
+            && self
+                .get_recurse_depth(id)
+                .map(|depth| depth < self.max_recurse_depth)
+                .unwrap_or_default()
+    }
+
+    /// Absolute paths of all the files in the tree, including the root and the followed redirectors
+    pub fn all_paths(&self) -> Vec<PathBuf> {
+        self.nodes
+            .values()
+            .map(|node| &node.path)
+            .chain(self.redirects.keys())
+            .map(|path| std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    /// Nodes without dependencies in the tree, e.g. textures and sounds
+    pub fn leaves(&self) -> Vec<NodeID> {
+        self.nodes
+            .keys()
+            .copied()
+            .filter(|&node_id| self.is_leaf(node_id))
+            .sorted()
+            .collect()
+    }
+
+    /// Leaves that reference other assets, so their dependencies failed to resolve (or were skipped)
+    pub fn is_broken_leaf(&self, id: NodeID) -> bool {
+        self.is_leaf(id)
+            && self
+                .get_node(id)
+                .map(|node| node.has_package_dependencies())
+                .unwrap_or_default()
+    }
+
+    /// Depth of the deepest node in the tree
+    pub fn depth(&self) -> u32 {
+        self.recurse_depths
+            .values()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// How the node refers to its connection
+    pub fn edge_kind(&self, from: NodeID, to: NodeID) -> EdgeKind {
+        match self.soft_edges.contains(&(from, to)) {
+            true => EdgeKind::Soft,
+            false => EdgeKind::Hard,
+        }
+    }
+
+    /// Positions of the nodes on the gradient of the color mode, from 0 for the lowest value in the tree to 1 for the highest.
+    /// Empty for the modes that aren't gradients
+    pub fn color_gradient(&self, color_mode: ColorMode) -> HashMap<NodeID, f32> {
+        let values = match color_mode {
+            ColorMode::OutDegree => self
+                .nodes
+                .keys()
+                .map(|&node_id| (node_id, self.get_node_connections(node_id).len() as u32))
+                .collect_vec(),
+            ColorMode::Depth => self
+                .nodes
+                .keys()
+                .map(|&node_id| (node_id, self.get_recurse_depth(node_id).unwrap_or_default()))
+                .collect_vec(),
+            ColorMode::Origin | ColorMode::GitStatus => return HashMap::new(),
+        };
+
+        let (min, max) = values
+            .iter()
+            .map(|(_, value)| *value)
+            .minmax()
+            .into_option()
+            .unwrap_or_default();
+
+        values
+            .into_iter()
+            .map(|(node_id, value)| {
+                (
+                    node_id,
+                    match max > min {
+                        true => (value - min) as f32 / (max - min) as f32,
+                        false => 0.0,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Graphviz colors of the nodes in the color mode, `is_modified` tells the assets modified in the git repos.
+    /// The gradients go from blue to red, the origin mode keeps the default colors
+    pub fn graphviz_colors(
+        &self,
+        color_mode: ColorMode,
+        is_modified: impl Fn(&Asset) -> bool,
+    ) -> HashMap<NodeID, String> {
+        match color_mode {
+            ColorMode::Origin => HashMap::new(),
+            ColorMode::GitStatus => self
+                .nodes
+                .iter()
+                .filter(|(_, node)| is_modified(node))
+                .map(|(&node_id, _)| (node_id, "orange".to_string()))
+                .collect(),
+            ColorMode::OutDegree | ColorMode::Depth => self
+                .color_gradient(color_mode)
+                .into_iter()
+                .map(|(node_id, position)| {
+                    // Graphviz takes the colors as "hue saturation value", from blue (2/3) to red (0)
+                    (
+                        node_id,
+                        format!("{:.3} 1.000 0.900", (1.0 - position) * 2.0 / 3.0),
+                    )
+                })
+                .collect(),
+        }
     }
 
-    pub fn get_parent_node(&self, id: NodeID) -> Option<Rc<Asset>> {
+    /// Combined size of the asset files of the node and everything it depends on, each counted once
+    pub fn subtree_size(&self, root: NodeID) -> u64 {
+        let mut visited = HashSet::from([root]);
+        let mut unvisited_nodes_ids = vec![root];
+
+        while let Some(cur_node_id) = unvisited_nodes_ids.pop() {
+            for con_node_id in self.get_node_connections(cur_node_id) {
+                if visited.insert(con_node_id) {
+                    unvisited_nodes_ids.push(con_node_id);
+                }
+            }
+        }
+
+        visited
+            .into_iter()
+            .filter_map(|node_id| self.nodes.get(&node_id))
+            .map(|node| node.file_size())
+            .sum()
+    }
+
+    pub fn get_parent_node(&self, id: NodeID) -> Option<Arc<Asset>> {
         self.get_parent_node_id(id)
             .and_then(|parent_id| self.get_node(parent_id))
     }
 
-    pub fn print_node_paths(&self) {
-        let res = self
+    /// Extracts the subtree of the `root` node with at most `max_depth` levels of dependencies.
+    /// Node ids are kept the same as in the original tree, depths are counted from the new root
+    pub fn subtree(&self, root: NodeID, max_depth: u32) -> Option<DepTree> {
+        let root_node = self.get_node(root)?;
+
+        let mut nodes = HashMap::from([(root, root_node)]);
+        let mut node_connections = HashMap::new();
+        let mut recurse_depths = HashMap::from([(root, 0)]);
+
+        let mut unresolved_nodes_ids = VecDeque::from([root]);
+
+        while let Some(cur_node_id) = unresolved_nodes_ids.pop_front() {
+            let cur_depth = *recurse_depths.get(&cur_node_id).unwrap();
+
+            if cur_depth >= max_depth {
+                continue;
+            }
+
+            let connections = self.get_node_connections(cur_node_id);
+
+            for &con_node_id in &connections {
+                if nodes.contains_key(&con_node_id) {
+                    continue;
+                }
+
+                if let Some(con_node) = self.get_node(con_node_id) {
+                    nodes.insert(con_node_id, con_node);
+                    recurse_depths.insert(con_node_id, cur_depth + 1);
+                    unresolved_nodes_ids.push_back(con_node_id);
+                }
+            }
+
+            if !connections.is_empty() {
+                node_connections.insert(cur_node_id, connections);
+            }
+        }
+
+        let redirects = self
+            .redirects
+            .iter()
+            .filter(|(_, target)| nodes.values().any(|node| &node.path == *target))
+            .map(|(redirector, target)| (redirector.clone(), target.clone()))
+            .collect();
+        let soft_edges = self
+            .soft_edges
+            .iter()
+            .filter(|(node_id, con_node_id)| {
+                nodes.contains_key(node_id) && nodes.contains_key(con_node_id)
+            })
+            .copied()
+            .collect();
+        let aggregated = self
+            .aggregated
+            .iter()
+            .filter(|(node_id, _)| nodes.contains_key(node_id))
+            .map(|(&node_id, aggregated_node)| (node_id, aggregated_node.clone()))
+            .collect();
+        // The nodes at the maximum depth of the subtree aren't expanded, so nothing of them is truncated
+        // and nothing they reference fails there
+        let expanded = |node_id: &NodeID| {
+            recurse_depths
+                .get(node_id)
+                .map(|&depth| depth < max_depth)
+                .unwrap_or_default()
+        };
+        let truncated = self
+            .truncated
+            .iter()
+            .filter(|(node_id, _)| expanded(node_id))
+            .map(|(&node_id, &left_out)| (node_id, left_out))
+            .collect();
+
+        Some(Self {
+            root_node_id: root,
+            extra_root_node_ids: vec![],
+            nodes,
+            parents_of: self.retained_parents(&node_connections),
+            node_connections,
+            // Failures of the nodes of the subtree, the ones only referenced from the outside are left out
+            failures: self
+                .failures
+                .iter()
+                .filter(|fail| self.failure_parents(fail).iter().any(&expanded))
+                .cloned()
+                .collect(),
+            failure_parents: self
+                .failure_parents
+                .iter()
+                .filter_map(|(path, parents)| {
+                    let parents = parents
+                        .iter()
+                        .copied()
+                        .filter(|parent_id| expanded(parent_id))
+                        .collect_vec();
+
+                    (!parents.is_empty()).then_some((path.clone(), parents))
+                })
+                .collect(),
+            retried_failures: HashSet::new(),
+            redirects,
+            soft_edges,
+            aggregated,
+            truncated,
+
+            max_recurse_depth: max_depth,
+            recurse_depths,
+            traversal_order: self.traversal_order,
+            next_id: self.next_id,
+        })
+    }
+
+    /// Weakly connected components of the tree, the connections are followed both ways.
+    /// The nodes and the components are sorted by the ids, so the one with the main root comes first
+    pub fn components(&self) -> Vec<Vec<NodeID>> {
+        fn find(parents: &mut HashMap<NodeID, NodeID>, mut node_id: NodeID) -> NodeID {
+            while parents[&node_id] != node_id {
+                let grandparent = parents[&parents[&node_id]];
+                parents.insert(node_id, grandparent);
+                node_id = grandparent;
+            }
+
+            node_id
+        }
+
+        // Union-find with path halving, every node starts as its own component
+        let mut parents = self
+            .nodes
+            .keys()
+            .map(|&node_id| (node_id, node_id))
+            .collect::<HashMap<_, _>>();
+
+        for (node_id, con_node_id) in self.edges() {
+            if !parents.contains_key(&node_id) || !parents.contains_key(&con_node_id) {
+                continue;
+            }
+
+            let (root1, root2) = (find(&mut parents, node_id), find(&mut parents, con_node_id));
+            // The lower id becomes the representative, it doesn't matter which one as long as it's consistent
+            if root1 != root2 {
+                parents.insert(root1.max(root2), root1.min(root2));
+            }
+        }
+
+        let node_ids = self.nodes.keys().copied().sorted().collect_vec();
+
+        node_ids
+            .into_iter()
+            .map(|node_id| (find(&mut parents, node_id), node_id))
+            .into_group_map()
+            .into_values()
+            .map(|component| component.into_iter().sorted().collect_vec())
+            .sorted_by_key(|component| component[0])
+            .collect()
+    }
+
+    /// Copy of the tree with only the nodes of one of its [`DepTree::components`].
+    /// The main root is kept if it's in the component, otherwise the lowest of its roots (or nodes) becomes the root
+    pub fn component(&self, node_ids: &[NodeID]) -> DepTree {
+        let in_component = node_ids.iter().copied().collect::<HashSet<_>>();
+
+        let roots = std::iter::once(self.root_node_id)
+            .chain(self.extra_root_node_ids.iter().copied())
+            .filter(|node_id| in_component.contains(node_id))
+            .collect_vec();
+        let (root_node_id, extra_root_node_ids) = match roots.split_first() {
+            Some((&root_node_id, extra_root_node_ids)) => {
+                (root_node_id, extra_root_node_ids.to_vec())
+            }
+            None => (
+                node_ids.iter().copied().min().unwrap_or(self.root_node_id),
+                vec![],
+            ),
+        };
+
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(node_id, _)| in_component.contains(node_id))
+            .map(|(&node_id, node)| (node_id, node.clone()))
+            .collect::<HashMap<_, _>>();
+        // The components are closed, the connections of their nodes never lead outside of them
+        let node_connections = self
+            .node_connections
+            .iter()
+            .filter(|(node_id, _)| in_component.contains(node_id))
+            .map(|(&node_id, connections)| (node_id, connections.clone()))
+            .collect::<HashMap<_, _>>();
+        let failure_parents = self
+            .failure_parents
+            .iter()
+            .filter_map(|(path, parents)| {
+                let parents = parents
+                    .iter()
+                    .copied()
+                    .filter(|parent_id| in_component.contains(parent_id))
+                    .collect_vec();
+
+                (!parents.is_empty()).then_some((path.clone(), parents))
+            })
+            .collect::<HashMap<_, _>>();
+
+        Self {
+            root_node_id,
+            extra_root_node_ids,
+            parents_of: self.retained_parents(&node_connections),
+            node_connections,
+            failures: self
+                .failures
+                .iter()
+                .filter(|fail| failure_parents.contains_key(&fail.path))
+                .cloned()
+                .collect(),
+            failure_parents,
+            retried_failures: self.retried_failures.clone(),
+            redirects: self
+                .redirects
+                .iter()
+                .filter(|(_, target)| nodes.values().any(|node| &node.path == *target))
+                .map(|(redirector, target)| (redirector.clone(), target.clone()))
+                .collect(),
+            soft_edges: self
+                .soft_edges
+                .iter()
+                .filter(|(node_id, _)| in_component.contains(node_id))
+                .copied()
+                .collect(),
+            aggregated: self
+                .aggregated
+                .iter()
+                .filter(|(node_id, _)| in_component.contains(node_id))
+                .map(|(&node_id, aggregated_node)| (node_id, aggregated_node.clone()))
+                .collect(),
+            truncated: self
+                .truncated
+                .iter()
+                .filter(|(node_id, _)| in_component.contains(node_id))
+                .map(|(&node_id, &left_out)| (node_id, left_out))
+                .collect(),
+            nodes,
+
+            max_recurse_depth: self.max_recurse_depth,
+            recurse_depths: self
+                .recurse_depths
+                .iter()
+                .filter(|(node_id, _)| in_component.contains(node_id))
+                .map(|(&node_id, &depth)| (node_id, depth))
+                .collect(),
+            traversal_order: self.traversal_order,
+            next_id: self.next_id,
+        }
+    }
+
+    /// Collapses the nodes with the same key into a single node, e.g. all the assets of a plugin.
+    /// The roots or the nodes with the lowest ids stand for their groups, the nodes without a key are kept as they are
+    pub fn aggregate_by(&self, key: impl Fn(&Asset) -> Option<String>) -> DepTree {
+        let mut groups = BTreeMap::<String, Vec<NodeID>>::new();
+        for (&node_id, node) in &self.nodes {
+            if let Some(key) = key(node) {
+                groups.entry(key).or_default().push(node_id);
+            }
+        }
+
+        let is_root = |node_id: &NodeID| {
+            *node_id == self.root_node_id || self.extra_root_node_ids.contains(node_id)
+        };
+
+        let mut representatives = HashMap::new();
+        let mut aggregated = HashMap::new();
+        for (name, node_ids) in groups {
+            let representative = node_ids
+                .iter()
+                .copied()
+                .min_by_key(|node_id| (!is_root(node_id), *node_id))
+                .unwrap();
+
+            representatives.extend(node_ids.iter().map(|&node_id| (node_id, representative)));
+            aggregated.insert(
+                representative,
+                AggregatedNode {
+                    name,
+                    node_count: node_ids.len(),
+                    edge_count: 0,
+                },
+            );
+        }
+
+        let representative =
+            |node_id: NodeID| representatives.get(&node_id).copied().unwrap_or(node_id);
+
+        let nodes = self
             .nodes
             .iter()
-            .sorted_by(|(_, asset), (_, asset2)| asset.path.cmp(&asset2.path))
+            .filter(|(&node_id, _)| representative(node_id) == node_id)
+            .map(|(&node_id, node)| (node_id, node.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let mut node_connections = HashMap::<NodeID, Vec<NodeID>>::new();
+        let mut hard_edges = HashSet::new();
+        for (node_id, con_node_id) in self.edges().sorted() {
+            let (from, to) = (representative(node_id), representative(con_node_id));
+
+            // The edges inside a group are gone with it
+            if from == to {
+                continue;
+            }
+
+            if let Some(aggregated_node) = aggregated.get_mut(&to) {
+                aggregated_node.edge_count += 1;
+            }
+
+            let connections = node_connections.entry(from).or_default();
+            if !connections.contains(&to) {
+                connections.push(to);
+            }
+
+            // Collapsed edges are soft only if all of them are
+            if self.edge_kind(node_id, con_node_id) == EdgeKind::Hard {
+                hard_edges.insert((from, to));
+            }
+        }
+
+        let soft_edges = node_connections
+            .iter()
+            .flat_map(|(&from, connections)| connections.iter().map(move |&to| (from, to)))
+            .filter(|edge| !hard_edges.contains(edge))
+            .collect();
+
+        // Groups are as deep as their shallowest node
+        let mut recurse_depths = HashMap::<NodeID, u32>::new();
+        for (&node_id, &depth) in &self.recurse_depths {
+            let cur_depth = recurse_depths
+                .entry(representative(node_id))
+                .or_insert(depth);
+            *cur_depth = (*cur_depth).min(depth);
+        }
+
+        Self {
+            root_node_id: self.root_node_id,
+            extra_root_node_ids: self.extra_root_node_ids.clone(),
+            nodes,
+            parents_of: Self::parents_by_connections(&node_connections),
+            node_connections,
+            failures: self.failures.clone(),
+            failure_parents: self
+                .failure_parents
+                .iter()
+                .map(|(path, parents)| {
+                    (
+                        path.clone(),
+                        parents
+                            .iter()
+                            .map(|&parent_id| representative(parent_id))
+                            .unique()
+                            .collect(),
+                    )
+                })
+                .collect(),
+            retried_failures: self.retried_failures.clone(),
+            redirects: self.redirects.clone(),
+            soft_edges,
+            aggregated,
+            // Only the nodes kept as they are, the groups don't tell which of their nodes were truncated
+            truncated: self
+                .truncated
+                .iter()
+                .filter(|(node_id, _)| !representatives.contains_key(node_id))
+                .map(|(&node_id, &left_out)| (node_id, left_out))
+                .collect(),
+
+            max_recurse_depth: self.max_recurse_depth,
+            recurse_depths,
+            traversal_order: self.traversal_order,
+            next_id: self.next_id,
+        }
+    }
+
+    /// Collapses the assets of each engine plugin into a single node
+    pub fn collapse_engine_plugins(&self) -> DepTree {
+        self.aggregate_by(|asset| match asset.origin {
+            AssetOrigin::EnginePlugin => asset.plugin_name(),
+            _ => None,
+        })
+    }
+
+    /// Serializes the tree to GraphML (e.g. for Gephi).
+    /// The attribute keys are part of the format, saved workspaces rely on them not changing
+    pub fn to_graphml(&self) -> String {
+        let mut res = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="label" for="node" attr.name="label" attr.type="string"/>
+  <key id="origin" for="node" attr.name="origin" attr.type="string"/>
+  <key id="depth" for="node" attr.name="depth" attr.type="int"/>
+"#,
+        );
+
+        res += &format!(
+            "  <graph id=\"{}\" edgedefault=\"directed\">\n",
+            xml_escape(&fix_file_name(self.get_root_node().path.file_name()))
+        );
+
+        for (node_id, node) in self.nodes.iter().sorted_by_key(|(id, _)| **id) {
+            res += &format!(
+                "    <node id=\"{}\">\n      <data key=\"label\">{}</data>\n      <data key=\"origin\">{}</data>\n      <data key=\"depth\">{}</data>\n    </node>\n",
+                node_id,
+                xml_escape(&node.file_name_str().unwrap_or_default()),
+                xml_escape(&node.origin.to_string()),
+                self.get_recurse_depth(*node_id).unwrap_or_default(),
+            );
+        }
+
+        for (source, target) in self.edges().sorted() {
+            res += &format!("    <edge source=\"{}\" target=\"{}\"/>\n", source, target);
+        }
+
+        res + "  </graph>\n</graphml>\n"
+    }
+
+    /// Compares the trees by the asset paths, as the node ids aren't stable across runs
+    pub fn diff(&self, other: &DepTree) -> TreeDiff {
+        TreeSnapshot::from(self).diff(&TreeSnapshot::from(other))
+    }
+
+    /// Paths of the nodes with their versions, only the ones saved with an engine older than `older_than` if it's set
+    pub fn node_paths_list(&self, sort_by: NodeSortKey, older_than: Option<(u16, u16)>) -> String {
+        self.nodes
+            .iter()
+            .filter(|(_, asset)| match older_than {
+                Some(older_than) => asset.is_older_than(older_than),
+                None => true,
+            })
+            .sorted_by(|(id, asset), (id2, asset2)| match sort_by {
+                NodeSortKey::Path => asset.path.cmp(&asset2.path),
+                NodeSortKey::Id => id.cmp(id2),
+                NodeSortKey::Origin => asset
+                    .origin
+                    .cmp(&asset2.origin)
+                    .then_with(|| asset.path.cmp(&asset2.path)),
+            })
             .fold(
                 "\n===== Loaded Asset Paths =====\n".to_string(),
-                |res, (node_id, asset)| format!("{}Node {} - {:?}\n", res, &node_id, asset.path),
+                |res, (node_id, asset)| {
+                    format!(
+                        "{}Node {} - {:?} (Engine {}, File Version {})\n",
+                        res,
+                        &node_id,
+                        asset.path,
+                        asset.engine_version(),
+                        asset.file_version()
+                    )
+                },
             )
-            + "==============================\n";
+            + "==============================\n"
+    }
+
+    /// The tree indented like `tree` does, following the connections from the roots, a line per node.
+    /// Nodes reached again (shared dependencies and cycles) are marked as seen and their dependencies aren't repeated
+    pub fn ascii_tree(&self) -> String {
+        let mut res = String::new();
+        let mut visited = HashSet::new();
+        // Node, the prefix of its line and if it's the last child of its parent, `None` for the roots
+        let mut stack = std::iter::once(self.root_node_id)
+            .chain(self.extra_root_node_ids.iter().copied())
+            .rev()
+            .map(|node_id| (node_id, String::new(), None))
+            .collect_vec();
+
+        while let Some((node_id, prefix, last)) = stack.pop() {
+            let name = self
+                .get_node(node_id)
+                .and_then(|node| node.file_name_str())
+                .unwrap_or_else(|| "...Unknown...".to_string());
+            let seen = !visited.insert(node_id);
+            let parents_count = self.parents(node_id).len();
+
+            res += &format!(
+                "{}{}{} - {}{}{}\n",
+                prefix,
+                match last {
+                    Some(true) => "└── ",
+                    Some(false) => "├── ",
+                    None => "",
+                },
+                node_id,
+                name,
+                match (seen, parents_count > 1) {
+                    (true, _) => " (seen)".to_string(),
+                    (false, true) => format!(" ({} parents)", parents_count),
+                    (false, false) => String::new(),
+                },
+                match (seen, self.truncated.get(&node_id)) {
+                    (false, Some(left_out)) => format!(" (+{} not followed)", left_out),
+                    _ => String::new(),
+                }
+            );
 
-        log::info!("{}", res);
+            if seen {
+                continue;
+            }
+
+            let child_prefix = match last {
+                Some(true) => format!("{}    ", prefix),
+                Some(false) => format!("{}│   ", prefix),
+                None => prefix,
+            };
+            let connections = self.get_node_connections(node_id);
+
+            stack.extend(
+                connections
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(index, &con_node_id)| {
+                        (
+                            con_node_id,
+                            child_prefix.clone(),
+                            Some(index == connections.len() - 1),
+                        )
+                    }),
+            );
+        }
+
+        res
     }
 
-    pub fn print_fails(&self) {
-        let res = self
-            .failures
-            .iter()
+    /// Leaves of the tree with their sizes, followed by their amount and total size
+    pub fn leaves_list(&self) -> String {
+        let leaves = self.leaves();
+
+        let res = leaves.iter().fold(
+            "\n===== Leaves of the dependency tree =====\n".to_string(),
+            |res, &node_id| {
+                let node = self.get_node(node_id).unwrap();
+
+                format!(
+                    "{}{} - {:?} ({}){}\n",
+                    res,
+                    node_id,
+                    node.path,
+                    format_size(node.file_size()),
+                    match self.is_broken_leaf(node_id) {
+                        true => " [dependencies failed to resolve]",
+                        false => "",
+                    }
+                )
+            },
+        ) + "=========================================\n";
+
+        format!(
+            "{}{} leaves, {} in total",
+            res,
+            leaves.len(),
+            format_size(
+                leaves
+                    .iter()
+                    .filter_map(|node_id| self.nodes.get(node_id))
+                    .map(|node| node.file_size())
+                    .sum()
+            )
+        )
+    }
+
+    /// The amount and the size of the assets in the tree per origin, a line per origin
+    pub fn stats(&self) -> String {
+        self.nodes
+            .values()
+            .into_group_map_by(|node| node.origin)
+            .into_iter()
+            .sorted_by_key(|(origin, _)| *origin)
+            .fold(String::new(), |res, (origin, nodes)| {
+                format!(
+                    "{}{:?}: {} assets, {}\n",
+                    res,
+                    origin,
+                    nodes.len(),
+                    format_size(nodes.iter().map(|node| node.file_size()).sum())
+                )
+            })
+    }
+
+    /// Overview of the whole tree as text, for pasting into the bug reports
+    pub fn summary(&self) -> String {
+        format!(
+            "Dependency tree of {:?}\nNodes: {}, edges: {}, depth: {}\n{}Failures: {}, skipped: {}, truncated nodes: {}\nTotal size: {}\n",
+            self.get_root_node().path,
+            self.nodes.len(),
+            self.edges().count(),
+            self.depth(),
+            self.stats(),
+            self.real_failures().count(),
+            self.failures.len() - self.real_failures().count(),
+            self.truncated.len(),
+            format_size(self.nodes.values().map(|node| node.file_size()).sum())
+        )
+    }
+
+    /// Failures excluding the dependencies that were skipped on purpose
+    pub fn real_failures(&self) -> impl Iterator<Item = &AssetError> {
+        self.failures.iter().filter(|fail| !fail.is_skipped())
+    }
+
+    /// Failures that aren't skipped nor ambiguous, sorted by their paths
+    pub fn failures_list(&self) -> String {
+        self.real_failures()
+            .filter(|fail| !fail.is_ambiguous())
             .sorted_by(|fail1, fail2| fail1.path.cmp(&fail2.path))
             .fold(
                 "\n===== Errors during the building of the dependency tree =====\n".to_string(),
                 |res, err| format!("{}{}\n", res, &err.to_string()),
             )
-            + "============================================================\n";
+            + "============================================================\n"
+    }
+
+    /// Dependencies matching several assets, sorted by their paths, `None` if there are none
+    pub fn ambiguous_list(&self) -> Option<String> {
+        let ambiguous = self
+            .real_failures()
+            .filter(|fail| fail.is_ambiguous())
+            .sorted_by(|fail1, fail2| fail1.path.cmp(&fail2.path))
+            .collect_vec();
 
-        log::error!("{}", res);
+        (!ambiguous.is_empty()).then(|| {
+            ambiguous.into_iter().fold(
+                "\n===== Dependencies matching several assets =====\n".to_string(),
+                |res, err| format!("{}{}\n", res, &err.to_string()),
+            ) + "================================================\n"
+        })
     }
 }
 
@@ -272,61 +1917,194 @@ use indicatif::ProgressBar;
 
 use crate::asset::AssetDirs;
 
-fn fix_file_name(file_name: Option<&OsStr>) -> String {
-    file_name
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .replace([' ', '-'], "_")
+/// Turns the file name (without the extension) into a valid graphviz id,
+/// falling back to "unknown" for the paths without a file name
+pub fn fix_file_name(file_name: Option<&OsStr>) -> String {
+    let file_name = file_name
+        .map(|file_name| file_name.to_string_lossy())
+        .unwrap_or_default();
+
+    // Plain ids can only contain letters, digits and underscores
+    let name = file_name
         .split('.')
         .next()
-        .unwrap()
-        .to_owned()
+        .unwrap_or_default()
+        .chars()
+        .map(|char| match char.is_ascii_alphanumeric() {
+            true => char,
+            false => '_',
+        })
+        .collect::<String>();
+
+    match name.chars().next() {
+        None => "unknown".to_string(),
+        // ...and can't start with a digit
+        Some(first_char) if first_char.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Quoted graphviz id of the text, for the labels and tooltips
+fn escaped_id(text: &str) -> graphviz_rust::dot_structures::Id {
+    graphviz_rust::dot_structures::Id::Escaped(format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
 }
 
 impl From<DepTree> for Graph {
     fn from(value: DepTree) -> Self {
+        value.to_graph(None, false, &HashMap::new(), None, None, false)
+    }
+}
+
+impl DepTree {
+    /// Converts the tree to a graphviz graph, keeping only the given nodes (and the edges between them) if they're set.
+    /// The labels are the file names, followed by the classes of the assets if `label_with_class` is set.
+    /// The nodes in `node_colors` are outlined with their colors, see [`DepTree::graphviz_colors`].
+    /// The `title` is rendered at the top of the graph.
+    /// The names longer than `label_max_len` are cut in the middle, the full ones are kept in the node tooltips.
+    /// With `failure_nodes` the unresolved dependencies are added as dashed red nodes connected to their parents
+    pub fn to_graph(
+        &self,
+        only_nodes: Option<&HashSet<NodeID>>,
+        label_with_class: bool,
+        node_colors: &HashMap<NodeID, String>,
+        title: Option<&str>,
+        label_max_len: Option<usize>,
+        failure_nodes: bool,
+    ) -> Graph {
         use graphviz_rust::dot_structures::Id;
 
-        let root_node = value.get_root_node();
+        let keep = |node_id: &NodeID| {
+            only_nodes
+                .map(|only_nodes| only_nodes.contains(node_id))
+                .unwrap_or(true)
+        };
 
-        let graph_node_ids = value
+        let root_node = self.get_root_node();
+
+        let graph_node_ids = self
             .nodes
-            .iter()
-            .map(|(&node_id, node)| {
+            .keys()
+            .filter(|node_id| keep(node_id))
+            .map(|&node_id| {
                 use graphviz_rust::dot_structures::NodeId;
 
+                // Keyed on the node id, as different assets can have the same file name
                 (
                     node_id,
-                    NodeId(Id::Plain(fix_file_name(node.path.file_name())), None),
+                    NodeId(Id::Plain(format!("node_{}", node_id)), None),
                 )
             })
             .collect::<HashMap<_, _>>();
 
         let mut statements = vec![];
-        statements.extend(value.nodes.iter().flat_map(|(node_id, _)| {
-            use graphviz_rust::dot_structures::{Edge, EdgeTy, Node, Stmt, Vertex};
-
-            let mut sub_statements = vec![];
-
-            let graph_node_id = graph_node_ids.get(node_id).unwrap();
-
-            sub_statements.push(Stmt::Node(Node::new(graph_node_id.clone(), vec![])));
+        if let Some(title) = title {
+            use graphviz_rust::dot_structures::{Attribute, GraphAttributes, Stmt};
+
+            statements.push(Stmt::GAttribute(GraphAttributes::Graph(vec![
+                Attribute(Id::Plain("label".to_string()), escaped_id(title)),
+                Attribute(
+                    Id::Plain("labelloc".to_string()),
+                    Id::Plain("t".to_string()),
+                ),
+            ])));
+        }
+        statements.extend(
+            // Sorted like the GraphML export, so the same tree always gives the same dot
+            self.nodes
+                .keys()
+                .filter(|node_id| keep(node_id))
+                .sorted()
+                .map(|node_id| {
+                    use graphviz_rust::dot_structures::{Attribute, Node, Stmt};
+
+                    let node = self.get_node(*node_id);
+                    let name = match self.aggregated.get(node_id) {
+                        Some(aggregated_node) => aggregated_node.name.clone(),
+                        None => node
+                            .as_ref()
+                            .and_then(|node| {
+                                node.path
+                                    .file_name()
+                                    .map(|file_name| file_name.to_string_lossy().to_string())
+                            })
+                            .unwrap_or_else(|| node_id.to_string()),
+                    };
+                    // Only the label is cut, the node id of the graph stays the same
+                    let short_name = match label_max_len {
+                        Some(label_max_len) => ellipsize_middle(&name, label_max_len),
+                        None => name.clone(),
+                    };
+                    let label = match self.aggregated.get(node_id) {
+                        Some(aggregated_node) => {
+                            format!("{}\n[{}]", short_name, aggregated_node.badge())
+                        }
+                        None => match node.and_then(|node| node.primary_class()) {
+                            Some(class) if label_with_class => {
+                                format!("{}\n({})", short_name, class)
+                            }
+                            _ => short_name.clone(),
+                        },
+                    };
+
+                    let mut attributes = vec![Attribute(
+                        Id::Plain("label".to_string()),
+                        escaped_id(&label),
+                    )];
+                    if short_name != name {
+                        attributes.push(Attribute(
+                            Id::Plain("tooltip".to_string()),
+                            escaped_id(&name),
+                        ));
+                    }
+                    if let Some(color) = node_colors.get(node_id) {
+                        attributes.push(Attribute(
+                            Id::Plain("color".to_string()),
+                            Id::Escaped(format!("\"{}\"", color)),
+                        ));
+                    }
+
+                    Stmt::Node(Node::new(
+                        graph_node_ids.get(node_id).unwrap().clone(),
+                        attributes,
+                    ))
+                }),
+        );
+        statements.extend(
+            self.edges()
+                .filter(|(node_id, connection_node_id)| keep(node_id) && keep(connection_node_id))
+                .sorted()
+                .map(|(node_id, connection_node_id)| {
+                    use graphviz_rust::dot_structures::{Attribute, Edge, EdgeTy, Stmt, Vertex};
 
-            sub_statements.extend(value.get_node_connections(*node_id).iter().map(
-                |connection_node_id| {
                     Stmt::Edge(Edge {
                         ty: EdgeTy::Pair(
-                            Vertex::N(graph_node_id.clone()),
-                            Vertex::N(graph_node_ids.get(connection_node_id).unwrap().clone()),
+                            Vertex::N(graph_node_ids.get(&node_id).unwrap().clone()),
+                            Vertex::N(graph_node_ids.get(&connection_node_id).unwrap().clone()),
                         ),
-                        attributes: vec![],
+                        // The soft references are dashed
+                        attributes: match self.edge_kind(node_id, connection_node_id) {
+                            EdgeKind::Hard => vec![],
+                            EdgeKind::Soft => vec![Attribute(
+                                Id::Plain("style".to_string()),
+                                Id::Plain("dashed".to_string()),
+                            )],
+                        },
                     })
-                },
-            ));
-
-            sub_statements
-        }));
+                }),
+        );
+        if failure_nodes {
+            statements.extend(self.failure_statements(keep, label_max_len));
+        }
 
         Graph::DiGraph {
             id: Id::Plain(format!(
@@ -337,4 +2115,77 @@ impl From<DepTree> for Graph {
             stmts: statements,
         }
     }
+
+    /// Dashed red nodes of the failures that aren't skipped or ambiguous, with the edges from their kept parents
+    fn failure_statements(
+        &self,
+        keep: impl Fn(&NodeID) -> bool,
+        label_max_len: Option<usize>,
+    ) -> Vec<graphviz_rust::dot_structures::Stmt> {
+        use graphviz_rust::dot_structures::{
+            Attribute, Edge, EdgeTy, Id, Node, NodeId, Stmt, Vertex,
+        };
+
+        let failure_style = || {
+            vec![
+                Attribute(
+                    Id::Plain("style".to_string()),
+                    Id::Plain("dashed".to_string()),
+                ),
+                Attribute(Id::Plain("color".to_string()), Id::Plain("red".to_string())),
+            ]
+        };
+
+        let mut statements = vec![];
+
+        for (index, failure) in self
+            .real_failures()
+            .filter(|failure| !failure.is_ambiguous())
+            .enumerate()
+        {
+            let parents = self
+                .failure_parents(failure)
+                .into_iter()
+                .filter(|parent_id| keep(parent_id))
+                .collect_vec();
+
+            if parents.is_empty() {
+                continue;
+            }
+
+            // Numbered apart from the nodes, the failures have no ids of their own
+            let failure_node_id = NodeId(Id::Plain(format!("failure_{}", index)), None);
+            let name = failure.path.to_string_lossy().to_string();
+
+            let mut attributes = failure_style();
+            attributes.push(Attribute(
+                Id::Plain("fontcolor".to_string()),
+                Id::Plain("red".to_string()),
+            ));
+            attributes.push(Attribute(
+                Id::Plain("label".to_string()),
+                escaped_id(&match label_max_len {
+                    Some(label_max_len) => ellipsize_middle(&name, label_max_len),
+                    None => name.clone(),
+                }),
+            ));
+            attributes.push(Attribute(
+                Id::Plain("tooltip".to_string()),
+                escaped_id(&format!("{}: {}", name, failure.kind)),
+            ));
+            statements.push(Stmt::Node(Node::new(failure_node_id.clone(), attributes)));
+
+            for parent_id in parents {
+                statements.push(Stmt::Edge(Edge {
+                    ty: EdgeTy::Pair(
+                        Vertex::N(NodeId(Id::Plain(format!("node_{}", parent_id)), None)),
+                        Vertex::N(failure_node_id.clone()),
+                    ),
+                    attributes: failure_style(),
+                }));
+            }
+        }
+
+        statements
+    }
 }
@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{dependency_tree::DepTree, util::normalize_path};
+
+/// The dependency tree keyed by the asset paths, as the node ids aren't stable across runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeSnapshot {
+    pub root: PathBuf,
+    pub nodes: BTreeSet<PathBuf>,
+    /// (dependent, dependency) pairs
+    pub edges: BTreeSet<(PathBuf, PathBuf)>,
+    /// (dependent, unresolved dependency) pairs, only exported on request and not compared
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub failure_edges: BTreeSet<(PathBuf, PathBuf)>,
+}
+
+impl From<&DepTree> for TreeSnapshot {
+    fn from(value: &DepTree) -> Self {
+        let path = |id| value.nodes.get(&id).unwrap().path.clone();
+
+        Self {
+            root: value.get_root_node().path.clone(),
+            nodes: value.nodes.values().map(|node| node.path.clone()).collect(),
+            edges: value
+                .edges()
+                .map(|(node_id, con_node_id)| (path(node_id), path(con_node_id)))
+                .collect(),
+            failure_edges: BTreeSet::new(),
+        }
+    }
+}
+
+impl TreeSnapshot {
+    /// Adds the edges to the dependencies that failed to resolve, from the nodes that referenced them
+    pub fn with_failure_edges(mut self, dep_tree: &DepTree) -> Self {
+        self.failure_edges = dep_tree
+            .real_failures()
+            .filter(|failure| !failure.is_ambiguous())
+            .flat_map(|failure| {
+                dep_tree
+                    .failure_parents(failure)
+                    .into_iter()
+                    .filter_map(|parent_id| dep_tree.get_node(parent_id))
+                    .map(|parent_node| (parent_node.path.clone(), failure.path.clone()))
+            })
+            .collect();
+
+        self
+    }
+
+    /// Writes the paths the way the other exports do, see [`normalize_path`]
+    pub fn with_normalized_paths(self, roots: &[PathBuf]) -> Self {
+        let normalize = |path: PathBuf| PathBuf::from(normalize_path(path, roots));
+        let normalize_edge = |(from, to)| (normalize(from), normalize(to));
+
+        Self {
+            root: normalize(self.root),
+            nodes: self.nodes.into_iter().map(normalize).collect(),
+            edges: self.edges.into_iter().map(normalize_edge).collect(),
+            failure_edges: self.failure_edges.into_iter().map(normalize_edge).collect(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> color_eyre::Result<Self> {
+        let file = std::fs::File::open(path)?;
+
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> color_eyre::Result<()> {
+        let file = std::fs::File::create(path)?;
+
+        Ok(serde_json::to_writer_pretty(file, self)?)
+    }
+
+    /// Changes needed to get from this tree to the `other` one
+    pub fn diff(&self, other: &TreeSnapshot) -> TreeDiff {
+        TreeDiff {
+            added_nodes: other.nodes.difference(&self.nodes).cloned().collect(),
+            removed_nodes: self.nodes.difference(&other.nodes).cloned().collect(),
+            added_edges: other.edges.difference(&self.edges).cloned().collect(),
+            removed_edges: self.edges.difference(&other.edges).cloned().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TreeDiff {
+    pub added_nodes: Vec<PathBuf>,
+    pub removed_nodes: Vec<PathBuf>,
+    pub added_edges: Vec<(PathBuf, PathBuf)>,
+    pub removed_edges: Vec<(PathBuf, PathBuf)>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+impl Display for TreeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "===== Nodes =====")?;
+
+        let nodes = self
+            .added_nodes
+            .iter()
+            .map(|path| ('+', path))
+            .chain(self.removed_nodes.iter().map(|path| ('-', path)))
+            .sorted_by(|(_, path1), (_, path2)| path1.cmp(path2));
+
+        for (sign, path) in nodes {
+            writeln!(f, "{} {:?}", sign, path)?;
+        }
+
+        writeln!(f, "===== Edges =====")?;
+
+        let edges = self
+            .added_edges
+            .iter()
+            .map(|edge| ('+', edge))
+            .chain(self.removed_edges.iter().map(|edge| ('-', edge)))
+            .sorted_by(|(_, edge1), (_, edge2)| edge1.cmp(edge2));
+
+        for (sign, (from, to)) in edges {
+            writeln!(f, "{} {:?} -> {:?}", sign, from, to)?;
+        }
+
+        write!(
+            f,
+            "Nodes: +{} -{}, Edges: +{} -{}",
+            self.added_nodes.len(),
+            self.removed_nodes.len(),
+            self.added_edges.len(),
+            self.removed_edges.len()
+        )
+    }
+}
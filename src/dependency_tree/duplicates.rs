@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+use crate::asset::find_package_paths;
+
+/// Finds the asset names used by several packages in the directory, with their paths.
+/// The names are compared case insensitively like the engine does, the most duplicated ones come first
+pub fn find_duplicate_names(content_dir: impl AsRef<Path>) -> Vec<(String, Vec<PathBuf>)> {
+    find_package_paths(content_dir)
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+
+            Some((name.to_lowercase(), (name, path)))
+        })
+        .into_group_map()
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|paths| {
+            let name = paths[0].0.clone();
+
+            (
+                name,
+                paths
+                    .into_iter()
+                    .map(|(_, path)| path)
+                    .sorted()
+                    .collect_vec(),
+            )
+        })
+        .sorted_by(|(name1, paths1), (name2, paths2)| {
+            paths2
+                .len()
+                .cmp(&paths1.len())
+                .then_with(|| name1.to_lowercase().cmp(&name2.to_lowercase()))
+        })
+        .collect()
+}
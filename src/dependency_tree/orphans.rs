@@ -0,0 +1,53 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use indicatif::ProgressBar;
+
+use crate::asset::{find_package_paths, Asset, AssetDirs};
+
+/// Finds the assets in the content directory that no other asset depends on.
+/// The levels are never orphans, as they're loaded by the game and not referenced by the other assets
+pub fn find_orphans(asset_dirs: &AssetDirs, pb: Option<&ProgressBar>) -> Vec<PathBuf> {
+    let Some(content_dir) = &asset_dirs.content_dir else {
+        return vec![];
+    };
+
+    let package_paths = find_package_paths(content_dir);
+
+    if let Some(pb) = pb {
+        pb.set_length(package_paths.len() as u64);
+    }
+
+    // Everything referenced by any of the packages
+    let mut referenced = HashSet::new();
+
+    for path in &package_paths {
+        if let Some(pb) = pb {
+            pb.set_message(format!("Reading the dependencies of {:?}", path));
+            pb.inc(1);
+        }
+
+        match Asset::new(path) {
+            Ok(asset) => {
+                let (dep_paths, _) = asset.get_dependency_asset_paths(
+                    content_dir,
+                    &asset_dirs.engine_content_dir,
+                    &asset_dirs.plugins_dirs,
+                    &asset_dirs.extra_content_roots,
+                    asset_dirs.plugin_scan_depth,
+                    true,
+                    &[],
+                    asset_dirs.include_soft,
+                );
+
+                referenced.extend(dep_paths.into_iter().map(|(dep_path, _)| dep_path));
+            }
+            Err(err) => log::warn!("Skipping {:?}: {}", path, err),
+        }
+    }
+
+    package_paths
+        .into_iter()
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) != Some("umap"))
+        .filter(|path| !referenced.contains(path))
+        .collect()
+}
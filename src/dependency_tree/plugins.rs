@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use color_eyre::eyre;
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::{
+    asset::{AssetDirs, AssetOrigin},
+    dependency_tree::{DepTree, NodeID},
+    util::find_descriptor,
+};
+
+/// Part of the `.uproject` file listing the plugins enabled or disabled explicitly
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProjectDescriptor {
+    #[serde(default)]
+    plugins: Vec<ProjectPluginEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProjectPluginEntry {
+    name: String,
+    #[serde(default)]
+    enabled: bool,
+}
+
+/// Part of the `.uplugin` file deciding if the plugins not listed in the project are enabled
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PluginDescriptor {
+    enabled_by_default: Option<bool>,
+}
+
+/// Plugin whose content the tree depends on, but which isn't enabled in the project
+#[derive(Debug, Clone)]
+pub struct DisabledPlugin {
+    pub name: String,
+    pub dir: PathBuf,
+    pub origin: AssetOrigin,
+    /// Why the plugin isn't enabled, e.g. it's disabled in the `.uproject` explicitly
+    pub reason: String,
+    /// Nodes of the assets of the plugin in the tree
+    pub node_ids: Vec<NodeID>,
+}
+
+/// Cross-references the plugins of the assets in the tree with the plugins enabled in the `.uproject` file.
+/// The plugins that aren't listed there are enabled as the editor would, project ones unless their `.uplugin`
+/// says otherwise and engine ones only if their `.uplugin` enables them by default
+pub fn find_disabled_plugins(
+    dep_tree: &DepTree,
+    asset_dirs: &AssetDirs,
+) -> eyre::Result<Vec<DisabledPlugin>> {
+    let Some(project_dir) = &asset_dirs.project_dir else {
+        return Err(eyre::eyre!("The project directory is not set"));
+    };
+
+    let uproject_path = asset_dirs
+        .uproject_path()
+        .ok_or_else(|| eyre::eyre!("No .uproject file in {:?}", project_dir))?;
+    let project =
+        serde_json::from_str::<ProjectDescriptor>(&std::fs::read_to_string(&uproject_path)?)?;
+
+    // Plugin names are case insensitive, like the rest of the engine names
+    let listed = project
+        .plugins
+        .into_iter()
+        .map(|plugin| (plugin.name.to_lowercase(), plugin.enabled))
+        .collect::<BTreeMap<_, _>>();
+
+    let plugin_nodes = dep_tree
+        .nodes
+        .iter()
+        .filter(|(_, node)| {
+            matches!(
+                node.origin,
+                AssetOrigin::ProjectPlugin | AssetOrigin::EnginePlugin
+            )
+        })
+        .filter_map(|(&node_id, node)| {
+            AssetDirs::get_plugin_dir(&node.path).map(|plugin_dir| (plugin_dir, (node_id, node)))
+        })
+        .into_group_map();
+
+    let disabled_plugins = plugin_nodes
+        .into_iter()
+        .filter_map(|(plugin_dir, nodes)| {
+            let origin = nodes.first()?.1.origin;
+            let uplugin_path = find_descriptor(&plugin_dir, "uplugin");
+
+            let name = uplugin_path
+                .as_ref()
+                .and_then(|uplugin_path| uplugin_path.file_stem())
+                .or_else(|| plugin_dir.file_name())?
+                .to_string_lossy()
+                .to_string();
+
+            let reason = match listed.get(&name.to_lowercase()) {
+                Some(true) => return None,
+                Some(false) => "disabled in the .uproject".to_string(),
+                None => {
+                    let enabled_by_default = uplugin_path
+                        .and_then(|uplugin_path| std::fs::read_to_string(uplugin_path).ok())
+                        .and_then(|contents| {
+                            serde_json::from_str::<PluginDescriptor>(&contents).ok()
+                        })
+                        .and_then(|plugin| plugin.enabled_by_default)
+                        .unwrap_or(origin == AssetOrigin::ProjectPlugin);
+
+                    match enabled_by_default {
+                        true => return None,
+                        false => {
+                            "not listed in the .uproject and not enabled by default".to_string()
+                        }
+                    }
+                }
+            };
+
+            Some(DisabledPlugin {
+                name,
+                dir: plugin_dir,
+                origin,
+                reason,
+                node_ids: nodes
+                    .into_iter()
+                    .map(|(node_id, _)| node_id)
+                    .sorted()
+                    .collect(),
+            })
+        })
+        .sorted_by(|plugin1, plugin2| plugin1.name.cmp(&plugin2.name))
+        .collect();
+
+    Ok(disabled_plugins)
+}
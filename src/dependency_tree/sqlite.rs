@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+use itertools::Itertools;
+use rusqlite::{params, Connection};
+
+use crate::{asset::EdgeKind, dependency_tree::DepTree, util::normalize_path};
+
+const SCHEMA: &str = "
+CREATE TABLE nodes (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    origin TEXT NOT NULL,
+    class TEXT,
+    depth INTEGER NOT NULL,
+    size INTEGER NOT NULL
+);
+CREATE TABLE edges (
+    parent_id INTEGER NOT NULL REFERENCES nodes(id),
+    child_id INTEGER NOT NULL REFERENCES nodes(id),
+    kind TEXT NOT NULL
+);
+CREATE TABLE failures (
+    parent_id INTEGER REFERENCES nodes(id),
+    path TEXT NOT NULL,
+    reason TEXT NOT NULL
+);
+CREATE INDEX edges_child_id ON edges(child_id);
+";
+
+/// Writes the nodes, edges and failures of the tree to a new SQLite database for ad hoc queries,
+/// replacing the file if it exists. The failures without known parents get a `NULL` parent.
+/// The paths are normalized with the roots, see [`normalize_path`]
+pub fn export_sqlite(
+    dep_tree: &DepTree,
+    path: impl AsRef<Path>,
+    roots: &[PathBuf],
+) -> eyre::Result<()> {
+    if path.as_ref().exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    let mut connection = Connection::open(&path)?;
+    let transaction = connection.transaction()?;
+
+    transaction.execute_batch(SCHEMA)?;
+
+    {
+        let mut insert_node = transaction.prepare(
+            "INSERT INTO nodes (id, path, origin, class, depth, size) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for (&node_id, node) in dep_tree.nodes.iter().sorted_by_key(|(id, _)| **id) {
+            insert_node.execute(params![
+                node_id,
+                normalize_path(&node.path, roots),
+                node.origin.to_string(),
+                node.primary_class(),
+                dep_tree.get_recurse_depth(node_id).unwrap_or_default(),
+                node.file_size(),
+            ])?;
+        }
+
+        let mut insert_edge = transaction
+            .prepare("INSERT INTO edges (parent_id, child_id, kind) VALUES (?1, ?2, ?3)")?;
+        for (parent_id, child_id) in dep_tree.edges().sorted() {
+            insert_edge.execute(params![
+                parent_id,
+                child_id,
+                match dep_tree.edge_kind(parent_id, child_id) {
+                    EdgeKind::Hard => "hard",
+                    EdgeKind::Soft => "soft",
+                },
+            ])?;
+        }
+
+        let mut insert_failure = transaction
+            .prepare("INSERT INTO failures (parent_id, path, reason) VALUES (?1, ?2, ?3)")?;
+        for failure in &dep_tree.failures {
+            let parents = dep_tree.failure_parents(failure);
+            let parents = match parents.is_empty() {
+                true => vec![None],
+                false => parents.into_iter().map(Some).collect_vec(),
+            };
+
+            for parent_id in parents {
+                insert_failure.execute(params![
+                    parent_id,
+                    normalize_path(&failure.path, roots),
+                    failure.kind.to_string(),
+                ])?;
+            }
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+//! Resolving the dependencies of the Unreal Engine's .uasset files, the core of the tool without the GUI
+
+pub mod asset;
+pub mod dependency_tree;
+pub mod util;
+
+pub use asset::{Asset, AssetDirs, AssetError};
+pub use dependency_tree::DepTree;
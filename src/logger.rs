@@ -0,0 +1,76 @@
+use std::{fs::File, io::Write, path::Path, sync::Mutex};
+
+use color_eyre::eyre;
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::timestamp;
+
+/// Logs to stderr as usual, and also to the file if it's set
+struct TeeLogger {
+    stderr: Box<dyn Log>,
+    /// Not buffered, so the last records before a crash aren't lost
+    file: Option<Mutex<File>>,
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        self.stderr.log(record);
+
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                // Nowhere to report the failures to, the stderr output still has the record
+                let _ = writeln!(
+                    file,
+                    "[{} {:<5} {}] {}",
+                    timestamp(),
+                    record.level(),
+                    record.target(),
+                    record.args()
+                );
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Sets up the logger with the level, appending the records to the log file too if it's set
+pub fn init(level: LevelFilter, log_file: Option<&Path>) -> eyre::Result<()> {
+    let file = log_file
+        .map(|log_file| {
+            File::options()
+                .create(true)
+                .append(true)
+                .open(log_file)
+                .map(Mutex::new)
+        })
+        .transpose()?;
+
+    let stderr = pretty_env_logger::formatted_builder()
+        .filter_level(level)
+        .build();
+
+    log::set_boxed_logger(Box::new(TeeLogger {
+        stderr: Box::new(stderr),
+        file,
+    }))?;
+    log::set_max_level(level);
+
+    Ok(())
+}
@@ -1,26 +1,47 @@
-#![feature(box_syntax)]
-
+#[cfg(feature = "gui")]
 mod app;
-mod asset;
-mod dependency_tree;
-mod util;
+mod logger;
+#[cfg(feature = "gui")]
+mod settings;
 
-use std::path::PathBuf;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use color_eyre::eyre;
 use graphviz_rust::{
     cmd::{CommandArg, Format},
-    dot_structures::Graph,
     exec,
     printer::PrinterContext,
 };
+#[cfg(feature = "gui")]
 use iced::{
     window::{self, Position},
     Application, Settings,
 };
+use itertools::Itertools;
+// The core lives in the library, imported here so the GUI modules can keep using it by the crate paths
+use uasset_helper::{asset, dependency_tree, util};
 
-use crate::{app::GuiApp, asset::AssetDirs, dependency_tree::DepTree};
+#[cfg(feature = "gui")]
+use crate::{app::GuiApp, settings::UserSettings};
+use crate::{
+    asset::{
+        is_package_path, Asset, AssetDirs, AssetErrorKind, AssetOrigin, GitRepo,
+        DEFAULT_PLUGIN_SCAN_DEPTH,
+    },
+    dependency_tree::{
+        export_sqlite, find_disabled_plugins, find_duplicate_names, find_orphans, fix_file_name,
+        ColorMode, DepTree, NodeID, NodeSortKey, TraversalOrder, TreeSnapshot,
+        UNLIMITED_RECURSE_DEPTH,
+    },
+    util::{default_jobs, format_size, normalize_path},
+};
 
 #[derive(Parser)]
 #[command(
@@ -30,18 +51,109 @@ use crate::{app::GuiApp, asset::AssetDirs, dependency_tree::DepTree};
     long_about = None
 )]
 struct Args {
+    /// Run the GUI instead, the binary has to be built with the "gui" feature (on by default)
     #[arg(long, default_value = "false")]
     gui: bool,
 
+    /// Path to the asset, or a glob pattern matching several assets (e.g. "Content/**/*.uasset")
     #[arg(short, long)]
-    file: Option<PathBuf>,
+    file: Option<String>,
 
     #[arg(short, long)]
     engine: Option<PathBuf>,
 
+    /// Shortcut for `--log-level debug`
     #[arg(short, long)]
     verbose: bool,
 
+    /// Most detailed log records to show (off, error, warn, info, debug, trace), overrides `--verbose`
+    #[arg(long)]
+    log_level: Option<log::LevelFilter>,
+
+    /// Also append the log records to this file
+    #[arg(long)]
+    log_file: Option<PathBuf>,
+
+    /// Proceed without confirmation when the file pattern matches a lot of assets
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Order the dependencies are resolved in, with bfs the depth limit cuts off the farthest assets
+    #[arg(long, value_enum, default_value = "dfs")]
+    traversal: TraversalOrder,
+
+    /// Retry resolving the failed dependencies once the tree is built
+    #[arg(long)]
+    retry_failures: bool,
+
+    /// Don't resolve the engine content at all, for the project-only audits
+    #[arg(long)]
+    skip_engine: bool,
+
+    /// Don't resolve the dependencies with the object paths matching this glob (e.g. "/Engine/*DefaultMaterial*"),
+    /// can be repeated
+    #[arg(long = "ignore")]
+    ignore_patterns: Vec<glob::Pattern>,
+
+    /// How deep the plugins directories are searched for the plugins the assets are referenced from
+    #[arg(long, default_value_t = DEFAULT_PLUGIN_SCAN_DEPTH)]
+    plugin_scan_depth: usize,
+
+    /// Content directory of a custom mount point, e.g. "SharedContent=/Studio/SharedContent"
+    /// for the imports starting with "/SharedContent/", can be repeated
+    #[arg(long = "mount", value_parser = parse_mount)]
+    mounts: Vec<(String, PathBuf)>,
+
+    /// Only resolve the dependencies of these origins (e.g. "project,project-plugin"), all of them by default.
+    /// Unlike the display filters this makes the tree itself smaller
+    #[arg(long, value_enum, value_delimiter = ',')]
+    origins: Vec<AssetOrigin>,
+
+    /// Only follow the first this many dependencies of each asset, for quick sketches of the huge trees.
+    /// The nodes that had some of their dependencies left out are reported
+    #[arg(long)]
+    max_children: Option<usize>,
+
+    /// Read the locked assets (e.g. open in the editor) once more after this many milliseconds,
+    /// instead of failing right away
+    #[arg(long)]
+    locked_retry_ms: Option<u64>,
+
+    /// Also follow the soft package references (e.g. `TSoftObjectPtr` properties) as soft edges,
+    /// only the imports are followed otherwise
+    #[arg(long)]
+    include_soft: bool,
+
+    /// Most assets read at once while building the trees, lower it if the files run out.
+    /// Defaults to the available parallelism
+    #[arg(long)]
+    jobs: Option<usize>,
+
+    /// Content directory of the project, instead of guessing it from the asset path
+    #[arg(long)]
+    content_dir: Option<PathBuf>,
+
+    /// Content directory of the engine, instead of deriving it from the engine path
+    #[arg(long)]
+    engine_content_dir: Option<PathBuf>,
+
+    /// Make the paths in the exports relative to the project and engine directories,
+    /// they use forward slashes on every platform either way
+    #[arg(long)]
+    normalize_paths: bool,
+
+    /// Directory for the output files, created if missing
+    #[arg(short, long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// Only keep the assets of this class (e.g. Texture2D) and the ones leading to them, can be repeated
+    #[arg(long = "class")]
+    classes: Vec<String>,
+
+    /// What in the built trees makes the run exit with an error, once all the outputs are written
+    #[arg(long, value_enum, default_value = "none")]
+    fail_on: FailOn,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -49,75 +161,1414 @@ struct Args {
 #[derive(Subcommand)]
 enum Command {
     DependencyTree {
-        #[arg(short = 'r', long, default_value = "64")]
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+
+        /// Only export the subtree of the asset with this path
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Maximum depth of the exported subtree (defaults to the max recurse depth)
+        #[arg(long, requires = "root")]
+        subtree_depth: Option<u32>,
+
+        /// Build the combined tree of the assets modified in the git repos instead
+        /// (the asset is only used to locate the project)
+        #[arg(long, conflicts_with = "root")]
+        changed_only: bool,
+
+        /// Add the classes of the assets (e.g. Texture2D) to the node labels
+        #[arg(long)]
+        label_with_class: bool,
+
+        /// Show each engine plugin as a single node in the graph
+        #[arg(long)]
+        collapse_engine_plugins: bool,
+
+        /// What the nodes of the graph are colored by
+        #[arg(long, value_enum, default_value = "origin")]
+        color_by: ColorMode,
+
+        /// Title rendered at the top of the graph, also added to the output file names
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Cut the longer node labels in the middle, the full names are still shown in the svg tooltips
+        #[arg(long)]
+        label_max_len: Option<usize>,
+
+        /// Add the dependencies that failed to resolve to the graph and the json as dashed red nodes,
+        /// to see where the holes in the tree are
+        #[arg(long)]
+        failure_nodes: bool,
+
+        /// Write each group of the connected assets to its own files ("<name>_component_1.svg", ...),
+        /// e.g. for the combined trees of the unrelated assets
+        #[arg(long)]
+        split_components: bool,
+
+        /// Also stream the nodes, edges and failures as newline-delimited JSON to this file ("-" for stdout)
+        #[arg(long)]
+        ndjson: Option<PathBuf>,
+
+        /// Keep running and rebuild the tree whenever the root asset changes
+        #[arg(short, long, conflicts_with = "changed_only")]
+        watch: bool,
+
+        /// Also rebuild when any of the assets in the tree changes
+        #[arg(long, requires = "watch")]
+        watch_dependencies: bool,
+    },
+    /// Print the sorted list of all the nodes in the dependency tree
+    ListNodes {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+
+        #[arg(short, long, value_enum, default_value = "path")]
+        sort_by: NodeSortKey,
+
+        /// Also print the dependencies that failed to resolve
+        #[arg(long)]
+        list_failures: bool,
+
+        /// Only print the assets saved with an engine older than this version (e.g. "5.1"),
+        /// the ones that may need resaving after an upgrade
+        #[arg(long, value_parser = parse_engine_version)]
+        older_than: Option<(u16, u16)>,
+
+        /// Also print the combined size of the asset files in the tree
+        #[arg(long)]
+        size: bool,
+    },
+    /// Print the assets without further dependencies (textures, sounds, ...) with their sizes
+    ListLeaves {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+    },
+    /// Print the dependency tree indented like `tree` does
+    Tree {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+    },
+    /// Print whether each direct dependency of the asset resolves and to which file, without building the tree
+    Resolve,
+    /// Print the project, engine and plugin directories inferred from the asset and the git repos found in them,
+    /// e.g. to find out why all the dependencies fail to resolve
+    Info,
+    /// Only check that the hard references of the asset itself resolve, without building the whole tree.
+    /// Prints nothing if they do and fails listing the ones that don't otherwise, e.g. for the pre-commit hooks
+    Check,
+    /// Print the plugins the asset depends on that aren't enabled in the .uproject, as the packaged game won't have them
+    AuditPlugins {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+    },
+    /// Export the dependency tree as GraphML, for Gephi and other graph visualization tools
+    ExportGraphml {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+
+        /// Path of the output .graphml file
+        #[arg(short, long, default_value = "deptree.graphml")]
+        path: PathBuf,
+    },
+    /// Export the nodes, edges and failures of the dependency tree to a SQLite database, to query it with SQL
+    ExportSqlite {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+
+        /// Path of the output database, replaced if it exists
+        #[arg(short, long, default_value = "deptree.sqlite")]
+        path: PathBuf,
+    },
+    /// Write the paths of all the files in the dependency tree, one per line, e.g. for packaging
+    Manifest {
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
+        max_recurse_depth: u32,
+
+        /// Path of the output file
+        #[arg(short, long, default_value = "manifest.txt")]
+        path: PathBuf,
+
+        /// Write the paths inside the content directory relative to it
+        #[arg(long)]
+        relative: bool,
+    },
+    /// Print the nodes and edges added and removed between two JSON exports of the dependency tree
+    Diff { left: PathBuf, right: PathBuf },
+    /// Print the assets in the content directory that no other asset depends on
+    Orphans {
+        /// The Content directory of the project
+        content_dir: PathBuf,
+    },
+    /// Print the asset names used by several assets in the content directory, the cook warns about them
+    DuplicateNames {
+        /// The Content directory of the project
+        content_dir: PathBuf,
+    },
+    /// Build the dependency trees of the assets listed in a file (one path per line), for CI and other automation.
+    /// Fails if any of the trees couldn't be built or have unresolved dependencies
+    Batch {
+        /// File with the paths of the .uasset files, empty lines and lines starting with '#' are skipped
+        manifest: PathBuf,
+
+        /// Maximum length of the dependency chains, 0 only reads the asset itself, "none" removes the limit
+        #[arg(short = 'r', long, default_value = "64", value_parser = parse_recurse_depth)]
         max_recurse_depth: u32,
+
+        /// Build one combined tree of all the listed assets instead of a tree per asset
+        #[arg(long)]
+        combined: bool,
     },
 }
 
+/// Problems in the built trees the run fails on, for the CI checks
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum FailOn {
+    /// Always succeeds, the failures are only reported
+    None,
+    /// Any dependency that failed to resolve, except the skipped and ignored ones
+    AnyFailure,
+    /// Dependency chains leading back to an asset already on them
+    Cycles,
+    /// Engine dependencies missing from the engine content directory
+    MissingEngine,
+}
+
+impl FailOn {
+    /// Describes the problem the tree fails the check with, if any
+    fn check(&self, dependency_tree: &DepTree) -> Option<String> {
+        let root_path = &dependency_tree.get_root_node().path;
+
+        match self {
+            FailOn::None => None,
+            FailOn::AnyFailure => {
+                let failures = dependency_tree
+                    .failures
+                    .iter()
+                    .filter(|failure| !failure.is_skipped())
+                    .count();
+
+                (failures > 0).then(|| {
+                    format!(
+                        "{} dependencies of {:?} failed to resolve",
+                        failures, root_path
+                    )
+                })
+            }
+            FailOn::Cycles => dependency_tree
+                .has_cycles()
+                .then(|| format!("The dependency tree of {:?} has cycles", root_path)),
+            FailOn::MissingEngine => {
+                let missing = dependency_tree
+                    .failures
+                    .iter()
+                    .filter(|failure| {
+                        matches!(
+                            failure.kind,
+                            AssetErrorKind::MissingEngineDir
+                                | AssetErrorKind::MissingInEngineContent
+                        )
+                    })
+                    .count();
+
+                (missing > 0).then(|| {
+                    format!(
+                        "{} engine dependencies of {:?} are missing",
+                        missing, root_path
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Changes applied to every built tree before it's used
+struct PostProcess {
+    retry_failures: bool,
+    classes: Vec<String>,
+    fail_on: FailOn,
+    /// Problems the trees failed the `--fail-on` check with, the run fails once everything is written
+    violations: RefCell<Vec<String>>,
+}
+
+impl PostProcess {
+    fn apply(&self, mut dependency_tree: DepTree, asset_dirs: &AssetDirs) -> eyre::Result<DepTree> {
+        if self.retry_failures {
+            let fixed = dependency_tree.retry_failures(asset_dirs)?;
+
+            log::info!(
+                "Retrying the failures fixed {}, {} still failing",
+                fixed,
+                dependency_tree.retried_failures.len()
+            );
+        }
+
+        let dependency_tree = dependency_tree.retain_classes(&self.classes);
+
+        if let Some(violation) = self.fail_on.check(&dependency_tree) {
+            log::error!("{}", violation);
+
+            self.violations.borrow_mut().push(violation);
+        }
+
+        Ok(dependency_tree)
+    }
+}
+
+/// Options the directories of every asset are set up with
+struct DirsConfig {
+    engine_dir: Option<PathBuf>,
+    skip_engine: bool,
+    ignore_patterns: Vec<glob::Pattern>,
+    plugin_scan_depth: usize,
+    mounts: Vec<(String, PathBuf)>,
+    origins: Vec<AssetOrigin>,
+    max_children: Option<usize>,
+    locked_retry_delay: Option<Duration>,
+    include_soft: bool,
+    jobs: usize,
+    content_dir: Option<PathBuf>,
+    engine_content_dir: Option<PathBuf>,
+    /// Make the exported paths relative to the project and engine directories of the assets
+    normalize_paths: bool,
+}
+
+impl DirsConfig {
+    fn asset_dirs(&self, asset_file_path: Option<PathBuf>) -> AssetDirs {
+        AssetDirs {
+            skip_engine: self.skip_engine,
+            ignore_patterns: self.ignore_patterns.clone(),
+            plugin_scan_depth: self.plugin_scan_depth,
+            extra_content_roots: self.mounts.clone(),
+            origins: self.origins.clone(),
+            max_children: self.max_children,
+            locked_retry_delay: self.locked_retry_delay,
+            include_soft: self.include_soft,
+            jobs: self.jobs,
+            ..AssetDirs::new(asset_file_path, self.engine_dir.clone())
+                .with_dir_overrides(self.content_dir.clone(), self.engine_content_dir.clone())
+        }
+    }
+
+    /// Roots the exported paths of the asset are made relative to, none if they're kept absolute
+    fn export_roots(&self, asset_dirs: &AssetDirs) -> Vec<PathBuf> {
+        match self.normalize_paths {
+            true => asset_dirs.export_roots(),
+            false => vec![],
+        }
+    }
+}
+
+/// Amount of matched assets after which the user has to confirm the run with `--yes`
+const GLOB_CONFIRM_THRESHOLD: usize = 1000;
+
 fn main() -> eyre::Result<()> {
     let Args {
         gui,
-        file: uasset_file_path,
+        file: uasset_file_pattern,
         engine: engine_dir,
         verbose,
+        log_level,
+        log_file,
+        yes,
+        traversal,
+        retry_failures,
+        skip_engine,
+        ignore_patterns,
+        plugin_scan_depth,
+        mounts,
+        origins,
+        max_children,
+        locked_retry_ms,
+        include_soft,
+        jobs,
+        content_dir,
+        engine_content_dir,
+        normalize_paths,
+        out_dir,
+        classes,
+        fail_on,
         command,
     } = Args::parse();
 
-    std::env::set_var(
-        "RUST_LOG",
-        match verbose {
-            true => "debug",
-            false => "info",
-        },
-    );
+    logger::init(
+        log_level.unwrap_or(match verbose {
+            true => log::LevelFilter::Debug,
+            false => log::LevelFilter::Info,
+        }),
+        log_file.as_deref(),
+    )?;
+
+    if let Command::Diff { left, right } = &command {
+        let diff = TreeSnapshot::load(left)?.diff(&TreeSnapshot::load(right)?);
+
+        match diff.is_empty() {
+            true => log::info!("The dependency trees are the same"),
+            false => log::info!("{}", diff),
+        }
+
+        return Ok(());
+    }
+
+    let dirs_config = DirsConfig {
+        engine_dir,
+        skip_engine,
+        ignore_patterns,
+        plugin_scan_depth,
+        mounts,
+        origins,
+        max_children,
+        locked_retry_delay: locked_retry_ms.map(Duration::from_millis),
+        include_soft,
+        jobs: jobs.unwrap_or_else(default_jobs),
+        content_dir,
+        engine_content_dir,
+        normalize_paths,
+    };
+
+    let post_process = PostProcess {
+        retry_failures,
+        classes,
+        fail_on,
+        violations: RefCell::new(vec![]),
+    };
+
+    if let Command::Orphans { content_dir } = &command {
+        return print_orphans(content_dir, &dirs_config);
+    }
+
+    if let Command::DuplicateNames { content_dir } = &command {
+        print_duplicate_names(content_dir);
+
+        return Ok(());
+    }
 
-    pretty_env_logger::init();
+    if let Command::Batch {
+        manifest,
+        max_recurse_depth,
+        combined,
+    } = &command
+    {
+        return run_batch(
+            manifest,
+            *max_recurse_depth,
+            &out_dir,
+            *combined,
+            traversal,
+            &dirs_config,
+            &post_process,
+        );
+    }
 
-    if !gui && uasset_file_path.is_none() {
+    if !gui && uasset_file_pattern.is_none() {
         return Err(eyre::eyre!(
             "Please specify the asset path if not using the gui"
         ));
     }
 
-    let asset_dirs = AssetDirs::new(uasset_file_path, engine_dir);
+    let uasset_file_paths = match &uasset_file_pattern {
+        Some(pattern) => expand_asset_paths(pattern, yes)?,
+        None => vec![],
+    };
+
+    if matches!(command, Command::DependencyTree { watch: true, .. }) && uasset_file_paths.len() > 1
+    {
+        return Err(eyre::eyre!("Only a single asset can be watched"));
+    }
+
+    if let Err(warning) = dirs_config.asset_dirs(None).validate_engine_dir() {
+        log::warn!("{}", warning);
+    }
+
+    for uasset_file_path in &uasset_file_paths {
+        if let Err(error) = dirs_config
+            .asset_dirs(Some(uasset_file_path.clone()))
+            .validate_asset_file()
+        {
+            match gui {
+                true => log::warn!("{}", error),
+                false => {
+                    return Err(eyre::eyre!(
+                        "{}, set --content-dir if the project has a custom layout",
+                        error
+                    ))
+                }
+            }
+        }
+    }
+
+    if !gui && matches!(command, Command::Check) {
+        return run_check(&uasset_file_paths, &dirs_config);
+    }
 
     if gui {
+        let asset_dirs = dirs_config.asset_dirs(uasset_file_paths.first().cloned());
+
         run_app(asset_dirs)?;
     } else {
-        match command {
-            Command::DependencyTree { max_recurse_depth } => {
-                let dependency_tree = DepTree::build_with_pb(&asset_dirs, max_recurse_depth)?;
+        std::fs::create_dir_all(&out_dir)?;
 
-                // graph gen
-                {
-                    use std::io::Write;
+        'assets: for uasset_file_path in uasset_file_paths {
+            let asset_dirs = dirs_config.asset_dirs(Some(uasset_file_path));
 
-                    use graphviz_rust::printer::DotPrinter;
+            match &command {
+                Command::DependencyTree {
+                    max_recurse_depth,
+                    root,
+                    subtree_depth,
+                    changed_only,
+                    label_with_class,
+                    collapse_engine_plugins,
+                    color_by,
+                    title,
+                    label_max_len,
+                    failure_nodes,
+                    split_components,
+                    ndjson,
+                    watch,
+                    watch_dependencies,
+                } => {
+                    loop {
+                        let dependency_tree = match changed_only {
+                            true => {
+                                let modified_assets = asset_dirs.modified_assets();
 
-                    let graph: Graph = dependency_tree.into();
+                                if modified_assets.is_empty() {
+                                    return Err(eyre::eyre!(
+                                        "No modified assets found in the git repos"
+                                    ));
+                                }
 
-                    let graph_dot = graph.print(&mut PrinterContext::default());
-                    let mut file = std::fs::File::create("deptree.dot")?;
-                    file.write_all(graph_dot.as_bytes())?;
+                                log::info!("Found {} modified assets", modified_assets.len());
 
-                    let graph_svg = exec(
-                        graph,
-                        &mut PrinterContext::default(),
-                        vec![CommandArg::Format(Format::Svg)],
-                    )
-                    .unwrap();
+                                DepTree::build_combined_with_pb(
+                                    &asset_dirs,
+                                    &modified_assets,
+                                    *max_recurse_depth,
+                                    traversal,
+                                )?
+                            }
+                            false => {
+                                DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?
+                            }
+                        };
+                        let dependency_tree = post_process.apply(dependency_tree, &asset_dirs)?;
+
+                        let dependency_tree = match root {
+                            Some(root) => {
+                                let root_id = dependency_tree
+                                    .find_node_id_by_path(root)
+                                    .ok_or_else(|| {
+                                        eyre::eyre!(
+                                            "{:?} is not a part of the dependency tree",
+                                            root
+                                        )
+                                    })?;
+
+                                dependency_tree
+                                    .subtree(root_id, subtree_depth.unwrap_or(*max_recurse_depth))
+                                    .unwrap()
+                            }
+                            None => dependency_tree,
+                        };
+
+                        let output_suffix = match title {
+                            Some(title) => {
+                                format!("{}_deptree", fix_file_name(Some(OsStr::new(title))))
+                            }
+                            None => "deptree".to_string(),
+                        };
+                        let output_name = match changed_only {
+                            true => format!("changed_{}", output_suffix),
+                            false => output_name(&dependency_tree, &output_suffix),
+                        };
+
+                        let watched_paths = match *watch {
+                            true => watched_paths(&dependency_tree, *watch_dependencies),
+                            false => vec![],
+                        };
+
+                        if let Some(ndjson_path) = ndjson {
+                            write_ndjson(
+                                &dependency_tree,
+                                ndjson_path,
+                                &dirs_config.export_roots(&asset_dirs),
+                            )?;
+                        }
+
+                        let collapsed_tree = collapse_engine_plugins
+                            .then(|| dependency_tree.collapse_engine_plugins());
+                        let graph_tree = collapsed_tree.as_ref().unwrap_or(&dependency_tree);
+
+                        let write = |graph_tree: &DepTree, output_name: &str| {
+                            write_graph(
+                                graph_tree,
+                                &out_dir.join(output_name),
+                                *label_with_class,
+                                &graph_tree.graphviz_colors(*color_by, |asset| {
+                                    asset_dirs.is_modified(asset)
+                                }),
+                                title.as_deref(),
+                                *label_max_len,
+                                *failure_nodes,
+                                &dirs_config.export_roots(&asset_dirs),
+                            )
+                        };
+
+                        let written_paths = match split_components {
+                            true => {
+                                let mut written_paths = vec![];
+
+                                for (index, component) in graph_tree.components().iter().enumerate()
+                                {
+                                    written_paths.extend(write(
+                                        &graph_tree.component(component),
+                                        &format!("{}_component_{}", output_name, index + 1),
+                                    )?);
+                                }
+
+                                written_paths
+                            }
+                            false => write(graph_tree, &output_name)?,
+                        };
+
+                        // Keeps the streamed NDJSON on stdout parsable
+                        if ndjson.as_deref() != Some(Path::new("-")) {
+                            print_summary(
+                                &dependency_tree,
+                                &written_paths,
+                                asset_dirs.jobs,
+                                verbose,
+                            );
+                        }
+
+                        // The modified assets are the same for all the matched files of the project
+                        if *changed_only {
+                            break 'assets;
+                        }
+
+                        if !*watch {
+                            break;
+                        }
+
+                        wait_for_changes(&watched_paths)?;
+
+                        log::info!("[{}] Rebuilding the dependency tree...", timestamp());
+                    }
+                }
+                Command::ListNodes {
+                    max_recurse_depth,
+                    sort_by,
+                    list_failures,
+                    older_than,
+                    size,
+                } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    log::info!("{}", dependency_tree.node_paths_list(*sort_by, *older_than));
+
+                    if *list_failures {
+                        print_fails(&dependency_tree);
+                    }
+
+                    if *size {
+                        let root_node_id = dependency_tree.root_node_id;
+
+                        log::info!(
+                            "Size of {:?}: {}, with dependencies: {}",
+                            dependency_tree.get_root_node().path,
+                            format_size(dependency_tree.get_root_node().file_size()),
+                            format_size(dependency_tree.subtree_size(root_node_id))
+                        );
+                    }
+                }
+                Command::ListLeaves { max_recurse_depth } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    log::info!("{}", dependency_tree.leaves_list());
+                }
+                Command::Tree { max_recurse_depth } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    log::info!("\n{}", dependency_tree.ascii_tree());
+                }
+                Command::Resolve => print_resolve_report(&asset_dirs)?,
+                Command::Info => print_dirs_info(&asset_dirs),
+                Command::AuditPlugins { max_recurse_depth } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    print_disabled_plugins(&dependency_tree, &asset_dirs)?;
+                }
+                Command::ExportGraphml {
+                    max_recurse_depth,
+                    path,
+                } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    let path = out_dir.join(path.with_file_name(output_name(
+                        &dependency_tree,
+                        &path.file_name().unwrap().to_string_lossy(),
+                    )));
+
+                    std::fs::write(&path, dependency_tree.to_graphml())?;
+
+                    log::info!("Saved the GraphML export to {:?}", path);
+                }
+                Command::ExportSqlite {
+                    max_recurse_depth,
+                    path,
+                } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    let path = out_dir.join(path.with_file_name(output_name(
+                        &dependency_tree,
+                        &path.file_name().unwrap().to_string_lossy(),
+                    )));
+
+                    export_sqlite(
+                        &dependency_tree,
+                        &path,
+                        &dirs_config.export_roots(&asset_dirs),
+                    )?;
+
+                    log::info!("Saved the SQLite export to {:?}", path);
+                }
+                Command::Manifest {
+                    max_recurse_depth,
+                    path,
+                    relative,
+                } => {
+                    let dependency_tree = post_process.apply(
+                        DepTree::build_with_pb(&asset_dirs, *max_recurse_depth, traversal)?,
+                        &asset_dirs,
+                    )?;
+
+                    let content_dir = asset_dirs
+                        .content_dir
+                        .as_ref()
+                        .and_then(|content_dir| std::fs::canonicalize(content_dir).ok());
+                    let paths = dependency_tree.all_paths();
+
+                    let roots = match (*relative, content_dir) {
+                        (true, Some(content_dir)) => vec![content_dir],
+                        _ => dirs_config.export_roots(&asset_dirs),
+                    };
+                    let manifest = paths
+                        .iter()
+                        .map(|file_path| normalize_path(file_path, &roots))
+                        .join("\n");
+
+                    let path = out_dir.join(path.with_file_name(output_name(
+                        &dependency_tree,
+                        &path.file_name().unwrap().to_string_lossy(),
+                    )));
+
+                    std::fs::write(&path, manifest + "\n")?;
+
+                    log::info!("Saved the manifest of {} files to {:?}", paths.len(), path);
+                }
+                Command::Diff { .. }
+                | Command::Batch { .. }
+                | Command::Orphans { .. }
+                | Command::DuplicateNames { .. }
+                | Command::Check => {
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    let violations = post_process.violations.into_inner();
+
+    if !violations.is_empty() {
+        return Err(eyre::eyre!(
+            "{} of the dependency trees failed the --fail-on check",
+            violations.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Delay after a change before rebuilding, editors often write the files several times when saving
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Paths of the tree watched for changes, the root and optionally all the other assets
+fn watched_paths(dependency_tree: &DepTree, watch_dependencies: bool) -> Vec<PathBuf> {
+    match watch_dependencies {
+        true => dependency_tree
+            .nodes
+            .values()
+            .map(|node| node.path.clone())
+            .sorted()
+            .collect(),
+        false => vec![dependency_tree.get_root_node().path.clone()],
+    }
+}
+
+/// Blocks until one of the files changes, waiting for the writes to settle down.
+/// Their directories are watched instead of the files, as saving often replaces the files
+fn wait_for_changes(paths: &[PathBuf]) -> eyre::Result<()> {
+    use notify::{RecursiveMode, Watcher};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
 
-                    let mut file = std::fs::File::create("deptree.svg")?;
-                    file.write_all(graph_svg.as_bytes())?;
+    for dir in paths.iter().filter_map(|path| path.parent()).unique() {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+
+    let is_relevant = |event: notify::Result<notify::Event>| match event {
+        Ok(event) => !event.kind.is_access() && event.paths.iter().any(|path| paths.contains(path)),
+        Err(err) => {
+            log::warn!("Failed to watch the assets: {}", err);
+
+            false
+        }
+    };
+
+    loop {
+        if is_relevant(rx.recv()?) {
+            break;
+        }
+    }
+
+    // Drain the events of the same save
+    while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+
+    Ok(())
+}
+
+/// Current UTC time of the day, e.g. `12:34:56`
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+        % (24 * 60 * 60);
+
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Prefixes the output file name with the root asset name, so the outputs of different assets don't overwrite each other
+fn output_name(dependency_tree: &DepTree, name: &str) -> String {
+    format!(
+        "{}_{}",
+        fix_file_name(dependency_tree.get_root_node().path.file_name()),
+        name
+    )
+}
+
+/// Parses the maximum recurse depth, "none" (or "unlimited") meaning there's no limit
+fn parse_recurse_depth(depth: &str) -> Result<u32, String> {
+    match depth {
+        "none" | "unlimited" => Ok(UNLIMITED_RECURSE_DEPTH),
+        _ => depth
+            .parse()
+            .map_err(|err| format!("Invalid recurse depth: {}", err)),
+    }
+}
+
+/// Parses the `MAJOR.MINOR` engine version
+fn parse_engine_version(version: &str) -> Result<(u16, u16), String> {
+    let (major, minor) = version
+        .split_once('.')
+        .ok_or_else(|| format!("\"{}\" is not in the MAJOR.MINOR format", version))?;
+
+    Ok((
+        major
+            .parse()
+            .map_err(|err| format!("Invalid major version: {}", err))?,
+        minor
+            .parse()
+            .map_err(|err| format!("Invalid minor version: {}", err))?,
+    ))
+}
+
+/// Parses a mount point in the `NAME=PATH` format, the name can be written with the slashes too (`/NAME/`)
+fn parse_mount(mount: &str) -> Result<(String, PathBuf), String> {
+    let (name, path) = mount
+        .split_once('=')
+        .ok_or_else(|| format!("\"{}\" is not in the NAME=PATH format", mount))?;
+    let name = name.trim_matches('/');
+
+    match name.is_empty() || name.contains('/') {
+        true => Err(format!("Invalid mount name \"{}\"", name)),
+        false => Ok((name.to_string(), PathBuf::from(path))),
+    }
+}
+
+/// Expands the `--file` argument into the list of the asset paths it matches
+fn expand_asset_paths(pattern: &str, yes: bool) -> eyre::Result<Vec<PathBuf>> {
+    // Existing files are taken as they are, their names can have the glob characters (e.g. `Maps[Old]`)
+    if Path::new(pattern).is_file() {
+        return Ok(vec![PathBuf::from(pattern)]);
+    }
+
+    let paths = glob::glob(pattern)?
+        .filter_map(|entry| match entry {
+            Ok(path) => Some(path),
+            Err(err) => {
+                log::warn!("Skipping unreadable path: {}", err);
+
+                None
+            }
+        })
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+
+    if paths.is_empty() {
+        return Err(eyre::eyre!("No assets match \"{}\"", pattern));
+    }
+
+    if paths.len() > GLOB_CONFIRM_THRESHOLD && !yes {
+        log::warn!(
+            "\"{}\" matches {} assets, building all of their dependency trees may take a while",
+            pattern,
+            paths.len()
+        );
+
+        return Err(eyre::eyre!(
+            "Too many assets matched ({} > {}), pass --yes to proceed anyway",
+            paths.len(),
+            GLOB_CONFIRM_THRESHOLD
+        ));
+    }
+
+    log::debug!("\"{}\" matches {} assets", pattern, paths.len());
+
+    Ok(paths)
+}
+
+/// Writes the dot, svg and json (for diffing) outputs of the tree, `output_base` is their path without the extension.
+/// Returns the paths of the written files
+#[allow(clippy::too_many_arguments)]
+fn write_graph(
+    dependency_tree: &DepTree,
+    output_base: &Path,
+    label_with_class: bool,
+    node_colors: &HashMap<NodeID, String>,
+    title: Option<&str>,
+    label_max_len: Option<usize>,
+    failure_nodes: bool,
+    export_roots: &[PathBuf],
+) -> eyre::Result<Vec<PathBuf>> {
+    use std::io::Write;
+
+    use graphviz_rust::printer::DotPrinter;
+
+    let json_path = output_base.with_extension("json");
+    let dot_path = output_base.with_extension("dot");
+    let svg_path = output_base.with_extension("svg");
+
+    let snapshot = TreeSnapshot::from(dependency_tree);
+    match failure_nodes {
+        true => snapshot.with_failure_edges(dependency_tree),
+        false => snapshot,
+    }
+    .with_normalized_paths(export_roots)
+    .save(&json_path)?;
+
+    let graph = dependency_tree.to_graph(
+        None,
+        label_with_class,
+        node_colors,
+        title,
+        label_max_len,
+        failure_nodes,
+    );
+
+    let graph_dot = graph.print(&mut PrinterContext::default());
+    let mut file = std::fs::File::create(&dot_path)?;
+    file.write_all(graph_dot.as_bytes())?;
+
+    let graph_svg = exec(
+        graph,
+        &mut PrinterContext::default(),
+        vec![CommandArg::Format(Format::Svg)],
+    )
+    .map_err(|e| eyre::eyre!("graphviz failed: {e}"))?;
+
+    let mut file = std::fs::File::create(&svg_path)?;
+    file.write_all(graph_svg.as_bytes())?;
+
+    Ok(vec![json_path, dot_path, svg_path])
+}
+
+/// Short overview of the built tree and the written files, with the stats table if verbose
+fn print_summary(dependency_tree: &DepTree, written_paths: &[PathBuf], jobs: usize, verbose: bool) {
+    log::info!(
+        "{:?}: {} nodes, {} edges, {} failures, read with {} jobs",
+        dependency_tree.get_root_node().path,
+        dependency_tree.nodes.len(),
+        dependency_tree.edges().count(),
+        dependency_tree.real_failures().count(),
+        jobs
+    );
+
+    for path in written_paths {
+        log::info!("Wrote {:?}", path);
+    }
+
+    if verbose {
+        log::info!(
+            "\n===== Dependency tree stats =====\n{}=================================\n",
+            dependency_tree.stats()
+        );
+    }
+}
+
+/// Logs the failures of the tree, the skipped dependencies only by their amount
+fn print_fails(dependency_tree: &DepTree) {
+    let skipped = dependency_tree.failures.len() - dependency_tree.real_failures().count();
+
+    if skipped > 0 {
+        log::info!("Skipped {} dependencies", skipped);
+    }
+
+    log::error!("{}", dependency_tree.failures_list());
+
+    if let Some(ambiguous) = dependency_tree.ambiguous_list() {
+        log::warn!("{}", ambiguous);
+    }
+}
+
+fn print_orphans(content_dir: &Path, dirs_config: &DirsConfig) -> eyre::Result<()> {
+    // The project dirs are derived from the path of an asset, any one in the content dir will do
+    let asset_file_path = walkdir::WalkDir::new(content_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .find(|path| path.is_file() && is_package_path(path))
+        .ok_or_else(|| eyre::eyre!("No assets found in {:?}", content_dir))?;
+
+    let asset_dirs = dirs_config.asset_dirs(Some(asset_file_path));
+
+    let pb = DepTree::new_pb()?;
+
+    let orphans = find_orphans(&asset_dirs, Some(&pb));
+
+    pb.finish_with_message("Done");
+
+    let total_size = orphans
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    log::info!(
+        "\n===== Assets nothing depends on =====\n{}=====================================\n",
+        orphans
+            .iter()
+            .map(|path| format!("{}\n", path.display()))
+            .join("")
+    );
+
+    log::info!(
+        "{} assets aren't referenced by anything, {} in total",
+        orphans.len(),
+        format_size(total_size)
+    );
+
+    Ok(())
+}
+
+/// Prints the names shared by several assets with all of their paths
+fn print_duplicate_names(content_dir: &Path) {
+    let duplicates = find_duplicate_names(content_dir);
+
+    log::info!(
+        "\n===== Asset names used more than once =====\n{}===========================================\n",
+        duplicates
+            .iter()
+            .map(|(name, paths)| {
+                format!(
+                    "{} ({})\n{}",
+                    name,
+                    paths.len(),
+                    paths
+                        .iter()
+                        .map(|path| format!("\t{}\n", path.display()))
+                        .join("")
+                )
+            })
+            .join("")
+    );
+
+    log::info!("{} asset names are used more than once", duplicates.len());
+}
+
+/// Prints where each direct dependency of the asset resolves to, or why it doesn't
+fn print_resolve_report(asset_dirs: &AssetDirs) -> eyre::Result<()> {
+    let Some(asset_file_path) = &asset_dirs.asset_file_path else {
+        return Err(eyre::eyre!("No asset to resolve the dependencies of"));
+    };
+
+    let report = Asset::new(asset_file_path)?.resolve_report(asset_dirs);
+
+    log::info!(
+        "\n{}",
+        report
+            .iter()
+            .map(|(dependency_name, res)| match res {
+                Ok(path) => format!("{} -> {}\n", dependency_name, path.display()),
+                Err(err) => format!("{} !! {}\n", dependency_name, err.kind),
+            })
+            .join("")
+    );
+
+    log::info!(
+        "{} of {} dependencies resolved",
+        report.iter().filter(|(_, res)| res.is_ok()).count(),
+        report.len()
+    );
+
+    Ok(())
+}
+
+/// Prints the inferred directories, and where the git repos the assets are tracked in were opened
+fn print_dirs_info(asset_dirs: &AssetDirs) {
+    log::info!("{:?}", asset_dirs);
+
+    let repo_workdir = |git_repo: Option<GitRepo>| match git_repo {
+        Some(git_repo) => match git_repo.lock() {
+            Ok(git_repo) => match git_repo.workdir() {
+                Some(workdir) => format!("opened at {}", workdir.display()),
+                None => "opened, bare".to_string(),
+            },
+            Err(_) => "opened, but in use".to_string(),
+        },
+        None => "not found".to_string(),
+    };
+
+    log::info!(
+        "project git repo: {}",
+        repo_workdir(asset_dirs.project_git_repo.clone())
+    );
+    log::info!(
+        "engine git repo: {}",
+        repo_workdir(asset_dirs.engine_git_repo.clone())
+    );
+
+    if let Some(asset_file_path) = &asset_dirs.asset_file_path {
+        if let Some(plugin_dir) = AssetDirs::get_plugin_dir(asset_file_path) {
+            log::info!(
+                "plugin git repo ({}): {}",
+                plugin_dir.display(),
+                repo_workdir(asset_dirs.get_plugin_git_repo(asset_file_path))
+            );
+        }
+    }
+}
+
+/// Builds the trees of the assets one level deep and lists their hard references that don't resolve,
+/// failing if there are any
+fn run_check(asset_paths: &[PathBuf], dirs_config: &DirsConfig) -> eyre::Result<()> {
+    let mut broken_assets = 0;
+
+    for asset_path in asset_paths {
+        // Only the hard references are checked, the soft ones are loaded on demand
+        let asset_dirs = AssetDirs {
+            include_soft: false,
+            ..dirs_config.asset_dirs(Some(asset_path.clone()))
+        };
+        let dependency_tree =
+            DepTree::build(&asset_dirs, 1, TraversalOrder::default(), None, None)?;
+
+        // The ambiguous references still resolve to one of their candidates
+        let unresolved = dependency_tree
+            .real_failures()
+            .filter(|failure| !failure.is_ambiguous())
+            .collect_vec();
+
+        if unresolved.is_empty() {
+            continue;
+        }
+
+        broken_assets += 1;
+
+        log::error!(
+            "{}\n{}",
+            asset_path.display(),
+            unresolved
+                .iter()
+                .map(|failure| format!("\t{}", failure))
+                .join("\n")
+        );
+    }
+
+    match broken_assets {
+        0 => Ok(()),
+        _ => Err(eyre::eyre!(
+            "{} of {} assets have unresolved hard references",
+            broken_assets,
+            asset_paths.len()
+        )),
+    }
+}
+
+/// Prints the plugins of the tree that aren't enabled, with their assets and what references them
+fn print_disabled_plugins(dep_tree: &DepTree, asset_dirs: &AssetDirs) -> eyre::Result<()> {
+    let disabled_plugins = find_disabled_plugins(dep_tree, asset_dirs)?;
+
+    if disabled_plugins.is_empty() {
+        log::info!("All the plugins the asset depends on are enabled");
+
+        return Ok(());
+    }
+
+    let mut res = "\n===== Dependencies on the plugins that aren't enabled =====\n".to_string();
+
+    for plugin in &disabled_plugins {
+        res += &format!(
+            "{} ({}, {}) - {:?}\n",
+            plugin.name,
+            plugin.origin.to_string(),
+            plugin.reason,
+            plugin.dir
+        );
+
+        for &node_id in &plugin.node_ids {
+            let referenced_by = dep_tree
+                .parents(node_id)
+                .into_iter()
+                // The plugin's own assets referencing each other aren't the problem
+                .filter(|parent_id| !plugin.node_ids.contains(parent_id))
+                .filter_map(|parent_id| dep_tree.get_node(parent_id))
+                .map(|parent_node| fix_file_name(parent_node.path.file_name()))
+                .join(", ");
+
+            res += &format!(
+                "\t{}{}\n",
+                dep_tree.get_node(node_id).unwrap().path.display(),
+                match referenced_by.is_empty() {
+                    true => String::new(),
+                    false => format!(" <- {}", referenced_by),
                 }
+            );
+        }
+    }
+
+    res += "===========================================================\n";
+
+    log::info!("{}", res);
+
+    log::warn!(
+        "The asset depends on {} plugins that aren't enabled",
+        disabled_plugins.len()
+    );
+
+    Ok(())
+}
+
+/// Builds the trees of all the assets in the manifest, logging one line per asset instead of the progress bars
+fn run_batch(
+    manifest: &Path,
+    max_recurse_depth: u32,
+    out_dir: &Path,
+    combined: bool,
+    traversal_order: TraversalOrder,
+    dirs_config: &DirsConfig,
+    post_process: &PostProcess,
+) -> eyre::Result<()> {
+    let asset_paths = std::fs::read_to_string(manifest)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
+    if asset_paths.is_empty() {
+        return Err(eyre::eyre!("No assets listed in {:?}", manifest));
+    }
+
+    std::fs::create_dir_all(out_dir)?;
+
+    let build = |asset_path: &PathBuf| {
+        let asset_dirs = dirs_config.asset_dirs(Some(asset_path.clone()));
+
+        let res = match combined {
+            true => DepTree::build_combined(
+                &asset_dirs,
+                &asset_paths,
+                max_recurse_depth,
+                traversal_order,
+                None,
+            ),
+            false => DepTree::build(&asset_dirs, max_recurse_depth, traversal_order, None, None),
+        };
+
+        (asset_dirs, res)
+    };
+
+    let roots = match combined {
+        true => &asset_paths[..1],
+        false => &asset_paths[..],
+    };
+
+    let mut failed_assets = 0;
+    let mut trees_with_failures = 0;
+
+    for asset_path in roots {
+        let (asset_dirs, res) = build(asset_path);
+
+        let dependency_tree = match res {
+            Ok(dependency_tree) => post_process.apply(dependency_tree, &asset_dirs)?,
+            Err(err) => {
+                log::error!("{:?}: {}", asset_path, err);
+                failed_assets += 1;
+
+                continue;
             }
+        };
+
+        let failures = dependency_tree.real_failures().count();
+
+        log::info!(
+            "{:?}: {} nodes, {} failures",
+            asset_path,
+            dependency_tree.nodes.len(),
+            failures
+        );
+
+        if failures > 0 {
+            trees_with_failures += 1;
         }
+
+        let output_name = match combined {
+            true => "combined_deptree".to_string(),
+            false => output_name(&dependency_tree, "deptree"),
+        };
+
+        write_graph(
+            &dependency_tree,
+            &out_dir.join(output_name),
+            false,
+            &HashMap::new(),
+            None,
+            None,
+            false,
+            &dirs_config.export_roots(&asset_dirs),
+        )?;
+    }
+
+    log::info!(
+        "Processed {} trees: {} failed to build, {} have unresolved dependencies",
+        roots.len(),
+        failed_assets,
+        trees_with_failures
+    );
+
+    match failed_assets + trees_with_failures {
+        0 => Ok(()),
+        _ => Err(eyre::eyre!("The batch finished with failures")),
+    }
+}
+
+/// Streams the tree as one JSON record per line (nodes, then edges, then failures), tagged with their `type`,
+/// so the large trees don't have to be serialized in one piece
+fn write_ndjson(
+    dependency_tree: &DepTree,
+    path: &Path,
+    export_roots: &[PathBuf],
+) -> eyre::Result<()> {
+    use std::io::{BufWriter, Write};
+
+    use serde_json::json;
+
+    let out: Box<dyn Write> = match path == Path::new("-") {
+        true => Box::new(std::io::stdout().lock()),
+        false => Box::new(std::fs::File::create(path)?),
+    };
+    let mut out = BufWriter::new(out);
+
+    let mut write_record = |record: serde_json::Value| -> eyre::Result<()> {
+        serde_json::to_writer(&mut out, &record)?;
+        out.write_all(b"\n")?;
+
+        Ok(())
+    };
+
+    for (node_id, node) in dependency_tree.nodes.iter().sorted_by_key(|(id, _)| **id) {
+        write_record(json!({
+            "type": "node",
+            "id": node_id,
+            "path": normalize_path(&node.path, export_roots),
+            "origin": node.origin.to_string(),
+            "class": node.primary_class,
+            "depth": dependency_tree.get_recurse_depth(*node_id),
+        }))?;
+    }
+
+    for (node_id, con_node_id) in dependency_tree.edges().sorted() {
+        write_record(json!({
+            "type": "edge",
+            "from": node_id,
+            "to": con_node_id,
+        }))?;
+    }
+
+    for failure in &dependency_tree.failures {
+        write_record(json!({
+            "type": "failure",
+            "path": normalize_path(&failure.path, export_roots),
+            "reason": failure.kind.to_string(),
+            "parents": dependency_tree.failure_parents(failure),
+        }))?;
+    }
+
+    out.flush()?;
+
+    if path != Path::new("-") {
+        log::info!("Saved the ndjson export to {:?}", path);
     }
 
     Ok(())
 }
 
+#[cfg(not(feature = "gui"))]
+fn run_app(_asset_dirs: AssetDirs) -> eyre::Result<()> {
+    Err(eyre::eyre!(
+        "The GUI isn't a part of this build, it needs the \"gui\" feature"
+    ))
+}
+
+#[cfg(feature = "gui")]
 fn run_app(asset_dirs: AssetDirs) -> eyre::Result<()> {
+    let user_settings = UserSettings::load();
+
     let settings = Settings {
         id: None,
         window: window::Settings {
@@ -133,10 +1584,13 @@ fn run_app(asset_dirs: AssetDirs) -> eyre::Result<()> {
             icon: None,
         },
         flags: asset_dirs,
-        default_font: Some(include_bytes!(
-            "../resources/fonts/jetbrains_mono/fonts/ttf/JetBrainsMono-Medium.ttf"
-        )),
-        default_text_size: 18,
+        default_font: match user_settings.system_font {
+            true => None,
+            false => Some(include_bytes!(
+                "../resources/fonts/jetbrains_mono/fonts/ttf/JetBrainsMono-Medium.ttf"
+            )),
+        },
+        default_text_size: user_settings.font_size,
         text_multithreading: true,
         antialiasing: true,
         exit_on_close_request: true,
@@ -147,3 +1601,54 @@ fn run_app(asset_dirs: AssetDirs) -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_asset_paths, parse_recurse_depth, UNLIMITED_RECURSE_DEPTH};
+
+    #[test]
+    fn recurse_depth_zero_is_kept() {
+        assert_eq!(parse_recurse_depth("0"), Ok(0));
+    }
+
+    #[test]
+    fn recurse_depth_can_be_unlimited() {
+        assert_eq!(parse_recurse_depth("none"), Ok(UNLIMITED_RECURSE_DEPTH));
+        assert_eq!(
+            parse_recurse_depth("unlimited"),
+            Ok(UNLIMITED_RECURSE_DEPTH)
+        );
+    }
+
+    #[test]
+    fn recurse_depth_rejects_garbage() {
+        assert!(parse_recurse_depth("-1").is_err());
+        assert!(parse_recurse_depth("deep").is_err());
+    }
+
+    #[test]
+    fn asset_paths_with_glob_characters_are_taken_literally() {
+        let root = tempfile::tempdir().unwrap();
+        let maps_dir = root.path().join("Maps[Old]");
+        std::fs::create_dir_all(&maps_dir).unwrap();
+        let level = maps_dir.join("Level.umap");
+        std::fs::write(&level, []).unwrap();
+        std::fs::write(maps_dir.join("Other.umap"), []).unwrap();
+
+        assert_eq!(
+            expand_asset_paths(level.to_str().unwrap(), false).unwrap(),
+            vec![level]
+        );
+        // The pattern of the same folder doesn't match it, the brackets are a character class
+        assert!(expand_asset_paths(maps_dir.join("*.umap").to_str().unwrap(), false).is_err());
+        assert_eq!(
+            expand_asset_paths(
+                root.path().join("Maps[[]Old[]]/*.umap").to_str().unwrap(),
+                false
+            )
+            .unwrap()
+            .len(),
+            2
+        );
+    }
+}
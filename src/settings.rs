@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+pub enum AppTheme {
+    Dark,
+    Light,
+    Custom,
+}
+
+impl ToString for AppTheme {
+    fn to_string(&self) -> String {
+        match self {
+            AppTheme::Dark => "Dark",
+            AppTheme::Light => "Light",
+            AppTheme::Custom => "Custom",
+        }
+        .to_string()
+    }
+}
+
+/// What clicking an asset in the lists copies to the clipboard
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+pub enum CopyFormat {
+    /// `Hero.uasset`
+    FileName,
+    /// `Hero`
+    BaseName,
+    /// Path of the asset file on the disk
+    DiskPath,
+    /// `/Game/Characters/Hero`
+    ObjectPath,
+    /// The editor's "Copy Reference", e.g. `Blueprint'/Game/Characters/Hero.Hero'`
+    Reference,
+}
+
+impl ToString for CopyFormat {
+    fn to_string(&self) -> String {
+        match self {
+            CopyFormat::FileName => "File Name",
+            CopyFormat::BaseName => "Base Name",
+            CopyFormat::DiskPath => "Disk Path",
+            CopyFormat::ObjectPath => "Object Path",
+            CopyFormat::Reference => "Reference",
+        }
+        .to_string()
+    }
+}
+
+/// Amount of the recently picked paths we remember
+const PATH_HISTORY_CAPACITY: usize = 10;
+
+pub const MIN_FONT_SIZE: u16 = 10;
+pub const MAX_FONT_SIZE: u16 = 48;
+
+/// Step of the large tree threshold buttons, also its minimum
+pub const LARGE_TREE_THRESHOLD_STEP: usize = 500;
+
+/// Share of the height the tabs of the dependency tree take above the footer by default
+pub const DEFAULT_FOOTER_SPLIT_RATIO: f32 = 0.8;
+
+/// User settings that persist between the runs of the app
+#[derive(Debug, Clone, Serialize, Deserialize, SmartDefault)]
+#[serde(default)]
+pub struct UserSettings {
+    #[default(AppTheme::Dark)]
+    pub theme: AppTheme,
+    /// Use the colorblind-safe node colors
+    pub colorblind_colors: bool,
+    /// Size of the text in the node lists, also the default text size on startup
+    #[default(18)]
+    pub font_size: u16,
+    /// Use the system font instead of the bundled one, applied on restart
+    pub system_font: bool,
+    /// What clicking an asset copies, shift-clicking always copies the disk path
+    #[default(CopyFormat::FileName)]
+    pub click_copy_format: CopyFormat,
+    /// What ctrl-clicking an asset copies
+    #[default(CopyFormat::Reference)]
+    pub ctrl_click_copy_format: CopyFormat,
+    /// Trees with more nodes than this have their nodes collapsed by default
+    #[default(1000)]
+    pub large_tree_threshold: usize,
+    /// Wrap the nodes of the graph into as many columns as fit the window
+    pub multi_column_graph: bool,
+    /// Share of the height the tabs of the dependency tree take above the footer
+    #[default(DEFAULT_FOOTER_SPLIT_RATIO)]
+    pub footer_split_ratio: f32,
+    /// Last max recursion depth set in the dependency tree tab, its default on startup
+    #[default(10)]
+    pub max_recurse_depth: u32,
+    /// Unreal Editor executable the assets are opened in, e.g. `UnrealEditor.exe`
+    pub editor_path: Option<PathBuf>,
+
+    /// Recently picked asset paths, the most recent first
+    pub asset_path_history: Vec<PathBuf>,
+    /// Recently picked engine paths, the most recent first
+    pub engine_path_history: Vec<PathBuf>,
+}
+
+impl UserSettings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join("uasset_helper").join("settings.json"))
+    }
+
+    /// Moves the path to the front of the history, dropping the oldest entries if it's full
+    pub fn push_path_history(history: &mut Vec<PathBuf>, path: PathBuf) {
+        history.retain(|old_path| old_path != &path);
+        history.insert(0, path);
+        history.truncate(PATH_HISTORY_CAPACITY);
+    }
+
+    /// Loads the settings from the settings file, falling back to the defaults if it doesn't exist or is broken
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|err| err.to_string()))
+        {
+            Ok(settings) => settings,
+            Err(err) => {
+                log::error!("Failed to load the settings from {:?}: {}", path, err);
+
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            log::error!("Failed to save the settings: couldn't find the config directory");
+
+            return;
+        };
+
+        let res = path
+            .parent()
+            .map(std::fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .map_err(|err| err.to_string())
+            .and_then(|_| serde_json::to_string_pretty(self).map_err(|err| err.to_string()))
+            .and_then(|contents| std::fs::write(&path, contents).map_err(|err| err.to_string()));
+
+        match res {
+            Ok(_) => log::debug!("Saved the settings to {:?}", path),
+            Err(err) => log::error!("Failed to save the settings to {:?}: {}", path, err),
+        }
+    }
+}
@@ -1,5 +1,11 @@
-use std::borrow::Cow;
-use std::path::Path;
+use std::collections::HashSet;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+
+use itertools::Itertools;
+
+use crate::asset::{AssetError, AssetErrorKind};
 
 pub struct SplitVecContainer<L, R> {
     pub left: Vec<L>,
@@ -92,13 +98,701 @@ pub fn path_to_str(path: impl AsRef<Path>) -> String {
     path.as_ref().to_str().unwrap().to_string()
 }
 
-pub fn save_to_clipboard<'a>(clipboard: &mut arboard::Clipboard, text: impl Into<Cow<'a, str>>) {
-    match clipboard.set_text(text) {
-        Ok(_) => {
-            log::info!("Copied text to clipboard");
+/// Path as written to the exports: relative to the first of the roots it's in and with forward slashes
+/// on every platform, so the exports made on different machines can be diffed.
+///
+/// The roots are canonical (see [`AssetDirs::export_roots`](crate::asset::AssetDirs::export_roots)),
+/// so the path is canonicalized as well before matching them. Only the existing part of the path is,
+/// as the failures are of the missing files, falling back to the path as given if none of it exists
+pub fn normalize_path(path: impl AsRef<Path>, roots: &[PathBuf]) -> String {
+    let path = path.as_ref();
+    let canonical_path = path.ancestors().find_map(|ancestor| {
+        let canonical_ancestor = std::fs::canonicalize(ancestor).ok()?;
+        let missing_part = path.strip_prefix(ancestor).ok()?;
+
+        Some(canonical_ancestor.join(missing_part))
+    });
+    let relative_path = [canonical_path.as_deref(), Some(path)]
+        .into_iter()
+        .flatten()
+        .find_map(|path| roots.iter().find_map(|root| path.strip_prefix(root).ok()))
+        .unwrap_or(path);
+
+    relative_path.to_string_lossy().replace('\\', "/")
+}
+
+/// Shortens the string to `max` chars by replacing its middle with `…`,
+/// keeping the file name at the end of a path whole if it fits
+pub fn ellipsize_middle(s: &str, max: usize) -> String {
+    let len = s.chars().count();
+
+    if len <= max {
+        return s.to_string();
+    }
+
+    if max == 0 {
+        return String::new();
+    }
+
+    // Length of the last path segment with its separator
+    let file_name_len = s
+        .rfind(['/', '\\'])
+        .map(|index| s[index..].chars().count())
+        .unwrap_or(len);
+    let tail_len = match file_name_len < max - 1 {
+        true => file_name_len,
+        false => (max - 1) / 2,
+    };
+    let head_len = max - 1 - tail_len;
+
+    format!(
+        "{}…{}",
+        s.chars().take(head_len).collect::<String>(),
+        s.chars().skip(len - tail_len).collect::<String>()
+    )
+}
+
+/// Formats the amount of bytes with the largest fitting binary unit, e.g. `12.34 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.2} {}", size, UNITS[unit]),
+    }
+}
+
+/// Amount of the assets read at once by default, one per available core
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|jobs| jobs.get())
+        .unwrap_or(1)
+}
+
+/// Tasks sent to the workers and their results, both with the indices of the items
+type WorkerChannels<T, U> = (
+    mpsc::Sender<(usize, T)>,
+    mpsc::Receiver<(usize, std::thread::Result<U>)>,
+);
+
+/// Maps the batches of items on the worker threads started by [`with_workers`]
+pub struct Workers<'f, T, U> {
+    f: &'f (dyn Fn(T) -> U + Sync),
+    /// `None` if the items are mapped on the calling thread
+    channels: Option<WorkerChannels<T, U>>,
+}
+
+impl<T, U> Workers<'_, T, U> {
+    /// Maps the items on the workers, keeping their order
+    pub fn map(&self, items: Vec<T>) -> Vec<U> {
+        let Some((tasks, results)) = &self.channels else {
+            return items.into_iter().map(self.f).collect();
+        };
+
+        let len = items.len();
+        for task in items.into_iter().enumerate() {
+            tasks
+                .send(task)
+                .expect("The workers stop only after the pool is dropped");
+        }
+
+        let mut mapped = (0..len).map(|_| None).collect_vec();
+        for _ in 0..len {
+            let (index, res) = results
+                .recv()
+                .expect("The workers send a result for every task");
+
+            mapped[index] = Some(res.unwrap_or_else(|err| std::panic::resume_unwind(err)));
+        }
+
+        mapped.into_iter().flatten().collect()
+    }
+}
+
+/// Starts at most `jobs` threads mapping the items with `f` and runs `body` with them.
+/// The same threads map all the batches, they stop when `body` returns
+pub fn with_workers<T: Send, U: Send, R>(
+    jobs: usize,
+    f: impl Fn(T) -> U + Sync,
+    body: impl FnOnce(&Workers<T, U>) -> R,
+) -> R {
+    if jobs <= 1 {
+        return body(&Workers {
+            f: &f,
+            channels: None,
+        });
+    }
+
+    let (task_sender, task_receiver) = mpsc::channel::<(usize, T)>();
+    let (result_sender, result_receiver) = mpsc::channel();
+    let task_receiver = Mutex::new(task_receiver);
+    let f = &f;
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            let task_receiver = &task_receiver;
+            let result_sender = result_sender.clone();
+
+            scope.spawn(move || loop {
+                // The lock is only held while waiting for the next task
+                let task = task_receiver.lock().unwrap().recv();
+                // The tasks channel closes when the workers are dropped
+                let Ok((index, item)) = task else {
+                    break;
+                };
+                // Panics are sent to the calling thread, so it doesn't wait for their results
+                let res = std::panic::catch_unwind(AssertUnwindSafe(|| f(item)));
+
+                if result_sender.send((index, res)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        body(&Workers {
+            f,
+            channels: Some((task_sender, result_receiver)),
+        })
+    })
+}
+
+/// First file with the extension directly in the directory, the descriptors are named after the project or plugin
+pub fn find_descriptor(dir: &Path, extension: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some(extension)
+        })
+        .sorted()
+        .next()
+}
+
+/// Opens the folder containing the file in the system file manager
+pub fn reveal_in_folder(path: impl AsRef<Path>) {
+    let Some(folder) = path.as_ref().parent() else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    let opener = "explorer";
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let opener = "xdg-open";
+
+    if let Err(err) = std::process::Command::new(opener).arg(folder).spawn() {
+        log::error!("Failed to open {:?}: {}", folder, err);
+    }
+}
+
+/// Resolves an object path (e.g. `/Game/Characters/Hero`) to all the asset files it can point to.
+/// There's more than one candidate only if the asset is found in several plugins
+pub fn object_path_candidates(
+    object_path: &str,
+    content_dir: impl AsRef<Path>,
+    engine_content_dir: &Option<impl AsRef<Path>>,
+    plugins_dirs: &[impl AsRef<Path>],
+    extra_content_roots: &[(String, PathBuf)],
+    plugin_scan_depth: usize,
+) -> Result<Vec<PathBuf>, AssetError> {
+    // The names come from the package data, so they can be anything
+    if !object_path.starts_with('/') {
+        return Err(AssetError::new(
+            object_path,
+            AssetErrorKind::MalformedObjectPath,
+        ));
+    }
+
+    let dep = if !object_path.ends_with(".uasset") {
+        format!("{}.uasset", object_path)
+    } else {
+        object_path.to_string()
+    };
+    let segments: Vec<_> = dep[1..].split('/').collect();
+
+    let Some(&root_folder) = segments
+        .first()
+        .filter(|root_folder| !root_folder.is_empty())
+    else {
+        log::debug!("Error: Couldn't get the root folder of the path");
+
+        return Err(AssetError::new(&dep, AssetErrorKind::NoRootFolder));
+    };
+
+    match root_folder {
+        "Game" => {
+            let path = content_dir.as_ref().join(segments[1..].join("/"));
+
+            match path.exists() {
+                true => Ok(vec![path]),
+                false => Err(AssetError::new(path, AssetErrorKind::MissingInContent)),
+            }
+        }
+        "Engine" => match engine_content_dir {
+            Some(engine_content_dir) => {
+                let path = engine_content_dir.as_ref().join(segments[1..].join("/"));
+
+                match path.exists() {
+                    true => Ok(vec![path]),
+                    false => Err(AssetError::new(
+                        path,
+                        AssetErrorKind::MissingInEngineContent,
+                    )),
+                }
+            }
+            None => Err(AssetError::new(&dep, AssetErrorKind::MissingEngineDir)),
+        },
+        "Script" => Err(AssetError::new(&dep, AssetErrorKind::ScriptReference)),
+        root_dir => {
+            // The custom mount points take precedence over the plugins with the same name
+            if let Some(path) = extra_content_roots
+                .iter()
+                .filter(|(mount_name, _)| mount_name == root_dir)
+                .map(|(_, mount_dir)| mount_dir.join(segments[1..].join("/")))
+                .find(|path| path.exists())
+            {
+                return Ok(vec![path]);
+            }
+
+            let candidate_dirs = plugins_dirs
+                .iter()
+                .map(|plugins_dir| {
+                    walkdir::WalkDir::new(plugins_dir.as_ref())
+                        .max_depth(plugin_scan_depth)
+                        .into_iter()
+                        // There are no plugins inside the content of a plugin, no need to walk the assets
+                        .filter_entry(|entry| entry.file_name() != "Content")
+                        .flat_map(|entry| {
+                            entry.ok().and_then(|entry| {
+                                if entry.file_name() == root_dir {
+                                    let content_dir = entry.path().join("Content");
+
+                                    if content_dir.exists() {
+                                        Some(content_dir)
+                                    } else {
+                                        None
+                                    }
+                                } else {
+                                    None
+                                }
+                            })
+                        })
+                })
+                .fold(HashSet::new(), |mut acc, candidate_dirs| {
+                    acc.extend(candidate_dirs);
+                    acc
+                });
+
+            let candidates = candidate_dirs
+                .iter()
+                .filter_map(|candidate_dir| {
+                    let path = candidate_dir.join(segments[1..].join("/"));
+
+                    if path.exists() {
+                        Some(path)
+                    } else {
+                        None
+                    }
+                })
+                .sorted()
+                .collect_vec();
+
+            match candidates.is_empty() {
+                false => Ok(candidates),
+                true => Err(AssetError::new(&dep, AssetErrorKind::NotInPlugins)),
+            }
+        }
+    }
+}
+
+/// Resolves an object path (e.g. `/Game/Characters/Hero`) to the asset file on disk
+pub fn object_path_to_disk_path(
+    name: &str,
+    content_dir: impl AsRef<Path>,
+    engine_content_dir: &Option<impl AsRef<Path>>,
+    plugins_dirs: &[impl AsRef<Path>],
+    extra_content_roots: &[(String, PathBuf)],
+    plugin_scan_depth: usize,
+) -> Result<PathBuf, AssetError> {
+    object_path_candidates(
+        name,
+        content_dir,
+        engine_content_dir,
+        plugins_dirs,
+        extra_content_roots,
+        plugin_scan_depth,
+    )
+    .map(|candidates| candidates.into_iter().next().unwrap())
+}
+
+/// Converts the path of an asset file to the object path the engine refers to it with,
+/// e.g. `<Project>/Content/Characters/Hero.uasset` to `/Game/Characters/Hero`
+pub fn disk_path_to_object_path(
+    path: impl AsRef<Path>,
+    content_dir: &Option<impl AsRef<Path>>,
+    engine_content_dir: &Option<impl AsRef<Path>>,
+    plugins_dirs: &[impl AsRef<Path>],
+) -> Option<String> {
+    let path = path.as_ref().with_extension("");
+
+    let to_object_path = |mount: &str, relative_path: &Path| {
+        format!(
+            "/{}/{}",
+            mount,
+            relative_path
+                .iter()
+                .map(|seg| seg.to_string_lossy())
+                .join("/")
+        )
+    };
+
+    if let Some(relative_path) = content_dir
+        .as_ref()
+        .and_then(|content_dir| path.strip_prefix(content_dir).ok())
+    {
+        return Some(to_object_path("Game", relative_path));
+    }
+
+    if let Some(relative_path) = engine_content_dir
+        .as_ref()
+        .and_then(|engine_content_dir| path.strip_prefix(engine_content_dir).ok())
+    {
+        return Some(to_object_path("Engine", relative_path));
+    }
+
+    plugins_dirs.iter().find_map(|plugins_dir| {
+        let relative_path = path.strip_prefix(plugins_dir).ok()?;
+        let segments = relative_path.iter().collect_vec();
+
+        // Plugins are mounted under the name of the directory containing their Content directory
+        let content_index = segments.iter().position(|seg| *seg == "Content")?;
+        let plugin_name = segments
+            .get(content_index.checked_sub(1)?)?
+            .to_string_lossy();
+
+        Some(to_object_path(
+            &plugin_name,
+            &segments[content_index + 1..].iter().collect::<PathBuf>(),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use tempfile::TempDir;
+
+    use super::{
+        disk_path_to_object_path, ellipsize_middle, normalize_path, object_path_candidates,
+        object_path_to_disk_path, with_workers,
+    };
+    use crate::asset::{AssetErrorKind, DEFAULT_PLUGIN_SCAN_DEPTH};
+
+    struct Layout {
+        _root: TempDir,
+        content_dir: PathBuf,
+        engine_content_dir: Option<PathBuf>,
+        plugins_dirs: Vec<PathBuf>,
+    }
+
+    fn touch(path: impl AsRef<Path>) {
+        std::fs::create_dir_all(path.as_ref().parent().unwrap()).unwrap();
+        std::fs::write(path, []).unwrap();
+    }
+
+    /// Project, engine and their plugins, with the `Shared` plugin present in both
+    fn layout() -> Layout {
+        let root = tempfile::tempdir().unwrap();
+
+        let project_dir = root.path().join("Project");
+        let engine_dir = root.path().join("UE").join("Engine");
+
+        touch(project_dir.join("Content/Characters/Hero.uasset"));
+        touch(project_dir.join("Plugins/Gameplay/Abilities/Content/Fireball.uasset"));
+        touch(project_dir.join("Plugins/Shared/Content/Common.uasset"));
+        touch(engine_dir.join("Content/BasicShapes/Cube.uasset"));
+        touch(engine_dir.join("Plugins/FX/Niagara/Content/DefaultSystem.uasset"));
+        touch(engine_dir.join("Plugins/Misc/Shared/Content/Common.uasset"));
+
+        Layout {
+            content_dir: project_dir.join("Content"),
+            engine_content_dir: Some(engine_dir.join("Content")),
+            plugins_dirs: vec![project_dir.join("Plugins"), engine_dir.join("Plugins")],
+            _root: root,
         }
-        Err(err) => {
-            log::error!("Failed to copy text to clipboard: {}", err);
+    }
+
+    fn resolve(layout: &Layout, object_path: &str) -> Result<PathBuf, crate::asset::AssetError> {
+        object_path_to_disk_path(
+            object_path,
+            &layout.content_dir,
+            &layout.engine_content_dir,
+            &layout.plugins_dirs,
+            &[],
+            DEFAULT_PLUGIN_SCAN_DEPTH,
+        )
+    }
+
+    #[test]
+    fn game_import_resolves_to_project_content() {
+        let layout = layout();
+
+        assert_eq!(
+            resolve(&layout, "/Game/Characters/Hero").unwrap(),
+            layout.content_dir.join("Characters/Hero.uasset")
+        );
+    }
+
+    #[test]
+    fn missing_game_import_fails() {
+        let layout = layout();
+
+        assert!(resolve(&layout, "/Game/Characters/Villain").is_err());
+    }
+
+    #[test]
+    fn engine_import_resolves_to_engine_content() {
+        let layout = layout();
+
+        assert_eq!(
+            resolve(&layout, "/Engine/BasicShapes/Cube").unwrap(),
+            layout
+                .engine_content_dir
+                .as_ref()
+                .unwrap()
+                .join("BasicShapes/Cube.uasset")
+        );
+    }
+
+    #[test]
+    fn engine_import_without_engine_dir_fails() {
+        let layout = Layout {
+            engine_content_dir: None,
+            ..layout()
+        };
+
+        assert!(resolve(&layout, "/Engine/BasicShapes/Cube").is_err());
+    }
+
+    #[test]
+    fn script_import_fails() {
+        let layout = layout();
+
+        assert!(resolve(&layout, "/Script/Engine").is_err());
+    }
+
+    #[test]
+    fn malformed_import_names_fail() {
+        let layout = layout();
+
+        for object_path in ["", "Game/Characters/Hero", "ü/Hero"] {
+            assert_eq!(
+                resolve(&layout, object_path).unwrap_err().kind,
+                AssetErrorKind::MalformedObjectPath
+            );
+        }
+        assert_eq!(
+            resolve(&layout, "//Hero").unwrap_err().kind,
+            AssetErrorKind::NoRootFolder
+        );
+    }
+
+    #[test]
+    fn plugin_imports_resolve_to_plugin_content() {
+        let layout = layout();
+
+        assert_eq!(
+            resolve(&layout, "/Abilities/Fireball").unwrap(),
+            layout.plugins_dirs[0].join("Gameplay/Abilities/Content/Fireball.uasset")
+        );
+        assert_eq!(
+            resolve(&layout, "/Niagara/DefaultSystem").unwrap(),
+            layout.plugins_dirs[1].join("FX/Niagara/Content/DefaultSystem.uasset")
+        );
+    }
+
+    #[test]
+    fn mounted_content_root_import_resolves() {
+        let layout = layout();
+        let shared_dir = layout.content_dir.parent().unwrap().join("SharedContent");
+        touch(shared_dir.join("Props/Crate.uasset"));
+
+        assert_eq!(
+            object_path_to_disk_path(
+                "/SharedContent/Props/Crate",
+                &layout.content_dir,
+                &layout.engine_content_dir,
+                &layout.plugins_dirs,
+                &[("SharedContent".to_string(), shared_dir.clone())],
+                DEFAULT_PLUGIN_SCAN_DEPTH,
+            )
+            .unwrap(),
+            shared_dir.join("Props/Crate.uasset")
+        );
+    }
+
+    #[test]
+    fn plugin_import_found_in_several_plugins_has_all_candidates() {
+        let layout = layout();
+
+        let candidates = object_path_candidates(
+            "/Shared/Common",
+            &layout.content_dir,
+            &layout.engine_content_dir,
+            &layout.plugins_dirs,
+            &[],
+            DEFAULT_PLUGIN_SCAN_DEPTH,
+        )
+        .unwrap();
+
+        assert_eq!(candidates.len(), 2);
+    }
+
+    #[test]
+    fn unknown_mount_point_fails() {
+        let layout = layout();
+
+        assert!(resolve(&layout, "/Unknown/Asset").is_err());
+    }
+
+    #[test]
+    fn disk_paths_convert_back_to_object_paths() {
+        let layout = layout();
+
+        for object_path in [
+            "/Game/Characters/Hero",
+            "/Engine/BasicShapes/Cube",
+            "/Abilities/Fireball",
+            "/Niagara/DefaultSystem",
+        ] {
+            let disk_path = resolve(&layout, object_path).unwrap();
+
+            assert_eq!(
+                disk_path_to_object_path(
+                    disk_path,
+                    &Some(&layout.content_dir),
+                    &layout.engine_content_dir,
+                    &layout.plugins_dirs,
+                )
+                .as_deref(),
+                Some(object_path)
+            );
+        }
+    }
+
+    #[test]
+    fn ellipsize_middle_keeps_the_file_name() {
+        assert_eq!(
+            ellipsize_middle("/Engine/Content/Very/Deep/Folder/Cube.uasset", 30),
+            "/Engine/Content/V…/Cube.uasset"
+        );
+    }
+
+    #[test]
+    fn ellipsize_middle_cuts_long_file_names_in_half() {
+        assert_eq!(ellipsize_middle("/Game/VeryLongFileName", 9), "/Gam…Name");
+    }
+
+    #[test]
+    fn ellipsize_middle_leaves_short_strings() {
+        assert_eq!(ellipsize_middle("/Game/Hero", 10), "/Game/Hero");
+    }
+
+    #[test]
+    fn normalize_path_strips_the_first_matching_root() {
+        let roots = [PathBuf::from("/Work/Project"), PathBuf::from("/Work")];
+
+        assert_eq!(
+            normalize_path("/Work/Project/Content/Hero.uasset", &roots),
+            "Content/Hero.uasset"
+        );
+        assert_eq!(
+            normalize_path("/Work/UE/Engine/Content/Cube.uasset", &roots),
+            "UE/Engine/Content/Cube.uasset"
+        );
+        assert_eq!(
+            normalize_path("/Other/Content/Hero.uasset", &roots),
+            "/Other/Content/Hero.uasset"
+        );
+    }
+
+    #[test]
+    fn normalize_path_uses_forward_slashes() {
+        assert_eq!(
+            normalize_path(r"C:\Project\Content\Hero.uasset", &[]),
+            "C:/Project/Content/Hero.uasset"
+        );
+    }
+
+    #[test]
+    fn normalize_path_matches_the_canonical_roots() {
+        let layout = layout();
+        let roots = [std::fs::canonicalize(layout.content_dir.parent().unwrap()).unwrap()];
+
+        assert_eq!(
+            normalize_path(
+                layout.content_dir.join("../Content/Characters/Hero.uasset"),
+                &roots
+            ),
+            "Content/Characters/Hero.uasset"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn normalize_path_follows_the_symlinked_project_dir() {
+        let layout = layout();
+        let link = layout._root.path().join("Link");
+        std::os::unix::fs::symlink(layout.content_dir.parent().unwrap(), &link).unwrap();
+        let roots = [std::fs::canonicalize(&link).unwrap()];
+
+        assert_eq!(
+            normalize_path(link.join("Content/Characters/Hero.uasset"), &roots),
+            "Content/Characters/Hero.uasset"
+        );
+        // The missing assets of the failures too
+        assert_eq!(
+            normalize_path(link.join("Content/Missing/Villain.uasset"), &roots),
+            "Content/Missing/Villain.uasset"
+        );
+    }
+
+    #[test]
+    fn workers_keep_the_order_of_every_batch() {
+        for jobs in [0, 1, 3, 16] {
+            let threads = std::sync::Mutex::new(std::collections::HashSet::new());
+
+            let batches = with_workers(
+                jobs,
+                |item: usize| {
+                    threads.lock().unwrap().insert(std::thread::current().id());
+                    item * 2
+                },
+                |workers| {
+                    [0..10, 10..11, 11..11, 11..40]
+                        .into_iter()
+                        .map(|batch| workers.map(batch.collect()))
+                        .collect::<Vec<_>>()
+                },
+            );
+
+            assert_eq!(
+                batches.concat(),
+                (0..40).map(|item| item * 2).collect::<Vec<_>>()
+            );
+            // The batches share the same workers
+            assert!(threads.lock().unwrap().len() <= jobs.max(1));
         }
     }
 }
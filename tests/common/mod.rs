@@ -0,0 +1,247 @@
+//! Synthetic packages and projects for the tests and the benchmarks, shared with `benches/build.rs`
+
+// Every test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use tempfile::TempDir;
+use uasset_helper::{
+    dependency_tree::{NodeID, TraversalOrder},
+    AssetDirs, DepTree,
+};
+
+/// `FPackageFileSummary::Tag`
+const PACKAGE_FILE_TAG: u32 = 0x9E2A83C1;
+/// UE 4.26+ packages
+const LEGACY_FILE_VERSION: i32 = -7;
+const FILE_VERSION_UE4: i32 = 522;
+/// Skips the editor only parts of the summary and the imports
+const PKG_FILTER_EDITOR_ONLY: u32 = 0x80000000;
+
+/// Writes the parts of the package header the parser reads, with an import for each dependency
+pub struct PackageWriter {
+    bytes: Vec<u8>,
+}
+
+impl PackageWriter {
+    fn i32(&mut self, value: i32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    /// ANSI string, the length includes the null terminator
+    fn fstring(&mut self, value: &str) {
+        self.i32(value.len() as i32 + 1);
+        self.bytes.extend(value.as_bytes());
+        self.bytes.push(0);
+    }
+
+    fn engine_version(&mut self) {
+        self.u16(4);
+        self.u16(27);
+        self.u16(0);
+        self.u32(0);
+        self.fstring("");
+    }
+
+    /// Overwrites the already written `i32` at the offset
+    fn patch_i32(&mut self, offset: usize, value: i32) {
+        self.bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn package(dependency_names: &[String]) -> Vec<u8> {
+        let names = ["None", "/Script/CoreUObject", "Package"]
+            .into_iter()
+            .map(str::to_string)
+            .chain(dependency_names.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let mut writer = Self { bytes: vec![] };
+
+        writer.u32(PACKAGE_FILE_TAG);
+        writer.i32(LEGACY_FILE_VERSION);
+        // UE3 version
+        writer.i32(864);
+        writer.i32(FILE_VERSION_UE4);
+        // Licensee version
+        writer.i32(0);
+        // Custom versions
+        writer.i32(0);
+        let total_header_size_offset = writer.bytes.len();
+        writer.i32(0);
+        writer.fstring("None");
+        writer.u32(PKG_FILTER_EDITOR_ONLY);
+        writer.i32(names.len() as i32);
+        let name_offset_offset = writer.bytes.len();
+        writer.i32(0);
+        // Gatherable text data count and offset
+        writer.i32(0);
+        writer.i32(0);
+        // Export count and offset
+        writer.i32(0);
+        writer.i32(0);
+        writer.i32(dependency_names.len() as i32);
+        let import_offset_offset = writer.bytes.len();
+        writer.i32(0);
+        // Depends offset
+        writer.i32(0);
+        // Soft package references count and offset
+        writer.i32(0);
+        writer.i32(0);
+        // Searchable names offset
+        writer.i32(0);
+        // Thumbnail table offset
+        writer.i32(0);
+        // Package guid
+        writer.bytes.extend([0; 16]);
+        // A single generation, with the export and name counts
+        writer.i32(1);
+        writer.i32(0);
+        writer.i32(names.len() as i32);
+        // Saved by and compatible with engine versions
+        writer.engine_version();
+        writer.engine_version();
+        // Compression flags and compressed chunks
+        writer.u32(0);
+        writer.i32(0);
+        // Package source
+        writer.u32(0);
+        // Additional packages to cook
+        writer.i32(0);
+        // Asset registry data offset
+        writer.i32(0);
+        // Bulk data start offset
+        writer.i64(0);
+        // World tile info data offset
+        writer.i32(0);
+        // Chunk ids
+        writer.i32(0);
+        // Preload dependency count and offset
+        writer.i32(0);
+        writer.i32(0);
+
+        let name_offset = writer.bytes.len() as i32;
+        writer.patch_i32(name_offset_offset, name_offset);
+        for name in &names {
+            writer.fstring(name);
+            // Non case preserving and case preserving hashes, not checked by the parser
+            writer.u16(0);
+            writer.u16(0);
+        }
+
+        let import_offset = writer.bytes.len() as i32;
+        writer.patch_i32(import_offset_offset, import_offset);
+        for index in 0..dependency_names.len() {
+            // Class package, class name, outer index and the object name, the names have no number suffixes
+            writer.u32(1);
+            writer.u32(0);
+            writer.u32(2);
+            writer.u32(0);
+            writer.i32(0);
+            writer.u32((3 + index) as u32);
+            writer.u32(0);
+        }
+
+        let total_header_size = writer.bytes.len() as i32;
+        writer.patch_i32(total_header_size_offset, total_header_size);
+
+        writer.bytes
+    }
+}
+
+/// Temporary project next to an engine, `Project/Content` and `UE/Engine/Content` under the same root
+pub struct Project {
+    _root: TempDir,
+    pub root_dir: PathBuf,
+    pub content_dir: PathBuf,
+    pub engine_dir: PathBuf,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        let root = tempfile::tempdir().unwrap();
+        let root_dir = root.path().to_path_buf();
+        let content_dir = root_dir.join("Project/Content");
+        let engine_dir = root_dir.join("UE");
+
+        std::fs::create_dir_all(&content_dir).unwrap();
+        std::fs::create_dir_all(engine_dir.join("Engine/Content")).unwrap();
+
+        Self {
+            _root: root,
+            root_dir,
+            content_dir,
+            engine_dir,
+        }
+    }
+
+    /// Writes the package at the path relative to the root, importing the object paths
+    pub fn write(&self, relative_path: &str, dependency_names: &[&str]) -> PathBuf {
+        let path = self.root_dir.join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let dependency_names = dependency_names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        std::fs::write(&path, PackageWriter::package(&dependency_names)).unwrap();
+
+        path
+    }
+
+    /// Writes `Project/Content/<name>.uasset` importing the `/Game/<dependency>` assets
+    pub fn asset(&self, name: &str, dependencies: &[&str]) -> PathBuf {
+        let dependency_names = dependencies
+            .iter()
+            .map(|dependency| format!("/Game/{}", dependency))
+            .collect::<Vec<_>>();
+
+        self.write(
+            &format!("Project/Content/{}.uasset", name),
+            &dependency_names
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn asset_dirs(&self, root: &Path) -> AssetDirs {
+        AssetDirs::new(Some(root.to_path_buf()), Some(self.engine_dir.clone()))
+    }
+
+    pub fn build(&self, root: &Path, max_recurse_depth: u32) -> DepTree {
+        DepTree::build(
+            &self.asset_dirs(root),
+            max_recurse_depth,
+            TraversalOrder::default(),
+            None,
+            None,
+        )
+        .unwrap()
+    }
+}
+
+/// Id of the node of the asset with the file name (without the extension)
+pub fn node_id(dep_tree: &DepTree, name: &str) -> NodeID {
+    dep_tree
+        .nodes
+        .iter()
+        .find(|(_, node)| node.path.file_stem() == Some(OsStr::new(name)))
+        .map(|(&node_id, _)| node_id)
+        .unwrap_or_else(|| panic!("{} isn't in the tree", name))
+}
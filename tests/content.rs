@@ -0,0 +1,46 @@
+//! Scans of the whole content directory of small synthetic projects, not only of the trees of their assets
+
+mod common;
+
+use uasset_helper::dependency_tree::{find_duplicate_names, find_orphans};
+
+use common::Project;
+
+#[test]
+fn orphans_are_the_assets_nothing_references_besides_the_levels() {
+    let project = Project::new();
+
+    project.write("Project/Content/Maps/Level.umap", &["/Game/A"]);
+    let a = project.asset("A", &["B"]);
+    project.asset("B", &[]);
+    project.asset("C", &[]);
+    let d = project.asset("D", &["C"]);
+    let e = project.asset("E", &[]);
+
+    // The level isn't referenced either, but it's loaded by the game
+    assert_eq!(find_orphans(&project.asset_dirs(&a), None), vec![d, e]);
+}
+
+#[test]
+fn duplicate_names_are_grouped_case_insensitively_the_most_used_first() {
+    let project = Project::new();
+
+    let rock = project.write("Project/Content/Props/Rock.uasset", &[]);
+    let rock_level = project.write("Project/Content/Maps/rock.umap", &[]);
+    let tree = project.write("Project/Content/Props/Tree.uasset", &[]);
+    let tree_copy = project.write("Project/Content/Old/Tree.uasset", &[]);
+    let tree_backup = project.write("Project/Content/Backup/TREE.uasset", &[]);
+    project.write("Project/Content/Props/Unique.uasset", &[]);
+
+    let duplicates = find_duplicate_names(&project.content_dir);
+
+    assert_eq!(duplicates.len(), 2);
+
+    let (name, paths) = &duplicates[0];
+    assert!(name.eq_ignore_ascii_case("tree"));
+    assert_eq!(paths, &vec![tree_backup, tree_copy, tree]);
+
+    let (name, paths) = &duplicates[1];
+    assert!(name.eq_ignore_ascii_case("rock"));
+    assert_eq!(paths, &vec![rock_level, rock]);
+}
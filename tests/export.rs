@@ -0,0 +1,255 @@
+//! Locks down the exports of tiny synthetic projects: the node and edge statements of the graphviz one,
+//! their ids and that `dot` can render it, and the tables and rows of the SQLite one
+
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use graphviz_rust::{
+    cmd::{CommandArg, Format},
+    dot_structures::{EdgeTy, Graph, Id, Stmt, Vertex},
+    exec,
+    printer::{DotPrinter, PrinterContext},
+};
+use uasset_helper::{
+    dependency_tree::{export_sqlite, TraversalOrder, UNLIMITED_RECURSE_DEPTH},
+    AssetDirs, DepTree,
+};
+
+use common::{node_id, PackageWriter, Project};
+
+fn plain_id(id: &Id) -> String {
+    match id {
+        Id::Plain(id) | Id::Escaped(id) | Id::Html(id) | Id::Anonymous(id) => id.clone(),
+    }
+}
+
+/// `Root` depends on `Prop` and `Texture`, `Prop` on `Texture`, and `Root` on a `Missing` asset
+fn build_tree() -> (tempfile::TempDir, DepTree) {
+    let root = tempfile::tempdir().unwrap();
+    let assets_dir = root.path().join("Project/Content/Test");
+    std::fs::create_dir_all(&assets_dir).unwrap();
+
+    for (name, dependencies) in [
+        (
+            "Root",
+            &[
+                "/Game/Test/Prop",
+                "/Game/Test/Texture",
+                "/Game/Test/Missing",
+            ][..],
+        ),
+        ("Prop", &["/Game/Test/Texture"][..]),
+        ("Texture", &[][..]),
+    ] {
+        let dependency_names = dependencies
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+
+        std::fs::write(
+            assets_dir.join(format!("{}.uasset", name)),
+            PackageWriter::package(&dependency_names),
+        )
+        .unwrap();
+    }
+
+    let asset_dirs = AssetDirs::new(Some(assets_dir.join("Root.uasset")), None);
+    let dep_tree = DepTree::build(
+        &asset_dirs,
+        UNLIMITED_RECURSE_DEPTH,
+        TraversalOrder::default(),
+        None,
+        None,
+    )
+    .unwrap();
+
+    (root, dep_tree)
+}
+
+#[test]
+fn graph_export_has_the_nodes_and_edges_of_the_tree() {
+    let (_root, dep_tree) = build_tree();
+
+    assert_eq!(dep_tree.nodes.len(), 3);
+    assert_eq!(dep_tree.real_failures().count(), 1);
+
+    let graph_ids = dep_tree
+        .nodes
+        .iter()
+        .map(|(node_id, node)| {
+            (
+                node.path.file_stem().unwrap().to_string_lossy().to_string(),
+                format!("node_{}", node_id),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let graph = Graph::from(dep_tree);
+    let Graph::DiGraph { stmts, strict, .. } = &graph else {
+        panic!("The dependency graph should be directed");
+    };
+    assert!(strict);
+
+    let nodes = stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Node(node) => Some(plain_id(&node.id.0)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let edges = stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Edge(edge) => match &edge.ty {
+                EdgeTy::Pair(Vertex::N(from), Vertex::N(to)) => {
+                    Some((plain_id(&from.0), plain_id(&to.0)))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    // Every node once, with the ids the edges refer to
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(
+        nodes.iter().collect::<HashSet<_>>(),
+        graph_ids.values().collect::<HashSet<_>>()
+    );
+    assert!(nodes.iter().all(|id| id
+        .strip_prefix("node_")
+        .map(|number| number.parse::<u64>().is_ok())
+        .unwrap_or_default()));
+
+    let edge = |from: &str, to: &str| (graph_ids[from].clone(), graph_ids[to].clone());
+    assert_eq!(
+        edges,
+        HashSet::from([
+            edge("Root", "Prop"),
+            edge("Root", "Texture"),
+            edge("Prop", "Texture"),
+        ])
+    );
+
+    let dot = graph.print(&mut PrinterContext::default());
+    assert!(dot.starts_with("strict digraph"));
+    for name in ["Root.uasset", "Prop.uasset", "Texture.uasset"] {
+        assert!(dot.contains(name), "{} is missing from the dot", name);
+    }
+    assert!(!dot.contains("Missing"));
+
+    // Rendering needs the graphviz binaries, which aren't installed everywhere
+    if std::process::Command::new("dot")
+        .arg("-V")
+        .output()
+        .is_err()
+    {
+        eprintln!("dot isn't installed, skipping the svg render");
+
+        return;
+    }
+
+    let svg = exec(
+        graph,
+        &mut PrinterContext::default(),
+        vec![CommandArg::Format(Format::Svg)],
+    )
+    .unwrap();
+    assert!(svg.contains("<svg"));
+}
+
+/// Rows of the query, with the columns read by `row`
+fn query<T>(
+    connection: &rusqlite::Connection,
+    sql: &str,
+    row: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> Vec<T> {
+    connection
+        .prepare(sql)
+        .unwrap()
+        .query_map([], row)
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn sqlite_export_has_the_nodes_edges_and_failures_of_the_tree() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B", "Missing"]);
+    project.asset("B", &[]);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    let roots = project.asset_dirs(&root).export_roots();
+    let db_path = project.root_dir.join("tree.db");
+
+    export_sqlite(&dep_tree, &db_path, &roots).unwrap();
+    // The database is replaced, not appended to
+    export_sqlite(&dep_tree, &db_path, &roots).unwrap();
+
+    let connection = rusqlite::Connection::open(&db_path).unwrap();
+
+    assert_eq!(
+        query(
+            &connection,
+            "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name",
+            |row| row.get::<_, String>(0),
+        ),
+        vec!["edges", "failures", "nodes"]
+    );
+
+    let (a, b) = (
+        node_id(&dep_tree, "A") as i64,
+        node_id(&dep_tree, "B") as i64,
+    );
+
+    assert_eq!(
+        query(
+            &connection,
+            "SELECT id, path, origin, class, depth FROM nodes ORDER BY id",
+            |row| Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, i64>(4)?,
+            )),
+        ),
+        vec![
+            (
+                a,
+                "Content/A.uasset".to_string(),
+                "Project".to_string(),
+                None,
+                0
+            ),
+            (
+                b,
+                "Content/B.uasset".to_string(),
+                "Project".to_string(),
+                None,
+                1
+            ),
+        ]
+    );
+    assert_eq!(
+        query(
+            &connection,
+            "SELECT parent_id, child_id, kind FROM edges",
+            |row| Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            )),
+        ),
+        vec![(a, b, "hard".to_string())]
+    );
+    assert_eq!(
+        query(&connection, "SELECT parent_id, path FROM failures", |row| {
+            Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, String>(1)?))
+        },),
+        vec![(Some(a), "Content/Missing.uasset".to_string())]
+    );
+}
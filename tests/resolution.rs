@@ -0,0 +1,143 @@
+//! Resolution of the imports of a package to the asset files, by the mount points of their object paths
+
+mod common;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use uasset_helper::{
+    asset::{AssetErrorKind, EdgeKind, DEFAULT_PLUGIN_SCAN_DEPTH},
+    Asset,
+};
+
+use common::Project;
+
+/// Project with an asset, an engine asset and a project plugin asset, and a level importing them
+/// along with the references that can't be resolved
+fn project() -> (Project, Asset) {
+    let project = Project::new();
+
+    project.asset("Props/Chair", &[]);
+    project.asset("Ignored/Rock", &[]);
+    project.write("UE/Engine/Content/BasicShapes/Cube.uasset", &[]);
+    project.write("Project/Plugins/Forest/Content/Trees/Oak.uasset", &[]);
+
+    let level = project.write(
+        "Project/Content/Maps/Level.umap",
+        &[
+            "/Game/Props/Chair",
+            "/Game/Props/Missing",
+            "/Game/Ignored/Rock",
+            "/Engine/BasicShapes/Cube",
+            "/Engine/BasicShapes/Sphere",
+            "/Script/Engine",
+            "/Forest/Trees/Oak",
+            "/Unknown/Thing",
+        ],
+    );
+
+    let asset = Asset::new(level).unwrap();
+
+    (project, asset)
+}
+
+fn resolve(
+    project: &Project,
+    asset: &Asset,
+    skip_engine: bool,
+) -> (HashSet<PathBuf>, HashMap<PathBuf, AssetErrorKind>) {
+    let asset_dirs = project.asset_dirs(&asset.path);
+
+    let (dep_paths, fails) = asset.get_dependency_asset_paths(
+        &project.content_dir,
+        &asset_dirs.engine_content_dir,
+        &asset_dirs.plugins_dirs,
+        &[],
+        DEFAULT_PLUGIN_SCAN_DEPTH,
+        skip_engine,
+        &[glob::Pattern::new("/Game/Ignored/*").unwrap()],
+        false,
+    );
+
+    assert!(dep_paths
+        .iter()
+        .all(|(_, edge_kind)| *edge_kind == EdgeKind::Hard));
+
+    (
+        dep_paths
+            .into_iter()
+            .map(|(dep_path, _)| dep_path)
+            .collect(),
+        fails
+            .into_iter()
+            .map(|fail| (fail.path, fail.kind))
+            .collect(),
+    )
+}
+
+#[test]
+fn imports_resolve_by_their_mount_points() {
+    let (project, asset) = project();
+    let engine_content_dir = project.engine_dir.join("Engine/Content");
+
+    let (dep_paths, fails) = resolve(&project, &asset, false);
+
+    assert_eq!(
+        dep_paths,
+        HashSet::from([
+            project.content_dir.join("Props/Chair.uasset"),
+            engine_content_dir.join("BasicShapes/Cube.uasset"),
+            project
+                .root_dir
+                .join("Project/Plugins/Forest/Content/Trees/Oak.uasset"),
+        ])
+    );
+    assert_eq!(
+        fails,
+        HashMap::from([
+            (
+                project.content_dir.join("Props/Missing.uasset"),
+                AssetErrorKind::MissingInContent
+            ),
+            (PathBuf::from("/Game/Ignored/Rock"), AssetErrorKind::Ignored),
+            (
+                engine_content_dir.join("BasicShapes/Sphere.uasset"),
+                AssetErrorKind::MissingInEngineContent
+            ),
+            (
+                PathBuf::from("/Script/Engine.uasset"),
+                AssetErrorKind::ScriptReference
+            ),
+            (
+                PathBuf::from("/Unknown/Thing.uasset"),
+                AssetErrorKind::NotInPlugins
+            ),
+        ])
+    );
+}
+
+#[test]
+fn engine_imports_are_skipped_without_being_looked_up() {
+    let (project, asset) = project();
+
+    let (dep_paths, fails) = resolve(&project, &asset, true);
+
+    assert!(dep_paths
+        .iter()
+        .all(|dep_path| !dep_path.starts_with(&project.engine_dir)));
+    assert_eq!(
+        fails.get(&PathBuf::from("/Engine/BasicShapes/Cube")),
+        Some(&AssetErrorKind::EngineSkipped)
+    );
+    assert_eq!(
+        fails.get(&PathBuf::from("/Engine/BasicShapes/Sphere")),
+        Some(&AssetErrorKind::EngineSkipped)
+    );
+    // The rest is resolved as usual
+    assert_eq!(
+        fails.get(&PathBuf::from("/Script/Engine.uasset")),
+        Some(&AssetErrorKind::ScriptReference)
+    );
+}
@@ -0,0 +1,517 @@
+//! Behavior of the built trees and the trees derived from them, over small synthetic projects
+
+mod common;
+
+use std::collections::HashSet;
+
+use uasset_helper::{
+    dependency_tree::{find_disabled_plugins, TraversalOrder, UNLIMITED_RECURSE_DEPTH},
+    AssetDirs, DepTree,
+};
+
+use common::{node_id, Project};
+
+fn name(dep_tree: &DepTree, node_id: u64) -> String {
+    dep_tree.nodes[&node_id]
+        .path
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Node names of the tree
+fn names(dep_tree: &DepTree) -> HashSet<String> {
+    dep_tree
+        .nodes
+        .keys()
+        .map(|&node_id| name(dep_tree, node_id))
+        .collect()
+}
+
+/// Edges of the tree by the names of the nodes
+fn edges(dep_tree: &DepTree) -> HashSet<(String, String)> {
+    dep_tree
+        .edges()
+        .map(|(from, to)| (name(dep_tree, from), name(dep_tree, to)))
+        .collect()
+}
+
+fn set<const N: usize>(items: [&str; N]) -> HashSet<String> {
+    items.into_iter().map(str::to_string).collect()
+}
+
+fn edge_set<const N: usize>(items: [(&str, &str); N]) -> HashSet<(String, String)> {
+    items
+        .into_iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect()
+}
+
+/// `A` depends on `B` and `C`, both of them on `D`, and `A` on a missing asset too
+fn diamond() -> (Project, DepTree) {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B", "C", "Missing"]);
+    project.asset("B", &["D"]);
+    project.asset("C", &["D"]);
+    project.asset("D", &[]);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+
+    (project, dep_tree)
+}
+
+#[test]
+fn subtree_of_a_diamond_keeps_the_shared_dependency_once() {
+    let (_project, dep_tree) = diamond();
+
+    let subtree = dep_tree
+        .subtree(dep_tree.root_node_id, UNLIMITED_RECURSE_DEPTH)
+        .unwrap();
+    assert_eq!(names(&subtree), set(["A", "B", "C", "D"]));
+    assert_eq!(
+        edges(&subtree),
+        edge_set([("A", "B"), ("A", "C"), ("B", "D"), ("C", "D")])
+    );
+    assert_eq!(subtree.real_failures().count(), 1);
+
+    // The failure is only referenced from outside of the subtree of `B`
+    let subtree = dep_tree
+        .subtree(node_id(&dep_tree, "B"), UNLIMITED_RECURSE_DEPTH)
+        .unwrap();
+    assert_eq!(subtree.root_node_id, node_id(&dep_tree, "B"));
+    assert_eq!(names(&subtree), set(["B", "D"]));
+    assert_eq!(edges(&subtree), edge_set([("B", "D")]));
+    assert_eq!(subtree.real_failures().count(), 0);
+}
+
+#[test]
+fn subtree_of_a_cycle_ends() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B"]);
+    project.asset("B", &["C"]);
+    project.asset("C", &["B"]);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(
+        edges(&dep_tree),
+        edge_set([("A", "B"), ("B", "C"), ("C", "B")])
+    );
+
+    let subtree = dep_tree
+        .subtree(node_id(&dep_tree, "B"), UNLIMITED_RECURSE_DEPTH)
+        .unwrap();
+    assert_eq!(names(&subtree), set(["B", "C"]));
+    assert_eq!(edges(&subtree), edge_set([("B", "C"), ("C", "B")]));
+}
+
+#[test]
+fn subtree_is_cut_at_the_depth() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B"]);
+    project.asset("B", &["C"]);
+    project.asset("C", &["D"]);
+    project.asset("D", &[]);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+
+    let subtree = dep_tree.subtree(dep_tree.root_node_id, 2).unwrap();
+    assert_eq!(names(&subtree), set(["A", "B", "C"]));
+    assert_eq!(edges(&subtree), edge_set([("A", "B"), ("B", "C")]));
+    assert_eq!(subtree.max_recurse_depth, 2);
+    assert_eq!(subtree.recurse_depths[&node_id(&dep_tree, "C")], 2);
+
+    // Depths are counted from the new root
+    let subtree = dep_tree.subtree(node_id(&dep_tree, "B"), 1).unwrap();
+    assert_eq!(names(&subtree), set(["B", "C"]));
+    assert_eq!(subtree.recurse_depths[&node_id(&dep_tree, "B")], 0);
+
+    assert!(dep_tree.subtree(u64::MAX, 1).is_none());
+}
+
+#[test]
+fn diff_reports_the_added_and_removed_nodes_and_edges() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B", "Shared"]);
+    project.asset("B", &[]);
+    project.asset("C", &[]);
+    project.asset("Shared", &[]);
+    let before = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+
+    project.asset("A", &["C", "Shared"]);
+    let after = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+
+    let path = |name: &str| project.content_dir.join(format!("{}.uasset", name));
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.added_nodes, vec![path("C")]);
+    assert_eq!(diff.removed_nodes, vec![path("B")]);
+    assert_eq!(diff.added_edges, vec![(path("A"), path("C"))]);
+    assert_eq!(diff.removed_edges, vec![(path("A"), path("B"))]);
+
+    assert!(after.diff(&after).is_empty());
+}
+
+#[test]
+fn retrying_the_failures_turns_the_resolvable_ones_into_nodes() {
+    let project = Project::new();
+
+    let root = project.write(
+        "Project/Content/A.uasset",
+        &["/Forest/Trees/Oak", "/Game/Missing"],
+    );
+    let asset_dirs = project.asset_dirs(&root);
+
+    let mut dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(names(&dep_tree), set(["A"]));
+    assert_eq!(dep_tree.real_failures().count(), 2);
+
+    // The plugin shows up after the build
+    project.write("Project/Plugins/Forest/Content/Trees/Oak.uasset", &[]);
+
+    assert_eq!(dep_tree.retry_failures(&asset_dirs).unwrap(), 1);
+    assert_eq!(names(&dep_tree), set(["A", "Oak"]));
+    assert_eq!(edges(&dep_tree), edge_set([("A", "Oak")]));
+
+    let missing_path = project.content_dir.join("Missing.uasset");
+    assert_eq!(
+        dep_tree
+            .real_failures()
+            .map(|failure| &failure.path)
+            .collect::<Vec<_>>(),
+        vec![&missing_path]
+    );
+    assert!(dep_tree.retried_failures.contains(&missing_path));
+}
+
+#[test]
+fn collapsing_an_engine_plugin_leaves_a_single_node_for_it() {
+    let project = Project::new();
+
+    let root = project.write(
+        "Project/Content/A.uasset",
+        &["/Game/B", "/Niagara/Systems/Fire", "/Niagara/Systems/Smoke"],
+    );
+    project.asset("B", &[]);
+    project.write(
+        "UE/Engine/Plugins/FX/Niagara/Content/Systems/Fire.uasset",
+        &["/Niagara/Systems/Smoke"],
+    );
+    project.write(
+        "UE/Engine/Plugins/FX/Niagara/Content/Systems/Smoke.uasset",
+        &[],
+    );
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(names(&dep_tree), set(["A", "B", "Fire", "Smoke"]));
+
+    let collapsed = dep_tree.collapse_engine_plugins();
+    assert_eq!(collapsed.nodes.len(), 3);
+    assert_eq!(collapsed.aggregated.len(), 1);
+
+    let (&plugin_node_id, plugin_node) = collapsed.aggregated.iter().next().unwrap();
+    assert_eq!(plugin_node.name, "Niagara");
+    assert_eq!(plugin_node.node_count, 2);
+    // Only the edges from the outside, the one between its assets is gone
+    assert_eq!(plugin_node.edge_count, 2);
+
+    let root_node_id = collapsed.root_node_id;
+    let b_node_id = node_id(&dep_tree, "B");
+    assert_eq!(
+        collapsed.edges().collect::<HashSet<_>>(),
+        HashSet::from([(root_node_id, b_node_id), (root_node_id, plugin_node_id)])
+    );
+    assert_eq!(collapsed.parents(plugin_node_id), vec![root_node_id]);
+
+    // The project assets aren't grouped
+    assert!(dep_tree
+        .aggregate_by(|asset| asset.plugin_name())
+        .aggregated
+        .values()
+        .all(|aggregated_node| aggregated_node.name == "Niagara"));
+}
+
+#[test]
+fn shared_dependencies_have_all_their_parents() {
+    let (_project, dep_tree) = diamond();
+
+    let [a, b, c, d] = ["A", "B", "C", "D"].map(|name| node_id(&dep_tree, name));
+
+    assert_eq!(
+        dep_tree.parents(d).into_iter().collect::<HashSet<_>>(),
+        HashSet::from([b, c])
+    );
+    assert!(dep_tree
+        .parents(d)
+        .contains(&dep_tree.get_parent_node_id(d).unwrap()));
+    assert_eq!(dep_tree.parents(b), vec![a]);
+    assert!(dep_tree.parents(a).is_empty());
+
+    let path_to_root = dep_tree.path_to_root(d);
+    assert_eq!(path_to_root.len(), 3);
+    assert_eq!(path_to_root.first(), Some(&a));
+    assert!([b, c].contains(&path_to_root[1]));
+    assert_eq!(path_to_root.last(), Some(&d));
+
+    assert_eq!(dep_tree.path_to_root(a), vec![a]);
+    assert!(dep_tree.path_to_root(u64::MAX).is_empty());
+}
+
+#[test]
+fn unrelated_roots_are_separate_components() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B", "Shared"]);
+    project.asset("B", &[]);
+    project.asset("Shared", &[]);
+    let other_root = project.asset("X", &["Y"]);
+    project.asset("Y", &[]);
+    // Reached from the first root already, so it's not a component of its own
+    let shared_root = project.content_dir.join("Shared.uasset");
+
+    let dep_tree = DepTree::build_combined(
+        &project.asset_dirs(&root),
+        &[root, other_root, shared_root],
+        UNLIMITED_RECURSE_DEPTH,
+        TraversalOrder::default(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(dep_tree.extra_root_node_ids.len(), 1);
+
+    let components = dep_tree.components();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0][0], dep_tree.root_node_id);
+
+    let component_names = components
+        .iter()
+        .map(|component| names(&dep_tree.component(component)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        component_names,
+        vec![set(["A", "B", "Shared"]), set(["X", "Y"])]
+    );
+
+    let other_component = dep_tree.component(&components[1]);
+    assert_eq!(name(&other_component, other_component.root_node_id), "X");
+    assert_eq!(edges(&other_component), edge_set([("X", "Y")]));
+}
+
+#[test]
+fn rebuilding_a_node_drops_what_it_no_longer_references() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B", "Missing"]);
+    project.asset("B", &["D"]);
+    project.asset("C", &[]);
+    project.asset("D", &[]);
+    let asset_dirs = project.asset_dirs(&root);
+
+    let mut dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(names(&dep_tree), set(["A", "B", "D"]));
+    assert_eq!(dep_tree.real_failures().count(), 1);
+
+    project.asset("A", &["C"]);
+    dep_tree
+        .rebuild_node(dep_tree.root_node_id, &asset_dirs, None)
+        .unwrap();
+
+    assert_eq!(names(&dep_tree), set(["A", "C"]));
+    assert_eq!(edges(&dep_tree), edge_set([("A", "C")]));
+    assert_eq!(dep_tree.real_failures().count(), 0);
+    assert!(dep_tree.failure_parents.is_empty());
+    assert_eq!(
+        dep_tree.parents(node_id(&dep_tree, "C")),
+        vec![dep_tree.root_node_id]
+    );
+
+    // The failures shared with the other nodes keep their parents
+    project.asset("A", &["C", "E", "Missing"]);
+    project.asset("E", &["Missing"]);
+    dep_tree
+        .rebuild_node(dep_tree.root_node_id, &asset_dirs, None)
+        .unwrap();
+    let missing = dep_tree.real_failures().next().unwrap().clone();
+    assert_eq!(
+        dep_tree.failure_parents(&missing),
+        vec![dep_tree.root_node_id, node_id(&dep_tree, "E")]
+    );
+
+    project.asset("A", &["C", "E"]);
+    dep_tree
+        .rebuild_node(dep_tree.root_node_id, &asset_dirs, None)
+        .unwrap();
+    assert_eq!(names(&dep_tree), set(["A", "C", "E"]));
+    assert_eq!(dep_tree.real_failures().count(), 1);
+    assert_eq!(
+        dep_tree.failure_parents(&missing),
+        vec![node_id(&dep_tree, "E")]
+    );
+
+    // And the truncated connections stay cut when rebuilding
+    let asset_dirs = AssetDirs {
+        max_children: Some(1),
+        ..asset_dirs
+    };
+    dep_tree
+        .rebuild_node(dep_tree.root_node_id, &asset_dirs, None)
+        .unwrap();
+    assert_eq!(names(&dep_tree), set(["A", "C"]));
+    assert_eq!(edges(&dep_tree), edge_set([("A", "C")]));
+    assert_eq!(dep_tree.truncated[&dep_tree.root_node_id], 1);
+    assert_eq!(dep_tree.real_failures().count(), 0);
+}
+
+#[test]
+fn depth_zero_is_only_the_root_and_unlimited_reaches_every_node() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B"]);
+    project.asset("B", &["C"]);
+    project.asset("C", &["D"]);
+    project.asset("D", &[]);
+
+    let dep_tree = project.build(&root, 0);
+    assert_eq!(names(&dep_tree), set(["A"]));
+    assert_eq!(dep_tree.edges().count(), 0);
+    assert_eq!(dep_tree.failures.len(), 0);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(names(&dep_tree), set(["A", "B", "C", "D"]));
+    assert_eq!(
+        edges(&dep_tree),
+        edge_set([("A", "B"), ("B", "C"), ("C", "D")])
+    );
+    assert_eq!(dep_tree.recurse_depths[&node_id(&dep_tree, "D")], 3);
+}
+
+#[test]
+fn assets_are_older_than_the_newer_engine_versions_only() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B"]);
+    project.asset("B", &[]);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(dep_tree.nodes.len(), 2);
+
+    // The synthetic packages are saved with 4.27
+    for (version, is_older) in [
+        ((5, 1), true),
+        ((4, 28), true),
+        ((4, 27), false),
+        ((4, 0), false),
+    ] {
+        assert!(
+            dep_tree
+                .nodes
+                .values()
+                .all(|node| node.is_older_than(version) == is_older),
+            "{:?}",
+            version
+        );
+    }
+}
+
+#[test]
+fn ascii_tree_prints_the_shared_dependencies_once() {
+    let project = Project::new();
+
+    let root = project.asset("A", &["B", "C"]);
+    project.asset("B", &["D"]);
+    project.asset("C", &["D"]);
+    project.asset("D", &[]);
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    let id = |name| node_id(&dep_tree, name);
+
+    assert_eq!(
+        dep_tree.ascii_tree(),
+        format!(
+            "{} - A.uasset\n├── {} - B.uasset\n│   └── {} - D.uasset (2 parents)\n└── {} - C.uasset\n    └── {} - D.uasset (seen)\n",
+            id("A"),
+            id("B"),
+            id("D"),
+            id("C"),
+            id("D")
+        )
+    );
+}
+
+#[test]
+fn plugins_not_enabled_in_the_project_are_reported_with_their_assets() {
+    let project = Project::new();
+
+    let descriptor = |relative_path: &str, contents: &str| {
+        std::fs::write(project.root_dir.join(relative_path), contents).unwrap();
+    };
+
+    project.write("Project/Plugins/Off/Content/Rock.uasset", &[]);
+    descriptor("Project/Plugins/Off/Off.uplugin", "{}");
+    project.write("Project/Plugins/On/Content/Tree.uasset", &[]);
+    descriptor("Project/Plugins/On/On.uplugin", "{}");
+    project.write("Project/Plugins/Unlisted/Content/Bush.uasset", &[]);
+    descriptor(
+        "Project/Plugins/Unlisted/Unlisted.uplugin",
+        r#"{ "EnabledByDefault": false }"#,
+    );
+    // Project plugins are enabled by default, unlike the engine ones
+    project.write("Project/Plugins/Default/Content/Grass.uasset", &[]);
+    project.write(
+        "UE/Engine/Plugins/FX/Niagara/Content/Systems/Fire.uasset",
+        &[],
+    );
+    descriptor("UE/Engine/Plugins/FX/Niagara/Niagara.uplugin", "{}");
+    descriptor(
+        "Project/Project.uproject",
+        r#"{ "Plugins": [{ "Name": "Off", "Enabled": false }, { "Name": "on", "Enabled": true }] }"#,
+    );
+
+    let root = project.write(
+        "Project/Content/Maps/Level.umap",
+        &[
+            "/Off/Rock",
+            "/On/Tree",
+            "/Unlisted/Bush",
+            "/Default/Grass",
+            "/Niagara/Systems/Fire",
+        ],
+    );
+
+    let dep_tree = project.build(&root, UNLIMITED_RECURSE_DEPTH);
+    assert_eq!(dep_tree.nodes.len(), 6);
+
+    let disabled_plugins = find_disabled_plugins(&dep_tree, &project.asset_dirs(&root)).unwrap();
+
+    assert_eq!(
+        disabled_plugins
+            .iter()
+            .map(|plugin| (
+                plugin.name.as_str(),
+                plugin.reason.as_str(),
+                plugin.node_ids.clone()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "Niagara",
+                "not listed in the .uproject and not enabled by default",
+                vec![node_id(&dep_tree, "Fire")]
+            ),
+            (
+                "Off",
+                "disabled in the .uproject",
+                vec![node_id(&dep_tree, "Rock")]
+            ),
+            (
+                "Unlisted",
+                "not listed in the .uproject and not enabled by default",
+                vec![node_id(&dep_tree, "Bush")]
+            ),
+        ]
+    );
+}